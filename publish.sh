@@ -5,4 +5,6 @@
     sleep 25 &&
     (cd schemafy_lib && cargo publish) &&
     sleep 25 &&
+    (cd schemafy_build && cargo publish) &&
+    sleep 25 &&
     cargo publish)
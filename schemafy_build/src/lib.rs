@@ -0,0 +1,202 @@
+//! Generates the types of JSON schemas from a build script, as an
+//! alternative to the `schemafy!` macro which keeps the generated code
+//! in a file that IDEs and `rustc` do not need to expand on every build.
+//!
+//! ```no_run
+//! // In `main` of build.rs
+//! schemafy_build::Generator::new()
+//!     .schema("api.json")
+//!     .root("Api")
+//!     .write_to_out_dir("api.rs")
+//!     .unwrap();
+//! ```
+//!
+//! The generated file is then included in the crate, which needs to
+//! depend on `serde` and `schemafy_core`:
+//!
+//! ```ignore
+//! include!(concat!(env!("OUT_DIR"), "/api.rs"));
+//! ```
+//!
+//! Cargo is told to run the build script again whenever one of the
+//! schemas changes.
+
+use std::{
+    env, fmt,
+    path::{Path, PathBuf},
+};
+
+pub use schemafy_lib::{Error, GeneratorBuilder};
+
+type Configure = Box<dyn for<'a, 'b> Fn(GeneratorBuilder<'a, 'b>) -> GeneratorBuilder<'a, 'b>>;
+
+/// Generates the types of one or more JSON schema files into a Rust file.
+#[derive(Default)]
+#[must_use]
+pub struct Generator {
+    schemas: Vec<PathBuf>,
+    root: Option<String>,
+    configure: Vec<Configure>,
+}
+
+impl fmt::Debug for Generator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Generator")
+            .field("schemas", &self.schemas)
+            .field("root", &self.root)
+            .finish()
+    }
+}
+
+impl Generator {
+    pub fn new() -> Self {
+        Generator::default()
+    }
+
+    /// Adds a schema file, relative to the directory of the crate's
+    /// `Cargo.toml`. With several schemas the types of each are put in a
+    /// module named after its file (see `GeneratorBuilder::with_input_files`).
+    pub fn schema<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.schemas.push(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// The name of the type generated for the root of the schema.
+    pub fn root(mut self, name: &str) -> Self {
+        self.root = Some(name.to_string());
+        self
+    }
+
+    /// Sets any other option of the underlying generator.
+    ///
+    /// ```no_run
+    /// schemafy_build::Generator::new()
+    ///     .schema("api.json")
+    ///     .configure(|builder| builder.with_derive("Eq").with_validate(true))
+    ///     .write_to_out_dir("api.rs")
+    ///     .unwrap();
+    /// ```
+    pub fn configure<F>(mut self, configure: F) -> Self
+    where
+        F: for<'a, 'b> Fn(GeneratorBuilder<'a, 'b>) -> GeneratorBuilder<'a, 'b> + 'static,
+    {
+        self.configure.push(Box::new(configure));
+        self
+    }
+
+    fn build(&self) -> schemafy_lib::Generator<'_, '_> {
+        let mut builder = schemafy_lib::Generator::builder().with_root_name(self.root.clone());
+        builder = match &self.schemas[..] {
+            [schema] => builder.with_input_file(schema),
+            schemas => builder.with_input_files(schemas),
+        };
+        for configure in &self.configure {
+            builder = configure(builder);
+        }
+        builder.build()
+    }
+
+    /// Generates the source of the types.
+    pub fn generate(&self) -> Result<String, Error> {
+        self.build().try_generate_source()
+    }
+
+    fn rerun_if_changed(&self) {
+        for schema in &self.schemas {
+            println!("cargo:rerun-if-changed={}", schema.display());
+        }
+    }
+
+    /// Writes the generated types to `path`, printing
+    /// `cargo:rerun-if-changed` for every schema.
+    pub fn write_to<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        self.rerun_if_changed();
+        let source = self.generate()?;
+        let path = path.as_ref();
+        std::fs::write(path, source).map_err(|err| Error::Write(path.to_path_buf(), err))
+    }
+
+    /// Writes the generated types to `file_name` in the `OUT_DIR` of the
+    /// build script, returning the path of the file.
+    pub fn write_to_out_dir<P: AsRef<Path>>(&self, file_name: P) -> Result<PathBuf, Error> {
+        let out_dir = env::var_os("OUT_DIR").expect("`OUT_DIR` is only set for build scripts");
+        let path = Path::new(&out_dir).join(file_name);
+        self.write_to(&path)?;
+        Ok(path)
+    }
+
+    /// Writes a file per top-level definition (or per schema, with several
+    /// schemas) to the directory `name` in the `OUT_DIR` of the build
+    /// script, which improves incremental compilation of large schemas.
+    /// Returns the path of `name.rs`, which declares the modules and is
+    /// meant to be included:
+    ///
+    /// ```ignore
+    /// include!(concat!(env!("OUT_DIR"), "/api.rs"));
+    /// ```
+    pub fn write_modules_to_out_dir(&self, name: &str) -> Result<PathBuf, Error> {
+        self.rerun_if_changed();
+        let out_dir = env::var_os("OUT_DIR").expect("`OUT_DIR` is only set for build scripts");
+        let dir = Path::new(&out_dir).join(name);
+        std::fs::create_dir_all(&dir).map_err(|err| Error::Write(dir.clone(), err))?;
+        let mut declarations = String::new();
+        for (module, source) in self.build().try_generate_modules()? {
+            let path = dir.join(format!("{}.rs", module));
+            std::fs::write(&path, source).map_err(|err| Error::Write(path.clone(), err))?;
+            // `include!` resolves module paths against the including file,
+            // so they have to be absolute
+            declarations.push_str(&format!("#[path = {:?}]\n", path.display().to_string()));
+            if self.schemas.len() == 1 {
+                declarations.push_str(&format!("mod {0};\npub use self::{0}::*;\n", module));
+            } else {
+                declarations.push_str(&format!("pub mod {};\n", module));
+            }
+        }
+        let path = dir.with_extension("rs");
+        std::fs::write(&path, declarations).map_err(|err| Error::Write(path.clone(), err))?;
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_to_out_dir() {
+        let out_dir = env::temp_dir().join("schemafy_build_test");
+        std::fs::create_dir_all(&out_dir).unwrap();
+        env::set_var("OUT_DIR", &out_dir);
+
+        let path = Generator::new()
+            .schema("../tests/modules/common.json")
+            .root("Common")
+            .configure(|builder| builder.with_derive("Eq"))
+            .write_to_out_dir("common.rs")
+            .unwrap();
+        assert_eq!(path, out_dir.join("common.rs"));
+        let source = std::fs::read_to_string(path).unwrap();
+        assert!(source.contains("pub struct Error"));
+        assert!(source.contains("Eq"));
+
+        let path = Generator::new()
+            .schema("../tests/split.json")
+            .root("Catalog")
+            .write_modules_to_out_dir("split")
+            .unwrap();
+        let declarations = std::fs::read_to_string(path).unwrap();
+        assert!(declarations.contains("mod order;\npub use self::order::*;"));
+        for module in ["order", "status", "catalog"] {
+            assert!(out_dir
+                .join("split")
+                .join(format!("{}.rs", module))
+                .exists());
+        }
+
+        let err = Generator::new()
+            .schema("missing.json")
+            .write_to(out_dir.join("missing.rs"))
+            .unwrap_err();
+        assert!(matches!(err, Error::Read(..)));
+    }
+}
@@ -0,0 +1,200 @@
+//! (De)serializes bytes transmitted as a base64 string, as described by
+//! `"format": "byte"` (see the `base64_bytes` option).
+//!
+//! Both the standard and the URL-safe alphabets are accepted, with or
+//! without padding, and the standard one with padding is written.
+
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+use serde::{de::Error as _, Deserialize, Deserializer, Serializer};
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes `bytes` with the standard alphabet and padding.
+pub fn encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | u32::from(b) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// A string which is not valid base64.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DecodeError;
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid base64")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecodeError {}
+
+fn sextet(c: u8) -> Result<u32, DecodeError> {
+    Ok(match c {
+        b'A'..=b'Z' => c - b'A',
+        b'a'..=b'z' => c - b'a' + 26,
+        b'0'..=b'9' => c - b'0' + 52,
+        b'+' | b'-' => 62,
+        b'/' | b'_' => 63,
+        _ => return Err(DecodeError),
+    }
+    .into())
+}
+
+/// Decodes `encoded`, in the standard or URL-safe alphabet, with or
+/// without padding.
+pub fn decode(encoded: &str) -> Result<Vec<u8>, DecodeError> {
+    let encoded = encoded.as_bytes();
+    let padding = encoded.iter().rev().take_while(|&&c| c == b'=').count();
+    if padding > 2 || padding > 0 && !encoded.len().is_multiple_of(4) {
+        return Err(DecodeError);
+    }
+    let encoded = &encoded[..encoded.len() - padding];
+    if encoded.len() % 4 == 1 {
+        return Err(DecodeError);
+    }
+    let mut bytes = Vec::with_capacity(encoded.len() * 3 / 4);
+    for chunk in encoded.chunks(4) {
+        let mut n = 0;
+        for (i, &c) in chunk.iter().enumerate() {
+            n |= sextet(c)? << (18 - 6 * i);
+        }
+        for i in 0..chunk.len() - 1 {
+            bytes.push((n >> (16 - 8 * i)) as u8);
+        }
+    }
+    Ok(bytes)
+}
+
+/// The types of the fields which this module handles, `Vec<u8>` or
+/// `Option<Vec<u8>>`.
+pub trait Base64Field: Sized {
+    fn deserialize_base64<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>;
+    fn serialize_base64<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer;
+}
+
+impl Base64Field for Vec<u8> {
+    fn deserialize_base64<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        decode(&String::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+
+    fn serialize_base64<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&encode(self))
+    }
+}
+
+impl Base64Field for Option<Vec<u8>> {
+    fn deserialize_base64<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<String>::deserialize(deserializer)?
+            .map(|encoded| decode(&encoded).map_err(D::Error::custom))
+            .transpose()
+    }
+
+    fn serialize_base64<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Some(bytes) => serializer.serialize_str(&encode(bytes)),
+            None => serializer.serialize_none(),
+        }
+    }
+}
+
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: Base64Field,
+    D: Deserializer<'de>,
+{
+    T::deserialize_base64(deserializer)
+}
+
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Base64Field,
+    S: Serializer,
+{
+    value.serialize_base64(serializer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde::Serialize;
+    use serde_json::{from_str, to_string};
+
+    #[test]
+    fn round_trip() {
+        for (bytes, encoded) in [
+            (&b""[..], ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"foob", "Zm9vYg=="),
+            (b"\xfb\xff", "+/8="),
+        ] {
+            assert_eq!(encode(bytes), encoded);
+            assert_eq!(decode(encoded).unwrap(), bytes);
+        }
+    }
+
+    #[test]
+    fn lenient_decode() {
+        assert_eq!(decode("-_8").unwrap(), b"\xfb\xff");
+        assert_eq!(decode("Zm9vYg").unwrap(), b"foob");
+        assert_eq!(decode("Zm9vY"), Err(DecodeError));
+        assert_eq!(decode("Zm9v===="), Err(DecodeError));
+        assert_eq!(decode("Zm9*"), Err(DecodeError));
+    }
+
+    #[derive(PartialEq, Debug, Deserialize, Serialize)]
+    struct Blob {
+        #[serde(with = "super")]
+        data: Vec<u8>,
+        #[serde(default, with = "super")]
+        checksum: Option<Vec<u8>>,
+    }
+
+    #[test]
+    fn fields() {
+        let json = r#"{"data":"Zm9v","checksum":null}"#;
+        let blob = from_str::<Blob>(json).unwrap();
+        assert_eq!(
+            blob,
+            Blob {
+                data: b"foo".to_vec(),
+                checksum: None,
+            }
+        );
+        assert_eq!(to_string(&blob).unwrap(), json);
+        assert!(from_str::<Blob>(r#"{"data":1}"#).is_err());
+    }
+}
@@ -0,0 +1,39 @@
+//! Support code for the `borrowed_strings` option, as serde only borrows a
+//! `Cow<str>` which is not wrapped in another type.
+
+use alloc::borrow::Cow;
+
+use serde::{Deserialize, Deserializer};
+
+#[derive(Deserialize)]
+struct Borrowed<'a>(#[serde(borrow)] Cow<'a, str>);
+
+/// Deserializes an optional string, borrowing it from the input when it
+/// has no escapes.
+pub fn deserialize_option<'de: 'a, 'a, D>(deserializer: D) -> Result<Option<Cow<'a, str>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<Borrowed<'a>>::deserialize(deserializer).map(|s| s.map(|s| s.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Optional<'a> {
+        #[serde(borrow, default, deserialize_with = "deserialize_option")]
+        s: Option<Cow<'a, str>>,
+    }
+
+    #[test]
+    fn borrows() {
+        let optional: Optional = serde_json::from_str(r#"{"s":"a"}"#).unwrap();
+        assert!(matches!(optional.s, Some(Cow::Borrowed("a"))));
+        let optional: Optional = serde_json::from_str(r#"{"s":"\n"}"#).unwrap();
+        assert!(matches!(optional.s, Some(Cow::Owned(_))));
+        let optional: Optional = serde_json::from_str(r#"{}"#).unwrap();
+        assert!(optional.s.is_none());
+    }
+}
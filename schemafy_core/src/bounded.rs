@@ -0,0 +1,163 @@
+//! Numbers restricted by the `minimum` and `maximum` keywords, which fail
+//! to deserialize when out of range.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+use core::{convert::TryFrom, fmt, ops::Deref};
+#[cfg(feature = "std")]
+use std::error::Error;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// The error returned when a number is outside the range allowed by a
+/// schema.
+#[derive(Clone, PartialEq, Debug)]
+pub struct OutOfBounds {
+    pub value: String,
+    pub bounds: String,
+}
+
+impl fmt::Display for OutOfBounds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` is not in the range {}", self.value, self.bounds)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for OutOfBounds {}
+
+/// An integer in the inclusive range `MIN..=MAX`.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Bounded<const MIN: i64, const MAX: i64>(i64);
+
+impl<const MIN: i64, const MAX: i64> Bounded<MIN, MAX> {
+    pub const MIN: i64 = MIN;
+    pub const MAX: i64 = MAX;
+
+    pub fn new(value: i64) -> Result<Self, OutOfBounds> {
+        if (MIN..=MAX).contains(&value) {
+            Ok(Bounded(value))
+        } else {
+            Err(OutOfBounds {
+                value: value.to_string(),
+                bounds: format!("[{}, {}]", MIN, MAX),
+            })
+        }
+    }
+
+    pub fn get(self) -> i64 {
+        self.0
+    }
+}
+
+impl<const MIN: i64, const MAX: i64> TryFrom<i64> for Bounded<MIN, MAX> {
+    type Error = OutOfBounds;
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        Bounded::new(value)
+    }
+}
+
+impl<const MIN: i64, const MAX: i64> From<Bounded<MIN, MAX>> for i64 {
+    fn from(value: Bounded<MIN, MAX>) -> i64 {
+        value.0
+    }
+}
+
+impl<const MIN: i64, const MAX: i64> Deref for Bounded<MIN, MAX> {
+    type Target = i64;
+    fn deref(&self) -> &i64 {
+        &self.0
+    }
+}
+
+impl<const MIN: i64, const MAX: i64> fmt::Display for Bounded<MIN, MAX> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<const MIN: i64, const MAX: i64> Serialize for Bounded<MIN, MAX> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(self.0)
+    }
+}
+
+impl<'de, const MIN: i64, const MAX: i64> Deserialize<'de> for Bounded<MIN, MAX> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = i64::deserialize(deserializer)?;
+        Bounded::new(value).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Checks that `value` is within the bounds given by a schema's `minimum`
+/// and `maximum`, which are exclusive if the corresponding flag is set.
+pub fn check_f64(
+    value: f64,
+    minimum: Option<(f64, bool)>,
+    maximum: Option<(f64, bool)>,
+) -> Result<f64, OutOfBounds> {
+    let above_minimum = match minimum {
+        Some((min, true)) => value > min,
+        Some((min, false)) => value >= min,
+        None => !value.is_nan(),
+    };
+    let below_maximum = match maximum {
+        Some((max, true)) => value < max,
+        Some((max, false)) => value <= max,
+        None => !value.is_nan(),
+    };
+    if above_minimum && below_maximum {
+        return Ok(value);
+    }
+    let bounds = format!(
+        "{}{}, {}{}",
+        match minimum {
+            Some((_, true)) | None => "(",
+            Some((_, false)) => "[",
+        },
+        minimum.map_or("-inf".to_string(), |(min, _)| min.to_string()),
+        maximum.map_or("inf".to_string(), |(max, _)| max.to_string()),
+        match maximum {
+            Some((_, true)) | None => ")",
+            Some((_, false)) => "]",
+        },
+    );
+    Err(OutOfBounds {
+        value: value.to_string(),
+        bounds,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounded_integer() {
+        assert_eq!(Bounded::<1, 3>::new(3).map(Bounded::get), Ok(3));
+        assert_eq!(
+            Bounded::<1, 3>::new(4).unwrap_err().to_string(),
+            "`4` is not in the range [1, 3]"
+        );
+        assert_eq!(
+            serde_json::from_str::<Bounded<{ -1 }, 1>>("-1").unwrap(),
+            Bounded(-1)
+        );
+        assert!(serde_json::from_str::<Bounded<{ -1 }, 1>>("2").is_err());
+        assert_eq!(serde_json::to_string(&Bounded::<0, 9>(5)).unwrap(), "5");
+    }
+
+    #[test]
+    fn bounded_float() {
+        assert_eq!(check_f64(0.5, Some((0.0, true)), None), Ok(0.5));
+        assert_eq!(
+            check_f64(0.0, Some((0.0, true)), Some((1.0, false)))
+                .unwrap_err()
+                .to_string(),
+            "`0` is not in the range (0, 1]"
+        );
+        assert!(check_f64(f64::NAN, None, None).is_err());
+    }
+}
@@ -0,0 +1,20 @@
+//! Support code for the builders of generated structs (see the `builders`
+//! option).
+
+use core::fmt;
+#[cfg(feature = "std")]
+use std::error::Error;
+
+/// The error returned by the `build` method of a builder when a required
+/// field was not set.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MissingField(pub &'static str);
+
+impl fmt::Display for MissingField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "missing field `{}`", self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for MissingField {}
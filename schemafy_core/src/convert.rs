@@ -0,0 +1,146 @@
+//! Support code for the `TryFrom<serde_json::Value>` implementations of
+//! generated types (see the `try_from_value` option), which report every
+//! missing or mismatched field instead of only the first one as serde does.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::validation::{join, ValidationErrors};
+
+/// Implemented by generated types to check that a JSON value can be
+/// converted to them.
+pub trait CheckJson {
+    /// Pushes an error for every part of `value`, which is located at the
+    /// JSON pointer `path`, which can not be converted.
+    fn check_json(value: &Value, path: &str, errors: &mut ValidationErrors);
+}
+
+/// Checks that `value` deserializes to `T`, pushing the error if not.
+pub fn check<T: DeserializeOwned>(value: &Value, path: &str, errors: &mut ValidationErrors) {
+    if let Err(err) = T::deserialize(value) {
+        errors.push(path, err.to_string());
+    }
+}
+
+/// Checks every item of the array `value` with `check_item`.
+pub fn check_items<F>(value: &Value, path: &str, errors: &mut ValidationErrors, mut check_item: F)
+where
+    F: FnMut(&Value, &str, &mut ValidationErrors),
+{
+    match value {
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                check_item(item, &join(path, i), errors);
+            }
+        }
+        _ => errors.push(
+            path,
+            format!("invalid type: {}, expected an array", kind(value)),
+        ),
+    }
+}
+
+/// Returns the members of the object `value`, or pushes an error.
+pub fn object<'a>(
+    value: &'a Value,
+    path: &str,
+    errors: &mut ValidationErrors,
+) -> Option<&'a serde_json::Map<String, Value>> {
+    match value {
+        Value::Object(object) => Some(object),
+        _ => {
+            errors.push(
+                path,
+                format!("invalid type: {}, expected an object", kind(value)),
+            );
+            None
+        }
+    }
+}
+
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Converts `value` to `T`, reporting every problem found by
+/// `T::check_json`.
+pub fn try_from_value<T>(value: Value) -> Result<T, ValidationErrors>
+where
+    T: CheckJson + DeserializeOwned,
+{
+    let mut errors = ValidationErrors::default();
+    T::check_json(&value, "", &mut errors);
+    errors.into_result()?;
+    // Anything `check_json` does not look at (such as unknown fields) is
+    // still reported by serde
+    serde_json::from_value(value).map_err(|err| {
+        let mut errors = ValidationErrors::default();
+        errors.push("", err.to_string());
+        errors
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::vec::Vec;
+
+    use serde_json::json;
+
+    #[derive(Debug, serde::Deserialize)]
+    struct Point {
+        x: i64,
+        y: i64,
+    }
+
+    impl CheckJson for Point {
+        fn check_json(value: &Value, path: &str, errors: &mut ValidationErrors) {
+            if let Some(object) = object(value, path, errors) {
+                for name in ["x", "y"] {
+                    match object.get(name) {
+                        Some(value) => check::<i64>(value, &join(path, name), errors),
+                        None => errors.push(join(path, name), "missing field"),
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn all_errors() {
+        let errors = try_from_value::<Point>(json!({"x": "1"})).unwrap_err();
+        assert_eq!(
+            errors.to_string(),
+            "/x: invalid type: string \"1\", expected i64\n/y: missing field"
+        );
+        assert_eq!(
+            try_from_value::<Point>(json!([])).unwrap_err().to_string(),
+            "invalid type: array, expected an object"
+        );
+        let point = try_from_value::<Point>(json!({"x": 1, "y": 2})).unwrap();
+        assert_eq!((point.x, point.y), (1, 2));
+    }
+
+    #[test]
+    fn items() {
+        let mut errors = ValidationErrors::default();
+        check_items(&json!([1, "a", 2, null]), "/a", &mut errors, check::<i64>);
+        assert_eq!(
+            errors.0.iter().map(|e| &e.path[..]).collect::<Vec<_>>(),
+            ["/a/1", "/a/3"]
+        );
+    }
+}
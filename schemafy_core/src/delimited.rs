@@ -0,0 +1,183 @@
+//! Arrays written as a single string of delimited items, as described by
+//! the `collectionFormat` of Swagger 2.0, such as `"a,b,c"` for `csv`.
+//!
+//! Each format has its module, for use with `#[serde(with)]` on fields of
+//! type `Vec<T>` or `Option<Vec<T>>`, whose items are parsed with `FromStr`
+//! and written with `Display`.
+
+use alloc::{string::String, vec::Vec};
+use core::{fmt::Display, str::FromStr};
+
+use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+/// The types of the fields which this module handles, `Vec<T>` or
+/// `Option<Vec<T>>`.
+pub trait DelimitedField: Sized {
+    fn deserialize_delimited<'de, D>(deserializer: D, delimiter: char) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>;
+    fn serialize_delimited<S>(&self, serializer: S, delimiter: char) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer;
+}
+
+fn split<T, E>(s: &str, delimiter: char) -> Result<Vec<T>, E>
+where
+    T: FromStr,
+    T::Err: Display,
+    E: Error,
+{
+    if s.is_empty() {
+        return Ok(Vec::new());
+    }
+    s.split(delimiter)
+        .map(|item| item.parse().map_err(E::custom))
+        .collect()
+}
+
+fn join<T: Display>(items: &[T], delimiter: char) -> String {
+    use core::fmt::Write;
+
+    let mut joined = String::new();
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            joined.push(delimiter);
+        }
+        let _ = write!(joined, "{}", item);
+    }
+    joined
+}
+
+impl<T> DelimitedField for Vec<T>
+where
+    T: FromStr + Display,
+    T::Err: Display,
+{
+    fn deserialize_delimited<'de, D>(deserializer: D, delimiter: char) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        split(&String::deserialize(deserializer)?, delimiter)
+    }
+
+    fn serialize_delimited<S>(&self, serializer: S, delimiter: char) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&join(self, delimiter))
+    }
+}
+
+impl<T> DelimitedField for Option<Vec<T>>
+where
+    T: FromStr + Display,
+    T::Err: Display,
+{
+    fn deserialize_delimited<'de, D>(deserializer: D, delimiter: char) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<String>::deserialize(deserializer)?
+            .map(|s| split(&s, delimiter))
+            .transpose()
+    }
+
+    fn serialize_delimited<S>(&self, serializer: S, delimiter: char) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Some(items) => serializer.serialize_str(&join(items, delimiter)),
+            None => serializer.serialize_none(),
+        }
+    }
+}
+
+macro_rules! delimited_format {
+    ($(#[$doc:meta])* $format:ident, $delimiter:expr) => {
+        $(#[$doc])*
+        pub mod $format {
+            use serde::{Deserializer, Serializer};
+
+            use super::DelimitedField;
+
+            pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+            where
+                T: DelimitedField,
+                D: Deserializer<'de>,
+            {
+                T::deserialize_delimited(deserializer, $delimiter)
+            }
+
+            pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                T: DelimitedField,
+                S: Serializer,
+            {
+                value.serialize_delimited(serializer, $delimiter)
+            }
+        }
+    };
+}
+
+delimited_format!(
+    /// Items separated by commas, `a,b`.
+    csv,
+    ','
+);
+delimited_format!(
+    /// Items separated by spaces, `a b`.
+    ssv,
+    ' '
+);
+delimited_format!(
+    /// Items separated by tabs, `a\tb`.
+    tsv,
+    '\t'
+);
+delimited_format!(
+    /// Items separated by pipes, `a|b`.
+    pipes,
+    '|'
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::vec;
+
+    use serde::Serialize;
+
+    #[derive(PartialEq, Debug, Deserialize, Serialize)]
+    struct Query {
+        #[serde(with = "csv")]
+        ids: Vec<i64>,
+        #[serde(with = "pipes", default)]
+        tags: Option<Vec<String>>,
+    }
+
+    #[test]
+    fn round_trip() {
+        for (json, query) in [
+            (
+                r#"{"ids":"1,2,3","tags":"a|b"}"#,
+                Query {
+                    ids: vec![1, 2, 3],
+                    tags: Some(vec!["a".into(), "b".into()]),
+                },
+            ),
+            (
+                r#"{"ids":"","tags":null}"#,
+                Query {
+                    ids: vec![],
+                    tags: None,
+                },
+            ),
+        ] {
+            assert_eq!(serde_json::from_str::<Query>(json).unwrap(), query);
+            assert_eq!(serde_json::to_string(&query).unwrap(), json);
+        }
+        assert!(serde_json::from_str::<Query>(r#"{"ids":"1,x"}"#).is_err());
+    }
+}
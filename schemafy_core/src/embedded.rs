@@ -0,0 +1,93 @@
+//! Validates JSON values against a schema embedded in the generated code
+//! (see the `embed_schema` option), using the `jsonschema` crate.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use jsonschema::JSONSchema;
+use once_cell::sync::Lazy;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::validation::ValidationErrors;
+
+type Key = (usize, &'static str);
+
+static VALIDATORS: Lazy<Mutex<HashMap<Key, &'static JSONSchema>>> = Lazy::new(Default::default);
+
+fn validator(schema: &'static str, pointer: &'static str) -> &'static JSONSchema {
+    let mut validators = VALIDATORS.lock().unwrap_or_else(|err| err.into_inner());
+    validators
+        .entry((schema.as_ptr() as usize, pointer))
+        .or_insert_with(|| {
+            let mut root: Value = serde_json::from_str(schema)
+                .unwrap_or_else(|err| panic!("Invalid embedded schema: {}", err));
+            // Validate against a definition by referring to it from a document
+            // which keeps the definitions (and `$schema`) of the root
+            if pointer != "#" {
+                if let Value::Object(root) = &mut root {
+                    root.retain(|key, _| key == "$schema" || key == "definitions");
+                    root.insert("$ref".into(), pointer.into());
+                }
+            }
+            let compiled = JSONSchema::compile(&root)
+                .unwrap_or_else(|err| panic!("Invalid embedded schema: {}", err));
+            // Validators live for the rest of the program, like the schemas
+            Box::leak(Box::new(compiled))
+        })
+}
+
+/// Validates `value` against the schema at `pointer` (`#` or
+/// `#/definitions/...`) in the JSON `schema`, then deserializes it.
+///
+/// # Panics
+///
+/// If `schema` is not a valid JSON schema.
+pub fn validate_json<T: DeserializeOwned>(
+    schema: &'static str,
+    pointer: &'static str,
+    value: &Value,
+) -> Result<T, ValidationErrors> {
+    let mut errors = ValidationErrors::default();
+    if let Err(iter) = validator(schema, pointer).validate(value) {
+        for error in iter {
+            errors.push(error.instance_path.to_string(), error.to_string());
+        }
+    }
+    errors.into_result()?;
+    serde_json::from_value(value.clone()).map_err(|err| {
+        let mut errors = ValidationErrors::default();
+        errors.push("", err.to_string());
+        errors
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    const SCHEMA: &str = r##"{
+        "type": "object",
+        "properties": { "a": { "$ref": "#/definitions/small" } },
+        "definitions": { "small": { "type": "integer", "maximum": 3 } }
+    }"##;
+
+    #[test]
+    fn validate_root() {
+        let value: Value = validate_json(SCHEMA, "#", &json!({ "a": 1 })).unwrap();
+        assert_eq!(value, json!({ "a": 1 }));
+        let errors = validate_json::<Value>(SCHEMA, "#", &json!({ "a": 4 })).unwrap_err();
+        assert_eq!(errors.0.len(), 1);
+        assert_eq!(errors.0[0].path, "/a");
+    }
+
+    #[test]
+    fn validate_definition() {
+        assert_eq!(
+            validate_json::<i64>(SCHEMA, "#/definitions/small", &json!(2)),
+            Ok(2)
+        );
+        assert!(validate_json::<i64>(SCHEMA, "#/definitions/small", &json!(5)).is_err());
+    }
+}
@@ -0,0 +1,80 @@
+//! (De)serializes an `Option<T>` which is transmitted as `""` when absent,
+//! as described by `"x-empty-string-as-none": true`.
+//!
+//! `null` is also read as `None`, which is written back as `""`.
+
+use alloc::string::String;
+
+use serde::{de::IntoDeserializer, Deserialize, Deserializer, Serialize, Serializer};
+
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    match Option::<String>::deserialize(deserializer)? {
+        Some(value) if !value.is_empty() => T::deserialize(value.into_deserializer()).map(Some),
+        _ => Ok(None),
+    }
+}
+
+pub fn serialize<T, S>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: Serializer,
+{
+    match value {
+        Some(value) => value.serialize(serializer),
+        None => serializer.serialize_str(""),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::{from_str, to_string};
+
+    #[derive(PartialEq, Debug, Deserialize, Serialize)]
+    #[serde(rename_all = "lowercase")]
+    enum Color {
+        Red,
+    }
+
+    #[derive(PartialEq, Debug, Deserialize, Serialize)]
+    struct Test {
+        #[serde(default, with = "super")]
+        name: Option<String>,
+        #[serde(default, with = "super")]
+        color: Option<Color>,
+    }
+
+    #[test]
+    fn empty_is_none() {
+        for json in [r#"{"name":"","color":""}"#, r#"{"name":null}"#, "{}"] {
+            let test = from_str::<Test>(json).unwrap();
+            assert_eq!(
+                test,
+                Test {
+                    name: None,
+                    color: None
+                }
+            );
+            assert_eq!(to_string(&test).unwrap(), r#"{"name":"","color":""}"#);
+        }
+    }
+
+    #[test]
+    fn value() {
+        let json = r#"{"name":"a","color":"red"}"#;
+        let test = from_str::<Test>(json).unwrap();
+        assert_eq!(
+            test,
+            Test {
+                name: Some("a".into()),
+                color: Some(Color::Red)
+            }
+        );
+        assert_eq!(to_string(&test).unwrap(), json);
+    }
+}
@@ -0,0 +1,49 @@
+//! Support code for the enums generated from string `enum` schemas.
+
+use alloc::string::String;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::error::Error;
+
+/// The error returned by `FromStr` when a string is not one of the values
+/// of an enum.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ParseEnumError {
+    pub value: String,
+    pub expected: &'static [&'static str],
+}
+
+impl fmt::Display for ParseEnumError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown variant `{}`, expected one of ", self.value)?;
+        for (i, expected) in self.expected.iter().enumerate() {
+            if i != 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "`{}`", expected)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for ParseEnumError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::string::ToString;
+
+    #[test]
+    fn display() {
+        let error = ParseEnumError {
+            value: "c".into(),
+            expected: &["a", "b"],
+        };
+        assert_eq!(
+            error.to_string(),
+            "unknown variant `c`, expected one of `a`, `b`"
+        );
+    }
+}
@@ -0,0 +1,59 @@
+//! The Kubernetes `IntOrString` type, generated for schemas with
+//! `"x-kubernetes-int-or-string": true`, such as ports and percentages.
+
+use alloc::string::String;
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// An integer or a string, (de)serialized as either.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum IntOrString {
+    Int(i64),
+    String(String),
+}
+
+impl From<i64> for IntOrString {
+    fn from(value: i64) -> Self {
+        IntOrString::Int(value)
+    }
+}
+
+impl From<String> for IntOrString {
+    fn from(value: String) -> Self {
+        IntOrString::String(value)
+    }
+}
+
+impl From<&str> for IntOrString {
+    fn from(value: &str) -> Self {
+        IntOrString::String(value.into())
+    }
+}
+
+impl fmt::Display for IntOrString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntOrString::Int(value) => value.fmt(f),
+            IntOrString::String(value) => f.write_str(value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn int_or_string() {
+        for (json, value) in [
+            ("8080", IntOrString::from(8080)),
+            (r#""25%""#, IntOrString::from("25%")),
+        ] {
+            assert_eq!(serde_json::from_str::<IntOrString>(json).unwrap(), value);
+            assert_eq!(serde_json::to_string(&value).unwrap(), json);
+        }
+        assert!(serde_json::from_str::<IntOrString>("1.5").is_err());
+    }
+}
@@ -0,0 +1,98 @@
+//! (De)serializes a value which is transmitted as a string containing
+//! JSON, as described by `"contentMediaType": "application/json"`.
+//!
+//! A `null` in place of the string is treated as the JSON text `null`
+//! so that `Option<T>` fields work as expected.
+
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: serde::de::DeserializeOwned,
+    D: serde::Deserializer<'de>,
+{
+    use alloc::string::String;
+
+    use serde::de::{Deserialize, Error};
+
+    let s = Option::<String>::deserialize(deserializer)?;
+    serde_json::from_str(s.as_deref().unwrap_or("null")).map_err(D::Error::custom)
+}
+
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: serde::Serialize,
+    S: serde::Serializer,
+{
+    use alloc::string::ToString;
+
+    use serde::ser::Error;
+
+    match serde_json::to_value(value).map_err(S::Error::custom)? {
+        serde_json::Value::Null => serializer.serialize_none(),
+        value => serializer.serialize_str(&value.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde::{Deserialize, Serialize};
+    use serde_json::{from_str, to_string};
+
+    #[derive(PartialEq, Debug, Deserialize, Serialize)]
+    struct Inner {
+        x: i32,
+    }
+
+    #[derive(PartialEq, Debug, Deserialize, Serialize)]
+    struct Outer {
+        #[serde(serialize_with = "serialize", deserialize_with = "deserialize")]
+        inner: Inner,
+        #[serde(
+            default,
+            serialize_with = "serialize",
+            deserialize_with = "deserialize"
+        )]
+        optional: Option<Inner>,
+    }
+
+    #[test]
+    fn deserialize_embedded() {
+        assert_eq!(
+            from_str::<Outer>(r#"{ "inner": "{\"x\": 1}", "optional": null }"#).unwrap(),
+            Outer {
+                inner: Inner { x: 1 },
+                optional: None,
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_missing_optional() {
+        assert_eq!(
+            from_str::<Outer>(r#"{ "inner": "{\"x\": 1}" }"#).unwrap(),
+            Outer {
+                inner: Inner { x: 1 },
+                optional: None,
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_invalid_embedded() {
+        assert!(from_str::<Outer>(r#"{ "inner": "{\"x\": \"1\"}" }"#).is_err());
+        assert!(from_str::<Outer>(r#"{ "inner": { "x": 1 } }"#).is_err());
+    }
+
+    #[test]
+    fn serialize_embedded() {
+        assert_eq!(
+            to_string(&Outer {
+                inner: Inner { x: 1 },
+                optional: Some(Inner { x: 2 }),
+            })
+            .unwrap(),
+            r#"{"inner":"{\"x\":1}","optional":"{\"x\":2}"}"#
+        );
+    }
+}
@@ -0,0 +1,107 @@
+//! Checks strings against the `minLength` and `maxLength` keywords of a
+//! schema, which count characters rather than bytes.
+
+use alloc::string::{String, ToString};
+use core::fmt;
+#[cfg(feature = "std")]
+use std::error::Error;
+
+#[cfg(feature = "std")]
+use crate::pattern::PatternError;
+
+/// The error returned when a string is too short or too long.
+#[derive(Clone, PartialEq, Debug)]
+pub struct LengthError {
+    pub value: String,
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+}
+
+impl fmt::Display for LengthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.min_length, self.max_length) {
+            (Some(min), Some(max)) => write!(
+                f,
+                "`{}` is not between {} and {} characters long",
+                self.value, min, max
+            ),
+            (Some(min), None) => write!(f, "`{}` is shorter than {} characters", self.value, min),
+            (None, Some(max)) => write!(f, "`{}` is longer than {} characters", self.value, max),
+            (None, None) => write!(f, "`{}` has an invalid length", self.value),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for LengthError {}
+
+pub fn check(
+    value: &str,
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+) -> Result<(), LengthError> {
+    let length = value.chars().count();
+    if min_length.is_some_and(|min| length < min) || max_length.is_some_and(|max| length > max) {
+        Err(LengthError {
+            value: value.to_string(),
+            min_length,
+            max_length,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/// The error returned by strings restricted by both a `pattern` and their
+/// length.
+#[cfg(feature = "std")]
+#[derive(Clone, PartialEq, Debug)]
+pub enum StringError {
+    Pattern(PatternError),
+    Length(LengthError),
+}
+
+#[cfg(feature = "std")]
+impl From<PatternError> for StringError {
+    fn from(error: PatternError) -> Self {
+        StringError::Pattern(error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<LengthError> for StringError {
+    fn from(error: LengthError) -> Self {
+        StringError::Length(error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for StringError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StringError::Pattern(error) => error.fmt(f),
+            StringError::Length(error) => error.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for StringError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_chars() {
+        assert_eq!(check("äö", Some(2), Some(2)), Ok(()));
+        assert_eq!(
+            check("abc", Some(1), Some(2)).unwrap_err().to_string(),
+            "`abc` is not between 1 and 2 characters long"
+        );
+        assert_eq!(
+            check("", Some(1), None).unwrap_err().to_string(),
+            "`` is shorter than 1 characters"
+        );
+    }
+}
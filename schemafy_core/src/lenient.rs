@@ -0,0 +1,103 @@
+//! Deserializers accepting the loosely typed values of legacy systems, as
+//! described by `"x-lenient": true` on `boolean`, `integer` and `number`
+//! schemas. Values are serialized as usual, so these are meant for
+//! `deserialize_with`.
+//!
+//! `null` is passed through so that `Option<T>` fields work as expected.
+
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+use serde_json::{Number, Value};
+
+/// Accepts `true`, `false`, `1`, `0` and the same quoted, ignoring case.
+pub mod boolean {
+    use super::*;
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: DeserializeOwned,
+        D: Deserializer<'de>,
+    {
+        let value = match Value::deserialize(deserializer)? {
+            Value::Number(n) if n.as_u64() == Some(1) => Value::Bool(true),
+            Value::Number(n) if n.as_u64() == Some(0) => Value::Bool(false),
+            Value::String(s) => match s.trim() {
+                s if s.eq_ignore_ascii_case("true") || s == "1" => Value::Bool(true),
+                s if s.eq_ignore_ascii_case("false") || s == "0" => Value::Bool(false),
+                _ => Value::String(s),
+            },
+            value => value,
+        };
+        T::deserialize(value).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Accepts numbers and quoted numbers.
+pub mod number {
+    use super::*;
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: DeserializeOwned,
+        D: Deserializer<'de>,
+    {
+        let value = match Value::deserialize(deserializer)? {
+            Value::String(s) => match s.trim().parse::<Number>() {
+                Ok(n) => Value::Number(n),
+                Err(_) => Value::String(s),
+            },
+            value => value,
+        };
+        T::deserialize(value).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::format;
+
+    use serde_json::from_str;
+
+    #[derive(PartialEq, Debug, Deserialize)]
+    struct Flags {
+        #[serde(deserialize_with = "boolean::deserialize")]
+        enabled: bool,
+        #[serde(default, deserialize_with = "boolean::deserialize")]
+        visible: Option<bool>,
+        #[serde(default, deserialize_with = "number::deserialize")]
+        count: Option<u32>,
+        #[serde(deserialize_with = "number::deserialize")]
+        ratio: f64,
+    }
+
+    #[test]
+    fn lenient() {
+        for (enabled, visible) in [("true", "1"), (r#""TRUE""#, r#""1""#)] {
+            let json = format!(
+                r#"{{"enabled":{},"visible":{},"count":"3","ratio":" 0.5"}}"#,
+                enabled, visible
+            );
+            assert_eq!(
+                from_str::<Flags>(&json).unwrap(),
+                Flags {
+                    enabled: true,
+                    visible: Some(true),
+                    count: Some(3),
+                    ratio: 0.5,
+                }
+            );
+        }
+        let flags = from_str::<Flags>(r#"{"enabled":"false","visible":0,"ratio":1}"#).unwrap();
+        assert!(!flags.enabled);
+        assert_eq!(flags.visible, Some(false));
+        assert_eq!(flags.count, None);
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(from_str::<Flags>(r#"{"enabled":"yes","ratio":1}"#).is_err());
+        assert!(from_str::<Flags>(r#"{"enabled":2,"ratio":1}"#).is_err());
+        assert!(from_str::<Flags>(r#"{"enabled":true,"ratio":"a"}"#).is_err());
+    }
+}
@@ -1 +1,36 @@
-pub mod one_or_many;
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod base64_bytes;
+pub mod borrow;
+pub mod bounded;
+pub mod builder;
+pub mod convert;
+pub mod delimited;
+#[cfg(feature = "jsonschema")]
+pub mod embedded;
+pub mod empty_string_as_none;
+pub mod enums;
+pub mod int_or_string;
+pub mod json_string;
+pub mod length;
+pub mod lenient;
+pub mod list_map;
+pub mod map_or_list;
+pub mod nullable;
+pub mod one_or_many;
+#[cfg(feature = "std")]
+pub mod pattern;
+#[cfg(feature = "raw_value")]
+pub mod raw;
+pub mod stringified;
+pub mod timestamp;
+pub mod union;
+pub mod validated;
+pub mod validation;
+
+pub use int_or_string::IntOrString;
+pub use nullable::Nullable;
+pub use one_or_many::OneOrMany;
+pub use union::{Either, Union3};
@@ -0,0 +1,199 @@
+//! Lists of objects told apart by the values of some of their fields, as
+//! described by `"x-kubernetes-list-type": "map"` and the
+//! `"x-kubernetes-list-map-keys"` naming these fields.
+//!
+//! Such lists are read into a map from the key of each object to the
+//! object, and written back as a list of the objects, in the order of their
+//! keys. Lists holding two objects with the same key are rejected.
+
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use serde::{de::Error, ser::SerializeSeq, Deserialize, Deserializer, Serialize, Serializer};
+
+/// The objects of a list map, with the key identifying them.
+pub trait Keyed {
+    /// The value of the key field, or a tuple of the values of the key
+    /// fields.
+    type Key;
+    fn key(&self) -> Self::Key;
+}
+
+/// The types of the fields which this module handles, maps and `Option`s
+/// of them.
+pub trait ListMapField<'de>: Sized {
+    fn deserialize_list_map<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>;
+    fn serialize_list_map<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer;
+}
+
+fn serialize_values<'v, V, S>(
+    values: impl ExactSizeIterator<Item = &'v V>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    V: Serialize + 'v,
+    S: Serializer,
+{
+    let mut seq = serializer.serialize_seq(Some(values.len()))?;
+    for value in values {
+        seq.serialize_element(value)?;
+    }
+    seq.end()
+}
+
+fn deserialize_values<'de, V, D>(
+    deserializer: D,
+    mut insert: impl FnMut(V::Key, V) -> bool,
+) -> Result<(), D::Error>
+where
+    V: Keyed + Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    for value in Vec::<V>::deserialize(deserializer)? {
+        if !insert(value.key(), value) {
+            return Err(D::Error::custom("duplicate key in a list map"));
+        }
+    }
+    Ok(())
+}
+
+impl<'de, K, V> ListMapField<'de> for BTreeMap<K, V>
+where
+    K: Ord,
+    V: Keyed<Key = K> + Deserialize<'de> + Serialize,
+{
+    fn deserialize_list_map<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut map = BTreeMap::new();
+        deserialize_values::<V, D>(deserializer, |key, value| map.insert(key, value).is_none())?;
+        Ok(map)
+    }
+
+    fn serialize_list_map<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_values(self.values(), serializer)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'de, K, V, H> ListMapField<'de> for std::collections::HashMap<K, V, H>
+where
+    K: Eq + core::hash::Hash,
+    V: Keyed<Key = K> + Deserialize<'de> + Serialize,
+    H: core::hash::BuildHasher + Default,
+{
+    fn deserialize_list_map<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut map = Self::default();
+        deserialize_values::<V, D>(deserializer, |key, value| map.insert(key, value).is_none())?;
+        Ok(map)
+    }
+
+    fn serialize_list_map<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_values(self.values(), serializer)
+    }
+}
+
+impl<'de, M: ListMapField<'de>> ListMapField<'de> for Option<M> {
+    fn deserialize_list_map<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Field<M>(M);
+        impl<'de, M: ListMapField<'de>> Deserialize<'de> for Field<M> {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                M::deserialize_list_map(deserializer).map(Field)
+            }
+        }
+        Option::<Field<M>>::deserialize(deserializer).map(|field| field.map(|field| field.0))
+    }
+
+    fn serialize_list_map<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        struct Field<'m, M>(&'m M);
+        impl<'de, M: ListMapField<'de>> Serialize for Field<'_, M> {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                self.0.serialize_list_map(serializer)
+            }
+        }
+        match self {
+            Some(map) => serializer.serialize_some(&Field(map)),
+            None => serializer.serialize_none(),
+        }
+    }
+}
+
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: ListMapField<'de>,
+    D: Deserializer<'de>,
+{
+    T::deserialize_list_map(deserializer)
+}
+
+pub fn serialize<'de, T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: ListMapField<'de>,
+    S: Serializer,
+{
+    value.serialize_list_map(serializer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::string::String;
+
+    use serde_json::{from_str, to_string};
+
+    #[derive(PartialEq, Debug, Deserialize, Serialize)]
+    struct Port {
+        name: String,
+        port: i64,
+    }
+
+    impl Keyed for Port {
+        type Key = String;
+        fn key(&self) -> String {
+            self.name.clone()
+        }
+    }
+
+    #[derive(PartialEq, Debug, Deserialize, Serialize)]
+    struct Service {
+        #[serde(with = "super")]
+        ports: BTreeMap<String, Port>,
+        #[serde(default, with = "super")]
+        extra: Option<BTreeMap<String, Port>>,
+    }
+
+    #[test]
+    fn list_map() {
+        let json = r#"{"ports":[{"name":"a","port":1},{"name":"b","port":2}],"extra":null}"#;
+        let service = from_str::<Service>(json).unwrap();
+        assert_eq!(service.ports["b"].port, 2);
+        assert_eq!(service.extra, None);
+        assert_eq!(to_string(&service).unwrap(), json);
+        let service = from_str::<Service>(r#"{"ports":[],"extra":[]}"#).unwrap();
+        assert_eq!(service.extra, Some(BTreeMap::new()));
+        assert!(
+            from_str::<Service>(r#"{"ports":[{"name":"a","port":1},{"name":"a","port":2}]}"#)
+                .is_err()
+        );
+    }
+}
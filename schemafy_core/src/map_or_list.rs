@@ -0,0 +1,145 @@
+//! Deserializes a map which may also be sent as a list of its values, as
+//! described by `"x-map-or-list": true`, so that both
+//! `{"a": {...}, "b": {...}}` and `[{...}, {...}]` are accepted.
+//!
+//! The values of a list are keyed by their index (`"0"`, `"1"`, ...).
+//! Maps are serialized as usual, so this is meant for `deserialize_with`.
+
+use alloc::{collections::BTreeMap, format, vec::Vec};
+use core::{fmt, iter::FromIterator, marker::PhantomData};
+
+use serde::{
+    de::{IntoDeserializer, MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer,
+};
+
+/// The types of the fields which this module handles, maps and `Option`s
+/// of them.
+pub trait MapOrListField<'de>: Sized {
+    fn deserialize_map_or_list<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>;
+}
+
+struct MapOrList<K, V, M>(PhantomData<(K, V, M)>);
+
+impl<'de, K, V, M> Visitor<'de> for MapOrList<K, V, M>
+where
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+    M: FromIterator<(K, V)>,
+{
+    type Value = M;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a map or a list")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<M, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut entries = Vec::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+        Ok(entries.into_iter().collect())
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<M, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut entries = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(value) = seq.next_element()? {
+            let index = format!("{}", entries.len());
+            let key = K::deserialize(IntoDeserializer::<A::Error>::into_deserializer(index))?;
+            entries.push((key, value));
+        }
+        Ok(entries.into_iter().collect())
+    }
+}
+
+impl<'de, K, V> MapOrListField<'de> for BTreeMap<K, V>
+where
+    K: Deserialize<'de> + Ord,
+    V: Deserialize<'de>,
+{
+    fn deserialize_map_or_list<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(MapOrList(PhantomData))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'de, K, V, S> MapOrListField<'de> for std::collections::HashMap<K, V, S>
+where
+    K: Deserialize<'de> + Eq + core::hash::Hash,
+    V: Deserialize<'de>,
+    S: core::hash::BuildHasher + Default,
+{
+    fn deserialize_map_or_list<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(MapOrList(PhantomData))
+    }
+}
+
+impl<'de, M: MapOrListField<'de>> MapOrListField<'de> for Option<M> {
+    fn deserialize_map_or_list<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Field<M>(M);
+        impl<'de, M: MapOrListField<'de>> Deserialize<'de> for Field<M> {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                M::deserialize_map_or_list(deserializer).map(Field)
+            }
+        }
+        Option::<Field<M>>::deserialize(deserializer).map(|field| field.map(|field| field.0))
+    }
+}
+
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: MapOrListField<'de>,
+    D: Deserializer<'de>,
+{
+    T::deserialize_map_or_list(deserializer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::string::String;
+
+    use serde_json::from_str;
+
+    #[derive(PartialEq, Debug, Deserialize)]
+    struct Item {
+        name: String,
+    }
+
+    #[derive(PartialEq, Debug, Deserialize)]
+    struct Test {
+        #[serde(deserialize_with = "deserialize")]
+        items: BTreeMap<String, Item>,
+        #[serde(default, deserialize_with = "deserialize")]
+        counts: Option<BTreeMap<String, i32>>,
+    }
+
+    #[test]
+    fn map_or_list() {
+        let map = from_str::<Test>(r#"{"items":{"a":{"name":"x"}},"counts":null}"#).unwrap();
+        let list = from_str::<Test>(r#"{"items":[{"name":"x"}],"counts":[1,2]}"#).unwrap();
+        assert_eq!(map.items.keys().collect::<Vec<_>>(), ["a"]);
+        assert_eq!(map.counts, None);
+        assert_eq!(list.items["0"], Item { name: "x".into() });
+        assert_eq!(list.counts.unwrap()["1"], 2);
+        assert!(from_str::<Test>(r#"{"items":1}"#).is_err());
+    }
+}
@@ -0,0 +1,94 @@
+//! Optional fields which may also be `null` (see the `nullable_fields`
+//! option), for APIs where leaving a field out and setting it to `null`
+//! mean different things, such as `PATCH` requests.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A field which is absent, `null` or has a value.
+///
+/// Fields of this type need `#[serde(default)]` to be absent, and
+/// `#[serde(skip_serializing_if = "Nullable::is_absent")]` to stay absent
+/// when serialized.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub enum Nullable<T> {
+    #[default]
+    Absent,
+    Null,
+    Value(T),
+}
+
+impl<T> Nullable<T> {
+    pub fn is_absent(&self) -> bool {
+        matches!(self, Nullable::Absent)
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Nullable::Null)
+    }
+
+    /// The value, if there is one.
+    pub fn as_option(&self) -> Option<&T> {
+        match self {
+            Nullable::Value(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The value, if there is one.
+    pub fn into_option(self) -> Option<T> {
+        match self {
+            Nullable::Value(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl<T> From<T> for Nullable<T> {
+    fn from(value: T) -> Self {
+        Nullable::Value(value)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Nullable<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Option::<T>::deserialize(deserializer).map(|value| match value {
+            Some(value) => Nullable::Value(value),
+            None => Nullable::Null,
+        })
+    }
+}
+
+impl<T: Serialize> Serialize for Nullable<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Nullable::Value(value) => serializer.serialize_some(value),
+            _ => serializer.serialize_none(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::string::{String, ToString};
+
+    #[derive(PartialEq, Debug, Deserialize, Serialize)]
+    struct Patch {
+        #[serde(default, skip_serializing_if = "Nullable::is_absent")]
+        name: Nullable<String>,
+    }
+
+    #[test]
+    fn tri_state() {
+        for (json, name) in [
+            ("{}", Nullable::Absent),
+            (r#"{"name":null}"#, Nullable::Null),
+            (r#"{"name":"a"}"#, Nullable::Value("a".to_string())),
+        ] {
+            let patch: Patch = serde_json::from_str(json).unwrap();
+            assert_eq!(patch, Patch { name });
+            assert_eq!(serde_json::to_string(&patch).unwrap(), json);
+        }
+    }
+}
@@ -1,12 +1,101 @@
-pub type OneOrMany<T> = Vec<T>;
+//! (De)serializes a `Vec` which is also accepted as a single item, for
+//! schemas such as `"anyOf": [T, { "type": "array", "items": T }]`.
+//!
+//! `null` deserializes to an empty `Vec`. `serialize` writes a single item
+//! without the array, as many APIs echo whichever form was sent, while
+//! `array` always writes an array and `option` handles `Option<Vec<T>>`.
+//!
+//! `OneOrMany` instead remembers which form was received (see the
+//! `one_or_many_type` option), so that it is written back identically.
+
+use alloc::vec::Vec;
+use core::slice;
+
+use serde::{Deserialize, Serialize};
+
+/// A single item or an array of them, (de)serialized as either.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum OneOrMany<T> {
+    Many(Vec<T>),
+    One(T),
+}
+
+impl<T> OneOrMany<T> {
+    pub fn as_slice(&self) -> &[T] {
+        match self {
+            OneOrMany::One(item) => slice::from_ref(item),
+            OneOrMany::Many(items) => items,
+        }
+    }
+
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.as_slice().iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.as_slice().is_empty()
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        match self {
+            OneOrMany::One(item) => alloc::vec![item],
+            OneOrMany::Many(items) => items,
+        }
+    }
+}
+
+impl<T> Default for OneOrMany<T> {
+    fn default() -> Self {
+        OneOrMany::Many(Vec::new())
+    }
+}
+
+impl<T> From<T> for OneOrMany<T> {
+    fn from(item: T) -> Self {
+        OneOrMany::One(item)
+    }
+}
+
+impl<T> From<Vec<T>> for OneOrMany<T> {
+    fn from(items: Vec<T>) -> Self {
+        OneOrMany::Many(items)
+    }
+}
+
+impl<T> From<OneOrMany<T>> for Vec<T> {
+    fn from(value: OneOrMany<T>) -> Self {
+        value.into_vec()
+    }
+}
+
+impl<T> IntoIterator for OneOrMany<T> {
+    type Item = T;
+    type IntoIter = alloc::vec::IntoIter<T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_vec().into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a OneOrMany<T> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
 
 pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Vec<T>, D::Error>
 where
     T: serde::Deserialize<'de>,
     D: serde::Deserializer<'de>,
 {
-    use std::fmt;
-    use std::marker::PhantomData;
+    use alloc::{string::String, vec};
+    use core::{fmt, marker::PhantomData};
 
     use serde::de::value::{MapAccessDeserializer, SeqAccessDeserializer};
     use serde::de::{self, Deserialize, IntoDeserializer};
@@ -22,6 +111,27 @@ where
             formatter.write_str("one or many")
         }
 
+        fn visit_unit<E>(self) -> Result<Vec<T>, E>
+        where
+            E: de::Error,
+        {
+            Ok(Vec::new())
+        }
+
+        fn visit_bool<E>(self, value: bool) -> Result<Vec<T>, E>
+        where
+            E: de::Error,
+        {
+            Deserialize::deserialize(value.into_deserializer()).map(|v| vec![v])
+        }
+
+        fn visit_f64<E>(self, value: f64) -> Result<Vec<T>, E>
+        where
+            E: de::Error,
+        {
+            Deserialize::deserialize(value.into_deserializer()).map(|v| vec![v])
+        }
+
         fn visit_i64<E>(self, value: i64) -> Result<Vec<T>, E>
         where
             E: de::Error,
@@ -80,12 +190,83 @@ where
     }
 }
 
+/// Always serializes an array, even of a single item.
+pub mod array {
+    pub use super::deserialize;
+
+    pub fn serialize<T, S>(value: &[T], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: serde::Serialize,
+        S: serde::Serializer,
+    {
+        serde::Serialize::serialize(value, serializer)
+    }
+
+    /// For `Option<Vec<T>>`, which is `None` when the value is `null`.
+    pub mod option {
+        use alloc::vec::Vec;
+
+        pub use super::super::option::deserialize;
+
+        pub fn serialize<T, S>(value: &Option<Vec<T>>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            T: serde::Serialize,
+            S: serde::Serializer,
+        {
+            serde::Serialize::serialize(value, serializer)
+        }
+    }
+}
+
+/// For `Option<Vec<T>>`, which is `None` when the value is `null`.
+pub mod option {
+    use alloc::vec::Vec;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<Vec<T>>, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        struct Items<T>(Vec<T>);
+        impl<'de, T: Deserialize<'de>> Deserialize<'de> for Items<T> {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                super::deserialize(deserializer).map(Items)
+            }
+        }
+        Option::<Items<T>>::deserialize(deserializer).map(|items| items.map(|items| items.0))
+    }
+
+    pub fn serialize<T, S>(value: &Option<Vec<T>>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        struct Items<'a, T>(&'a [T]);
+        impl<T: Serialize> Serialize for Items<'_, T> {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                super::serialize(self.0, serializer)
+            }
+        }
+        match value {
+            Some(items) => serializer.serialize_some(&Items(items)),
+            None => serializer.serialize_none(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use alloc::{
+        string::{String, ToString},
+        vec,
+    };
+
     use serde::{Deserialize, Serialize};
-    use serde_json::from_str;
+    use serde_json::{from_str, to_string};
 
     #[derive(PartialEq, Debug, Deserialize, Serialize)]
     struct OneOrMany<T>(
@@ -107,6 +288,77 @@ mod tests {
         );
     }
 
+    #[test]
+    fn keeps_form() {
+        for (json, value) in [
+            ("1", super::OneOrMany::One(1)),
+            ("[1]", super::OneOrMany::Many(vec![1])),
+            ("[]", super::OneOrMany::default()),
+        ] {
+            assert_eq!(from_str::<super::OneOrMany<i32>>(json).unwrap(), value);
+            assert_eq!(to_string(&value).unwrap(), json);
+        }
+        let value = super::OneOrMany::One("a");
+        assert_eq!(value.len(), 1);
+        assert_eq!(value.iter().collect::<Vec<_>>(), [&"a"]);
+        assert_eq!(Vec::from(value), ["a"]);
+    }
+
+    #[test]
+    fn deserialize_other_scalars() {
+        assert_eq!(
+            from_str::<OneOrMany<bool>>("true").unwrap(),
+            OneOrMany(vec![true])
+        );
+        assert_eq!(
+            from_str::<OneOrMany<f64>>("1.5").unwrap(),
+            OneOrMany(vec![1.5])
+        );
+        assert_eq!(
+            from_str::<OneOrMany<i32>>("null").unwrap(),
+            OneOrMany(vec![])
+        );
+    }
+
+    #[test]
+    fn serialize_collapsed() {
+        assert_eq!(to_string(&OneOrMany(vec![1])).unwrap(), "1");
+        assert_eq!(to_string(&OneOrMany(vec![1, 2])).unwrap(), "[1,2]");
+    }
+
+    #[derive(PartialEq, Debug, Deserialize, Serialize)]
+    struct Options {
+        #[serde(default, with = "option")]
+        optional: Option<Vec<i32>>,
+        #[serde(default, with = "array")]
+        array: Vec<i32>,
+        #[serde(default, with = "array::option")]
+        optional_array: Option<Vec<i32>>,
+    }
+
+    #[test]
+    fn option_and_array() {
+        let options =
+            from_str::<Options>(r#"{ "optional": 1, "array": 2, "optional_array": 3 }"#).unwrap();
+        assert_eq!(
+            options,
+            Options {
+                optional: Some(vec![1]),
+                array: vec![2],
+                optional_array: Some(vec![3]),
+            }
+        );
+        assert_eq!(
+            to_string(&options).unwrap(),
+            r#"{"optional":1,"array":[2],"optional_array":[3]}"#
+        );
+        let options =
+            from_str::<Options>(r#"{ "optional": null, "optional_array": null }"#).unwrap();
+        assert_eq!(options.optional, None);
+        assert_eq!(options.array, Vec::<i32>::new());
+        assert_eq!(options.optional_array, None);
+    }
+
     #[derive(Deserialize, Serialize, Debug, PartialEq)]
     struct Test {
         x: i32,
@@ -0,0 +1,85 @@
+//! Checks strings against the regular expressions of a schema's
+//! `pattern` (or `propertyNames.pattern`) keyword.
+//!
+//! Each pattern is compiled once and cached for the rest of the program.
+
+use std::{collections::HashMap, error::Error, fmt, sync::Mutex};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+static CACHE: Lazy<Mutex<HashMap<String, &'static Regex>>> = Lazy::new(Default::default);
+
+/// The error returned when a string does not match a pattern.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PatternError {
+    pub pattern: &'static str,
+    pub value: String,
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "`{}` does not match the pattern `{}`",
+            self.value, self.pattern
+        )
+    }
+}
+
+impl Error for PatternError {}
+
+/// Returns the compiled regular expression for `pattern`.
+///
+/// # Panics
+///
+/// If `pattern` is not a valid regular expression.
+pub fn regex(pattern: &str) -> &'static Regex {
+    let mut cache = CACHE.lock().unwrap_or_else(|err| err.into_inner());
+    cache.entry(pattern.to_string()).or_insert_with(|| {
+        let regex = Regex::new(pattern)
+            .unwrap_or_else(|err| panic!("Invalid pattern `{}`: {}", pattern, err));
+        Box::leak(Box::new(regex))
+    })
+}
+
+/// Returns whether `value` matches `pattern` anywhere, as JSON schema
+/// patterns are not implicitly anchored.
+///
+/// # Panics
+///
+/// If `pattern` is not a valid regular expression.
+pub fn is_match(pattern: &str, value: &str) -> bool {
+    regex(pattern).is_match(value)
+}
+
+pub fn check(pattern: &'static str, value: &str) -> Result<(), PatternError> {
+    if is_match(pattern, value) {
+        Ok(())
+    } else {
+        Err(PatternError {
+            pattern,
+            value: value.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unanchored() {
+        assert!(is_match("[0-9]", "abc1"));
+        assert!(!is_match("^[0-9]+$", "abc1"));
+    }
+
+    #[test]
+    fn check_error() {
+        assert_eq!(check("^a", "abc"), Ok(()));
+        assert_eq!(
+            check("^a", "cba").unwrap_err().to_string(),
+            "`cba` does not match the pattern `^a`"
+        );
+    }
+}
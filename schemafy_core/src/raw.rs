@@ -0,0 +1,89 @@
+//! Free-form JSON kept as it was received (see the `raw_value` option),
+//! so that large opaque payloads are neither parsed nor reformatted.
+
+use alloc::boxed::Box;
+use core::{
+    fmt,
+    hash::{Hash, Hasher},
+    ops::Deref,
+};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::value::RawValue;
+
+/// The exact text of a JSON value. Values are compared by their text, so
+/// `{"a":1}` and `{ "a": 1 }` differ.
+#[derive(Clone)]
+pub struct RawJson(pub Box<RawValue>);
+
+impl RawJson {
+    pub fn into_inner(self) -> Box<RawValue> {
+        self.0
+    }
+}
+
+impl From<Box<RawValue>> for RawJson {
+    fn from(value: Box<RawValue>) -> Self {
+        RawJson(value)
+    }
+}
+
+impl Deref for RawJson {
+    type Target = RawValue;
+    fn deref(&self) -> &RawValue {
+        &self.0
+    }
+}
+
+impl PartialEq for RawJson {
+    fn eq(&self, other: &Self) -> bool {
+        self.get() == other.get()
+    }
+}
+
+impl Eq for RawJson {}
+
+impl Hash for RawJson {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.get().hash(state)
+    }
+}
+
+impl fmt::Debug for RawJson {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Display for RawJson {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.get())
+    }
+}
+
+impl<'de> Deserialize<'de> for RawJson {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Box::<RawValue>::deserialize(deserializer).map(RawJson)
+    }
+}
+
+impl Serialize for RawJson {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_text() {
+        let raw: RawJson = serde_json::from_str(r#"{ "a": [1,  2] }"#).unwrap();
+        assert_eq!(raw.get(), r#"{ "a": [1,  2] }"#);
+        assert_eq!(serde_json::to_string(&raw).unwrap(), r#"{ "a": [1,  2] }"#);
+        let other: RawJson = serde_json::from_str(r#"{"a":[1,2]}"#).unwrap();
+        assert_ne!(raw, other);
+        assert_eq!(raw, raw.clone());
+    }
+}
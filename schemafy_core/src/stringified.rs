@@ -0,0 +1,91 @@
+//! (De)serializes a number which is transmitted as a JSON string (`"123"`),
+//! as described by `"x-stringified": true` or a `"type": "string"` schema
+//! whose `format` is mapped to a number type.
+//!
+//! Plain numbers are accepted as well, and `null` is passed through so that
+//! `Option<T>` fields work as expected.
+
+use alloc::string::ToString;
+
+use serde::{
+    de::{DeserializeOwned, Error as _},
+    ser::Error as _,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use serde_json::{Number, Value};
+
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: DeserializeOwned,
+    D: Deserializer<'de>,
+{
+    match Value::deserialize(deserializer)? {
+        // Types such as decimals may keep the exact text of the number
+        Value::String(s) => T::deserialize(Value::String(s.clone())).or_else(|_| {
+            let number = s
+                .trim()
+                .parse::<Number>()
+                .map_err(|_| D::Error::custom(format_args!("invalid number `{}`", s)))?;
+            T::deserialize(Value::Number(number)).map_err(D::Error::custom)
+        }),
+        value => T::deserialize(value).map_err(D::Error::custom),
+    }
+}
+
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: Serializer,
+{
+    match serde_json::to_value(value).map_err(S::Error::custom)? {
+        Value::Number(number) => serializer.serialize_str(&number.to_string()),
+        value => value.serialize(serializer),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::{from_str, to_string};
+
+    #[derive(PartialEq, Debug, Deserialize, Serialize)]
+    struct Price {
+        #[serde(with = "super")]
+        amount: f64,
+        #[serde(with = "super")]
+        id: u64,
+        #[serde(default, with = "super")]
+        count: Option<i64>,
+    }
+
+    #[test]
+    fn stringified() {
+        let price = from_str::<Price>(r#"{"amount":"1.5","id":"18446744073709551615"}"#).unwrap();
+        assert_eq!(
+            price,
+            Price {
+                amount: 1.5,
+                id: u64::MAX,
+                count: None,
+            }
+        );
+        assert_eq!(
+            to_string(&price).unwrap(),
+            r#"{"amount":"1.5","id":"18446744073709551615","count":null}"#
+        );
+    }
+
+    #[test]
+    fn plain_numbers() {
+        let price = from_str::<Price>(r#"{"amount":2,"id":1,"count":"-3"}"#).unwrap();
+        assert_eq!(price.amount, 2.0);
+        assert_eq!(price.count, Some(-3));
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(from_str::<Price>(r#"{"amount":"a","id":"1"}"#).is_err());
+        assert!(from_str::<Price>(r#"{"amount":"1","id":"-1"}"#).is_err());
+    }
+}
@@ -0,0 +1,234 @@
+//! (De)serializes a point in time transmitted as a Unix timestamp, in
+//! seconds (`seconds`) or milliseconds (`milliseconds`) since the epoch.
+//!
+//! These are meant for the `format_modules` option, such as
+//! `format_modules { "unix-time" => "schemafy_core::timestamp::seconds" }`
+//! along with `formats { "unix-time" => "std::time::SystemTime" }`.
+//! `std::time::SystemTime` and, with the `chrono` feature,
+//! `chrono::DateTime<chrono::Utc>` are supported, as well as `Option`s of
+//! them.
+//!
+//! Fractional timestamps are accepted, and timestamps are written as
+//! integers, rounded down.
+
+use core::convert::TryFrom;
+
+use serde::{ser::Error as _, Deserialize, Deserializer, Serializer};
+
+const NANOS_PER_SECOND: i128 = 1_000_000_000;
+const NANOS_PER_MILLISECOND: i128 = 1_000_000;
+
+/// A point in time which can be represented as a Unix timestamp.
+pub trait Timestamp: Sized {
+    /// The point in time `nanos` nanoseconds after the Unix epoch, if it
+    /// can be represented.
+    fn from_unix_nanos(nanos: i128) -> Option<Self>;
+    /// The nanoseconds since the Unix epoch, negative before it.
+    fn unix_nanos(&self) -> i128;
+}
+
+#[cfg(feature = "std")]
+impl Timestamp for std::time::SystemTime {
+    fn from_unix_nanos(nanos: i128) -> Option<Self> {
+        let duration = core::time::Duration::from_nanos(u64::try_from(nanos.abs()).ok()?);
+        if nanos < 0 {
+            Self::UNIX_EPOCH.checked_sub(duration)
+        } else {
+            Self::UNIX_EPOCH.checked_add(duration)
+        }
+    }
+
+    fn unix_nanos(&self) -> i128 {
+        match self.duration_since(Self::UNIX_EPOCH) {
+            Ok(duration) => duration.as_nanos() as i128,
+            Err(err) => -(err.duration().as_nanos() as i128),
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Timestamp for chrono::DateTime<chrono::Utc> {
+    fn from_unix_nanos(nanos: i128) -> Option<Self> {
+        let seconds = i64::try_from(nanos.div_euclid(NANOS_PER_SECOND)).ok()?;
+        Self::from_timestamp(seconds, nanos.rem_euclid(NANOS_PER_SECOND) as u32)
+    }
+
+    fn unix_nanos(&self) -> i128 {
+        i128::from(self.timestamp()) * NANOS_PER_SECOND + i128::from(self.timestamp_subsec_nanos())
+    }
+}
+
+/// The types of the fields which `seconds` and `milliseconds` handle, a
+/// `Timestamp` or an `Option` of one.
+pub trait TimestampField: Sized {
+    fn deserialize_timestamp<'de, D>(deserializer: D, unit: i128) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>;
+    fn serialize_timestamp<S>(&self, serializer: S, unit: i128) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer;
+}
+
+fn from_number<T: Timestamp, E: serde::de::Error>(
+    number: serde_json::Number,
+    unit: i128,
+) -> Result<T, E> {
+    let nanos = match number.as_i64() {
+        Some(n) => i128::from(n).checked_mul(unit),
+        None => number
+            .as_f64()
+            .map(|n| n * unit as f64)
+            .filter(|n| n.abs() < i128::MAX as f64)
+            .map(|n| n as i128),
+    };
+    nanos
+        .and_then(T::from_unix_nanos)
+        .ok_or_else(|| E::custom(format_args!("timestamp `{}` is out of range", number)))
+}
+
+fn to_number<S: Serializer>(nanos: i128, serializer: S, unit: i128) -> Result<S::Ok, S::Error> {
+    let timestamp = i64::try_from(nanos.div_euclid(unit))
+        .map_err(|_| S::Error::custom("timestamp is out of range"))?;
+    serializer.serialize_i64(timestamp)
+}
+
+impl<T: Timestamp> TimestampField for T {
+    fn deserialize_timestamp<'de, D>(deserializer: D, unit: i128) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        from_number(serde_json::Number::deserialize(deserializer)?, unit)
+    }
+
+    fn serialize_timestamp<S>(&self, serializer: S, unit: i128) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        to_number(self.unix_nanos(), serializer, unit)
+    }
+}
+
+impl<T: Timestamp> TimestampField for Option<T> {
+    fn deserialize_timestamp<'de, D>(deserializer: D, unit: i128) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<serde_json::Number>::deserialize(deserializer)?
+            .map(|number| from_number(number, unit))
+            .transpose()
+    }
+
+    fn serialize_timestamp<S>(&self, serializer: S, unit: i128) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Some(value) => to_number(value.unix_nanos(), serializer, unit),
+            None => serializer.serialize_none(),
+        }
+    }
+}
+
+/// Seconds since the Unix epoch.
+pub mod seconds {
+    use super::{TimestampField, NANOS_PER_SECOND};
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: TimestampField,
+        D: serde::Deserializer<'de>,
+    {
+        T::deserialize_timestamp(deserializer, NANOS_PER_SECOND)
+    }
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: TimestampField,
+        S: serde::Serializer,
+    {
+        value.serialize_timestamp(serializer, NANOS_PER_SECOND)
+    }
+}
+
+/// Milliseconds since the Unix epoch.
+pub mod milliseconds {
+    use super::{TimestampField, NANOS_PER_MILLISECOND};
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: TimestampField,
+        D: serde::Deserializer<'de>,
+    {
+        T::deserialize_timestamp(deserializer, NANOS_PER_MILLISECOND)
+    }
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: TimestampField,
+        S: serde::Serializer,
+    {
+        value.serialize_timestamp(serializer, NANOS_PER_MILLISECOND)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    use std::time::{Duration, SystemTime};
+
+    use serde::Serialize;
+    use serde_json::{from_str, to_string};
+
+    #[derive(PartialEq, Debug, Deserialize, Serialize)]
+    struct Event {
+        #[serde(with = "seconds")]
+        created: SystemTime,
+        #[serde(default, with = "milliseconds")]
+        updated: Option<SystemTime>,
+    }
+
+    #[test]
+    fn system_time() {
+        let json = r#"{"created":1700000000,"updated":1700000000123}"#;
+        let event = from_str::<Event>(json).unwrap();
+        let created = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        assert_eq!(
+            event,
+            Event {
+                created,
+                updated: Some(created + Duration::from_millis(123)),
+            }
+        );
+        assert_eq!(to_string(&event).unwrap(), json);
+    }
+
+    #[test]
+    fn fractional_and_negative() {
+        let event = from_str::<Event>(r#"{"created":-1.5,"updated":null}"#).unwrap();
+        assert_eq!(
+            event.created,
+            SystemTime::UNIX_EPOCH - Duration::from_millis(1500)
+        );
+        assert_eq!(event.updated, None);
+        assert_eq!(
+            to_string(&event).unwrap(),
+            r#"{"created":-2,"updated":null}"#
+        );
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono() {
+        #[derive(Deserialize, Serialize)]
+        struct Event {
+            #[serde(with = "milliseconds")]
+            created: chrono::DateTime<chrono::Utc>,
+        }
+
+        let event = from_str::<Event>(r#"{"created":-1}"#).unwrap();
+        assert_eq!(event.created.timestamp_millis(), -1);
+        assert_eq!(event.created.timestamp(), -1);
+        assert_eq!(to_string(&event).unwrap(), r#"{"created":-1}"#);
+    }
+}
@@ -0,0 +1,51 @@
+//! Unions of types which are not named in the schema, generated for
+//! `anyOf` and `type` arrays by the `union_types` option. They are
+//! (de)serialized as whichever variant matches first.
+
+use serde::{Deserialize, Serialize};
+
+/// A value of either type `A` or `B`.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Either<A, B> {
+    A(A),
+    B(B),
+}
+
+/// A value of type `A`, `B` or `C`.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Union3<A, B, C> {
+    A(A),
+    B(B),
+    C(C),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::{string::String, vec, vec::Vec};
+
+    #[test]
+    fn either() {
+        for (json, value) in [("1", Either::A(1)), (r#""a""#, Either::B("a".into()))] {
+            assert_eq!(
+                serde_json::from_str::<Either<i64, String>>(json).unwrap(),
+                value
+            );
+            assert_eq!(serde_json::to_string(&value).unwrap(), json);
+        }
+        assert!(serde_json::from_str::<Either<i64, String>>("true").is_err());
+    }
+
+    #[test]
+    fn union3() {
+        let values: Vec<Union3<bool, i64, Vec<String>>> =
+            serde_json::from_str(r#"[true, 2, ["c"]]"#).unwrap();
+        assert_eq!(
+            values,
+            [Union3::A(true), Union3::B(2), Union3::C(vec!["c".into()])]
+        );
+    }
+}
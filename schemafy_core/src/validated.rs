@@ -0,0 +1,127 @@
+//! A wrapper which checks the constraints of a generated type (see the
+//! `validate` option) while deserializing it, so that values which parse
+//! but violate their schema are rejected up front.
+
+use alloc::string::ToString;
+use core::ops::Deref;
+
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::validation::{Validate, ValidationErrors};
+
+/// A `T` which satisfied every constraint of its schema when it was
+/// deserialized.
+///
+/// Deserializing fails with an error listing all the violated
+/// constraints, one per line. `from_str` and `from_value` return them as
+/// `ValidationErrors` instead.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ValidatedJson<T>(T);
+
+impl<T> ValidatedJson<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: Validate> ValidatedJson<T> {
+    /// Validates a deserialized `value`.
+    pub fn new(value: T) -> Result<Self, ValidationErrors> {
+        value.validate()?;
+        Ok(ValidatedJson(value))
+    }
+}
+
+impl<T> ValidatedJson<T>
+where
+    T: DeserializeOwned + Validate,
+{
+    /// Parses and validates the JSON text `s`. A value which can not be
+    /// deserialized at all is reported as a single error.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Result<Self, ValidationErrors> {
+        let value = serde_json::from_str(s).map_err(parse_error)?;
+        Self::new(value)
+    }
+
+    pub fn from_value(value: serde_json::Value) -> Result<Self, ValidationErrors> {
+        let value = serde_json::from_value(value).map_err(parse_error)?;
+        Self::new(value)
+    }
+}
+
+fn parse_error(err: serde_json::Error) -> ValidationErrors {
+    let mut errors = ValidationErrors::default();
+    errors.push("", err.to_string());
+    errors
+}
+
+impl<T> Deref for ValidatedJson<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Serialize> Serialize for ValidatedJson<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for ValidatedJson<T>
+where
+    T: Deserialize<'de> + Validate,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = T::deserialize(deserializer)?;
+        value.validate().map_err(serde::de::Error::custom)?;
+        Ok(ValidatedJson(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::validation::join;
+
+    #[derive(Debug, Deserialize)]
+    struct Range {
+        min: i64,
+        max: i64,
+    }
+
+    impl Validate for Range {
+        fn validate_into(&self, path: &str, errors: &mut ValidationErrors) {
+            if self.min < 0 {
+                errors.push(join(path, "min"), "must be at least 0");
+            }
+            if self.max > 9 {
+                errors.push(join(path, "max"), "must be at most 9");
+            }
+        }
+    }
+
+    #[test]
+    fn deserialize() {
+        let range: ValidatedJson<Range> = serde_json::from_str(r#"{"min": 1, "max": 2}"#).unwrap();
+        assert_eq!((range.min, range.max), (1, 2));
+
+        let err =
+            serde_json::from_str::<ValidatedJson<Range>>(r#"{"min": -1, "max": 10}"#).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "/min: must be at least 0\n/max: must be at most 9"
+        );
+    }
+
+    #[test]
+    fn collect_errors() {
+        let errors = ValidatedJson::<Range>::from_str(r#"{"min": -1, "max": 10}"#).unwrap_err();
+        assert_eq!(errors.0.len(), 2);
+
+        let errors = ValidatedJson::<Range>::from_value(serde_json::json!({"min": 1})).unwrap_err();
+        assert_eq!(errors.to_string(), "missing field `max`");
+    }
+}
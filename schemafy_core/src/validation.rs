@@ -0,0 +1,356 @@
+//! Support code for the `validate` methods of generated types, which check
+//! the constraints of a schema that can not be expressed in the Rust types.
+
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+#[cfg(feature = "std")]
+use std::{collections::HashMap, error::Error, sync::Mutex};
+
+#[cfg(feature = "std")]
+use once_cell::sync::Lazy;
+use serde_json::Value;
+
+#[cfg(feature = "std")]
+use crate::pattern;
+
+/// A constraint violated by the value at `path`, a JSON pointer relative to
+/// the validated value.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ValidationError {
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+/// All the constraints violated by a value.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct ValidationErrors(pub Vec<ValidationError>);
+
+impl ValidationErrors {
+    pub fn push(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.0.push(ValidationError {
+            path: path.into(),
+            message: message.into(),
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn into_result(self) -> Result<(), ValidationErrors> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, error) in self.0.iter().enumerate() {
+            if i != 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", error)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for ValidationErrors {}
+
+/// Implemented by generated types to check the constraints of their schema
+/// which the Rust types can not express.
+pub trait Validate {
+    /// Pushes every constraint violated by `self`, which is located at the
+    /// JSON pointer `path`, to `errors`.
+    fn validate_into(&self, path: &str, errors: &mut ValidationErrors);
+
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        self.validate_into("", &mut errors);
+        errors.into_result()
+    }
+}
+
+impl<T: Validate + ?Sized> Validate for Box<T> {
+    fn validate_into(&self, path: &str, errors: &mut ValidationErrors) {
+        (**self).validate_into(path, errors)
+    }
+}
+
+impl<T: Validate> Validate for Option<T> {
+    fn validate_into(&self, path: &str, errors: &mut ValidationErrors) {
+        if let Some(value) = self {
+            value.validate_into(path, errors)
+        }
+    }
+}
+
+impl<T: Validate> Validate for Vec<T> {
+    fn validate_into(&self, path: &str, errors: &mut ValidationErrors) {
+        for (i, value) in self.iter().enumerate() {
+            value.validate_into(&join(path, i), errors)
+        }
+    }
+}
+
+/// Appends `token` to the JSON pointer `path`, escaping it as needed.
+pub fn join(path: &str, token: impl fmt::Display) -> String {
+    let token = token.to_string();
+    format!("{}/{}", path, token.replace('~', "~0").replace('/', "~1"))
+}
+
+/// Returns whether `value` is a multiple of `n`, as required by the
+/// `multipleOf` keyword, allowing for the rounding error of dividing
+/// decimal fractions such as `0.3 / 0.1`.
+pub fn is_multiple_of(value: f64, n: f64) -> bool {
+    let quotient = value / n;
+    (quotient - round(quotient)).abs() <= 1e-9 * quotient.abs().max(1.0)
+}
+
+#[cfg(feature = "std")]
+fn round(x: f64) -> f64 {
+    x.round()
+}
+
+/// `f64::round`, which needs `std`. Floats this large have no fraction.
+#[cfg(not(feature = "std"))]
+fn round(x: f64) -> f64 {
+    if x.abs() >= 4_503_599_627_370_496.0 {
+        x
+    } else if x < 0.0 {
+        (x - 0.5) as i64 as f64
+    } else {
+        (x + 0.5) as i64 as f64
+    }
+}
+
+#[cfg(feature = "std")]
+static SCHEMAS: Lazy<Mutex<HashMap<&'static str, Value>>> = Lazy::new(Default::default);
+
+/// Returns whether `value` is valid against the JSON `schema`.
+///
+/// Only the keywords describing a single value are checked (`type`,
+/// `enum`, `const`, the numeric and string bounds, `pattern`) along with
+/// `properties`, `required`, `items` and the `allOf`/`anyOf`/`oneOf`/`not`
+/// combinators. Other keywords are ignored.
+///
+/// # Panics
+///
+/// If `schema` is not valid JSON.
+#[cfg(feature = "std")]
+pub fn matches(schema: &'static str, value: &Value) -> bool {
+    let mut schemas = SCHEMAS.lock().unwrap_or_else(|err| err.into_inner());
+    let schema = schemas.entry(schema).or_insert_with(|| {
+        serde_json::from_str(schema)
+            .unwrap_or_else(|err| panic!("Invalid schema `{}`: {}", schema, err))
+    });
+    matches_value(schema, value)
+}
+
+/// Without `std` the schema is parsed on every call, and `pattern` is not
+/// checked.
+#[cfg(not(feature = "std"))]
+pub fn matches(schema: &'static str, value: &Value) -> bool {
+    let schema = serde_json::from_str(schema)
+        .unwrap_or_else(|err| panic!("Invalid schema `{}`: {}", schema, err));
+    matches_value(&schema, value)
+}
+
+/// Returns whether any of `items` is valid against the JSON `schema`, as
+/// required by the `contains` keyword.
+pub fn contains<'a, I, T>(schema: &'static str, items: I) -> bool
+where
+    I: IntoIterator<Item = &'a T>,
+    T: serde::Serialize + 'a,
+{
+    items
+        .into_iter()
+        .any(|item| serde_json::to_value(item).is_ok_and(|item| matches(schema, &item)))
+}
+
+fn matches_value(schema: &Value, value: &Value) -> bool {
+    let schema = match schema {
+        Value::Object(schema) => schema,
+        Value::Bool(b) => return *b,
+        _ => return true,
+    };
+    schema
+        .iter()
+        .all(|(keyword, arg)| matches_keyword(schema, keyword, arg, value))
+}
+
+fn matches_keyword(
+    schema: &serde_json::Map<String, Value>,
+    keyword: &str,
+    arg: &Value,
+    value: &Value,
+) -> bool {
+    let exclusive = |name: &str| schema.get(name) == Some(&Value::Bool(true));
+    match (keyword, arg) {
+        ("type", Value::String(typ)) => matches_type(typ, value),
+        ("type", Value::Array(types)) => {
+            types.is_empty()
+                || types
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .any(|typ| matches_type(typ, value))
+        }
+        ("enum", Value::Array(values)) => values.contains(value),
+        ("const", arg) => arg == value,
+        ("minimum", Value::Number(min)) => match (min.as_f64(), value.as_f64()) {
+            (Some(min), Some(v)) if exclusive("exclusiveMinimum") => v > min,
+            (Some(min), Some(v)) => v >= min,
+            _ => true,
+        },
+        ("maximum", Value::Number(max)) => match (max.as_f64(), value.as_f64()) {
+            (Some(max), Some(v)) if exclusive("exclusiveMaximum") => v < max,
+            (Some(max), Some(v)) => v <= max,
+            _ => true,
+        },
+        ("exclusiveMinimum", Value::Number(min)) => match value.as_f64() {
+            Some(v) => Some(v) > min.as_f64(),
+            None => true,
+        },
+        ("exclusiveMaximum", Value::Number(max)) => match value.as_f64() {
+            Some(v) => Some(v) < max.as_f64(),
+            None => true,
+        },
+        ("multipleOf", Value::Number(n)) => match (n.as_f64(), value.as_f64()) {
+            (Some(n), Some(v)) => is_multiple_of(v, n),
+            _ => true,
+        },
+        ("minLength", Value::Number(min)) => match value.as_str() {
+            Some(s) => Some(s.chars().count() as u64) >= min.as_u64(),
+            None => true,
+        },
+        ("maxLength", Value::Number(max)) => match value.as_str() {
+            Some(s) => Some(s.chars().count() as u64) <= max.as_u64(),
+            None => true,
+        },
+        #[cfg(feature = "std")]
+        ("pattern", Value::String(regex)) => match value.as_str() {
+            Some(s) => pattern::is_match(regex, s),
+            None => true,
+        },
+        ("properties", Value::Object(properties)) => match value {
+            Value::Object(value) => properties
+                .iter()
+                .all(|(name, schema)| match value.get(name) {
+                    Some(value) => matches_value(schema, value),
+                    None => true,
+                }),
+            _ => true,
+        },
+        ("required", Value::Array(required)) => match value {
+            Value::Object(value) => required
+                .iter()
+                .filter_map(Value::as_str)
+                .all(|name| value.contains_key(name)),
+            _ => true,
+        },
+        ("items", items @ Value::Object(_)) => match value {
+            Value::Array(value) => value.iter().all(|value| matches_value(items, value)),
+            _ => true,
+        },
+        ("allOf", Value::Array(schemas)) => schemas.iter().all(|s| matches_value(s, value)),
+        ("anyOf", Value::Array(schemas)) => schemas.iter().any(|s| matches_value(s, value)),
+        ("oneOf", Value::Array(schemas)) => {
+            schemas.iter().filter(|s| matches_value(s, value)).count() == 1
+        }
+        ("not", schema) => !matches_value(schema, value),
+        _ => true,
+    }
+}
+
+fn matches_type(typ: &str, value: &Value) -> bool {
+    match typ {
+        "array" => value.is_array(),
+        "boolean" => value.is_boolean(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|f| round(f) == f)
+        }
+        "null" => value.is_null(),
+        "number" => value.is_number(),
+        "object" => value.is_object(),
+        "string" => value.is_string(),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::vec;
+
+    use serde_json::json;
+
+    #[test]
+    fn matches_keywords() {
+        assert!(matches(r#"{"const": "a"}"#, &json!("a")));
+        assert!(!matches(r#"{"const": "a"}"#, &json!("b")));
+        assert!(matches(r#"{"type": "integer", "minimum": 2}"#, &json!(2)));
+        assert!(!matches(
+            r#"{"type": "integer", "minimum": 2, "exclusiveMinimum": true}"#,
+            &json!(2)
+        ));
+        assert!(!matches(r#"{"type": "integer"}"#, &json!("2")));
+        assert!(matches(r#"{"pattern": "^a"}"#, &json!("abc")));
+        assert!(!matches(r#"{"enum": [1, 2]}"#, &json!(3)));
+        assert!(matches(
+            r#"{"properties": {"id": {"type": "string"}}, "required": ["id"]}"#,
+            &json!({"id": "x"})
+        ));
+        assert!(!matches(r#"{"required": ["id"]}"#, &json!({})));
+    }
+
+    #[test]
+    fn contains_item() {
+        assert!(contains(r#"{"const": 2}"#, &vec![1, 2, 3]));
+        assert!(!contains(r#"{"const": 4}"#, &vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn multiple_of() {
+        assert!(is_multiple_of(0.3, 0.1));
+        assert!(is_multiple_of(45.0, 15.0));
+        assert!(is_multiple_of(-10.0, 2.5));
+        assert!(!is_multiple_of(0.35, 0.1));
+        assert!(!is_multiple_of(7.0, 2.0));
+    }
+
+    #[test]
+    fn join_pointer() {
+        assert_eq!(join("", "a"), "/a");
+        assert_eq!(join("/a", 0), "/a/0");
+        assert_eq!(join("/a", "b/c~d"), "/a/b~1c~0d");
+    }
+
+    #[test]
+    fn errors() {
+        let mut errors = ValidationErrors::default();
+        assert_eq!(errors.clone().into_result(), Ok(()));
+        errors.push("/a/0", "is too large");
+        errors.push("", "is invalid");
+        assert_eq!(errors.to_string(), "/a/0: is too large\nis invalid");
+    }
+}
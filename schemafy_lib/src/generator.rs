@@ -1,12 +1,83 @@
-
-
-
-use crate::Expander;
+use crate::{
+    naming::{AcronymNaming, DefaultNaming, COMMON_ACRONYMS},
+    openapi, str_to_ident, Expander, ExternalSchema, NamingStrategy, Schema, TypeMapper,
+};
+use inflector::Inflector;
 use std::{
-    io,
+    collections::BTreeMap,
+    fmt, io,
     path::{Path, PathBuf},
 };
 
+/// The reasons generating types from a schema can fail.
+#[derive(Debug)]
+pub enum Error {
+    /// The schema file could not be read.
+    Read(PathBuf, io::Error),
+    /// The schema is not valid JSON, or not a JSON schema. The first field
+    /// describes where the schema came from.
+    Parse(String, serde_json::Error),
+    /// A `$ref` which does not point into the schema.
+    Ref(String),
+    /// With `strict` set, the places where the generated code would not
+    /// represent the schema exactly.
+    Strict(Vec<String>),
+    /// The generated code could not be written.
+    Write(PathBuf, io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Read(path, err) => {
+                write!(f, "Unable to read `{}`: {}", path.to_string_lossy(), err)
+            }
+            Error::Parse(source, err) => write!(f, "Cannot parse {} as JSON: {}", source, err),
+            Error::Ref(err) => err.fmt(f),
+            Error::Strict(warnings) => write!(
+                f,
+                "The schema can not be represented exactly:\n{}",
+                warnings.join("\n")
+            ),
+            Error::Write(path, err) => {
+                write!(f, "Unable to write `{}`: {}", path.to_string_lossy(), err)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Read(_, err) | Error::Write(_, err) => Some(err),
+            Error::Parse(_, err) => Some(err),
+            Error::Ref(_) | Error::Strict(_) => None,
+        }
+    }
+}
+
+/// A function rewriting each schema after it is read and before types are
+/// generated from it (see [`GeneratorBuilder::with_preprocessor`]).
+pub struct Preprocessor(Box<dyn Fn(&mut Schema)>);
+
+impl Preprocessor {
+    pub fn new<F: Fn(&mut Schema) + 'static>(f: F) -> Self {
+        Preprocessor(Box::new(f))
+    }
+}
+
+impl fmt::Debug for Preprocessor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Preprocessor(..)")
+    }
+}
+
+impl PartialEq for Preprocessor {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::addr_eq(&*self.0, &*other.0)
+    }
+}
+
 /// A configurable builder for generating Rust types from a JSON
 /// schema.
 ///
@@ -17,7 +88,9 @@ use std::{
 pub struct Generator<'a, 'b> {
     /// The name of the root type defined by the schema. If the schema
     /// does not define a root type (some schemas are simply a
-    /// collection of definitions) then simply pass `None`.
+    /// collection of definitions) then simply pass `None`, and the root
+    /// type is then named after the `title` of the schema, if it has one
+    /// which is not the name of a definition.
     pub root_name: Option<String>,
     /// The module path to this crate. Some generated code may make
     /// use of types defined in this crate. Unless you have
@@ -26,6 +99,232 @@ pub struct Generator<'a, 'b> {
     pub schemafy_path: &'a str,
     /// The JSON schema file to read
     pub input_file: &'b Path,
+    /// The JSON schema itself. If set, `input_file` is not read.
+    pub input_schema: Option<&'b str>,
+    /// Several JSON schema files to generate types from instead of
+    /// `input_file`. The types of each file are put in a module named
+    /// after it (`events` for `events.json`), and `$ref`s to the other
+    /// files (by their file name or `$id`) refer to the types in their
+    /// modules, so the schemas may define types of the same name.
+    pub input_files: Vec<&'b Path>,
+    /// The Rust type used for `number` schemas with `"format":
+    /// "decimal"`. Defaults to `serde_json::Number`, which keeps the
+    /// exact digits when serde_json's `arbitrary_precision` feature is
+    /// enabled; `rust_decimal::Decimal` is a common alternative.
+    pub decimal_type: String,
+    /// Use `decimal_type` for every `number` schema instead of `f64`,
+    /// regardless of its `format`.
+    pub all_numbers_decimal: bool,
+    /// Rust types to use for schemas with a given `format`, e.g.
+    /// `"ipv4"` to `std::net::Ipv4Addr`. These take precedence over
+    /// the types normally chosen for the schema's `type`.
+    pub formats: BTreeMap<String, String>,
+    /// Modules to (de)serialize the schemas with a given `format` with
+    /// (`#[serde(with = "...")]`), e.g. `"unix-time"` to
+    /// `schemafy_core::timestamp::seconds`, usually along with `formats`.
+    pub format_modules: BTreeMap<String, String>,
+    /// Extra traits to derive for every generated type, such as `Eq` or
+    /// `Hash`. A derive named like one of the defaults (such as
+    /// `serde::Deserialize`) replaces it instead.
+    pub derives: Vec<String>,
+    /// Extra attributes, such as `#[non_exhaustive]`, for the types
+    /// generated from the given definitions (by their name in the schema
+    /// or in Rust).
+    pub type_attributes: BTreeMap<String, Vec<String>>,
+    /// Existing Rust types, such as `chrono::DateTime<chrono::Utc>`, to
+    /// use for the given definitions (by their name in the schema or in
+    /// Rust) instead of generating them.
+    pub replacements: BTreeMap<String, String>,
+    /// Names to use instead of the generated ones, for definitions (given
+    /// as `definitions/foo-bar`) and fields (given as `Type.field`, by the
+    /// name of the type in the schema or in Rust and of the field in the
+    /// schema).
+    pub renames: BTreeMap<String, String>,
+    /// Acronyms, such as `HTTP` or `URL`, which are written like words in
+    /// the names of types, fields and variants (`HttpServerUrl` rather
+    /// than `HttpserverUrl`), whatever the `naming_strategy`.
+    pub acronyms: Vec<String>,
+    /// The visibility of the generated types and their fields, such as
+    /// `pub(crate)` or an empty string for private items. Defaults to
+    /// `pub`.
+    pub visibility: String,
+    /// Prepended to the name of every generated type, so that types
+    /// generated from different schemas (say `DapRequest` and
+    /// `LspRequest`) can live in the same module.
+    pub prefix: String,
+    /// Appended to the name of every generated type, such as `V2`.
+    pub suffix: String,
+    /// Wrap the generated items in a module of this name, which imports
+    /// the serde derives they use.
+    pub module: Option<String>,
+    /// Decode strings with `"contentMediaType": "application/json"`
+    /// into the type described by their `contentSchema` (or
+    /// `serde_json::Value` if there is none) instead of keeping them
+    /// as `String`.
+    pub decode_json_strings: bool,
+    /// The set type, e.g. `::std::collections::BTreeSet`, to use
+    /// instead of `Vec` for arrays with `"uniqueItems": true`. Only
+    /// used when the item type can be stored in a set.
+    pub set_type: Option<String>,
+    /// The map type used for objects with `additionalProperties`.
+    /// Defaults to `::std::collections::BTreeMap`; `HashMap` or
+    /// `indexmap::IndexMap` (which keeps the JSON key order) can be used
+    /// instead.
+    pub map_type: String,
+    /// Generate an empty struct, rather than a map, for object schemas
+    /// without any `properties`, `patternProperties` or
+    /// `additionalProperties`.
+    pub empty_structs: bool,
+    /// Add `#[serde(deny_unknown_fields)]` to every struct whose other
+    /// members are not kept in a map, rather than only to those whose schema
+    /// has `"additionalProperties": false`.
+    pub deny_unknown_fields: bool,
+    /// Mark the structs of schemas which allow other members (without
+    /// `"additionalProperties": false`) and the enums `#[non_exhaustive]`.
+    pub non_exhaustive: bool,
+    /// Skip serializing the fields with `"readOnly": true` and deserializing
+    /// those with `"writeOnly": true`, as a client of an API does.
+    pub read_write_only: bool,
+    /// Generate `Cow<'a, str>` instead of `String` for strings, borrowed from
+    /// the input with `#[serde(borrow)]` when possible, and a lifetime `'a` on
+    /// the types holding them.
+    pub borrowed_strings: bool,
+    /// Derive `PartialOrd` and `Ord` for the structs and untagged enums whose
+    /// members implement them, so that generated records can be sorted.
+    pub derive_ord: bool,
+    /// Generate a builder for each struct, with a setter per field, so that
+    /// only the fields which are set need to be written.
+    pub builders: bool,
+    /// Generate a `new` function for each struct taking the fields which have
+    /// no default, the others being `None` or their `default`.
+    pub constructors: bool,
+    /// Generate code for `no_std` crates with `extern crate alloc`, using the
+    /// `core` and `alloc` paths instead of the `std` ones. The generated
+    /// files and modules import `String`, `Vec`, `Box` and `ToString`.
+    pub no_std: bool,
+    /// Derive `schemars::JsonSchema` for every generated type, so that the schema
+    /// can be emitted again from the Rust types, such as for OpenAPI documents.
+    pub json_schema: bool,
+    /// Derive `arbitrary::Arbitrary` for the generated types whose members
+    /// implement it when the `arbitrary` feature of the generated crate is
+    /// enabled, so that fuzz targets can build values of the schema's shape.
+    pub arbitrary: bool,
+    /// Generate `schemafy_core::raw::RawJson`, which keeps the exact text of the
+    /// value, instead of `serde_json::Value` for free-form JSON, so that large
+    /// opaque payloads are neither parsed into a tree nor reformatted. Requires
+    /// the `raw_value` feature of `schemafy_core`.
+    pub raw_value: bool,
+    /// Generate `schemafy_core::Nullable` instead of `Option` for the optional
+    /// fields which may also be `null`, so that an absent field can be told
+    /// apart from an explicit `null`.
+    pub nullable_fields: bool,
+    /// Generate `ordered_float::OrderedFloat<f64>` instead of `f64` for numbers,
+    /// so that the types holding them can derive `Eq` and `Hash`. Requires the
+    /// `ordered-float` crate with its `serde` feature.
+    pub ordered_float: bool,
+    /// Generate a `FooRead` struct without the `writeOnly` fields and a
+    /// `FooWrite` struct without the `readOnly` ones, with `From<Foo>`, for each
+    /// struct `Foo` which has such fields.
+    pub read_write_variants: bool,
+    /// Serialize the fields accepting one item or an array of them always as an
+    /// array, instead of writing a single item without the array.
+    pub one_or_many_arrays: bool,
+    /// Generate `schemafy_core::OneOrMany<T>` for the fields accepting one item or an
+    /// array of them, which remembers the form received, instead of `Vec<T>`.
+    pub one_or_many_type: bool,
+    /// Generate `Vec<u8>` for `string` schemas with `"format": "byte"`, (de)serialized
+    /// as base64 through `schemafy_core::base64_bytes`.
+    pub base64_bytes: bool,
+    /// Generate `schemafy_core::Either` or `Union3` for `anyOf` with two or three
+    /// variants and `type` arrays with two or three types, instead of
+    /// `serde_json::Value`.
+    pub union_types: bool,
+    /// Generate a single struct for the structurally identical inline objects, and
+    /// type aliases named after the other ones.
+    pub dedup_types: bool,
+    /// Name the inline objects and `oneOf` variants with a `title` after it instead of
+    /// after their parent and field, unless another type is named so.
+    pub title_names: bool,
+    /// Name the anonymous types after their JSON pointer from the definition holding
+    /// them, such as `ConfigServersItemTls`, instead of after their parent and field.
+    pub pointer_names: bool,
+    /// Fields named after a keyword, such as `type`, are raw identifiers
+    /// (`r#type`) rather than having a trailing underscore (`type_`).
+    pub raw_identifiers: bool,
+    /// Derive `Copy` for the enums generated from `enum`, which have no fields
+    /// (on by default).
+    pub copy_enums: bool,
+    /// Implement `Validate` for each struct, checking the constraints
+    /// (such as `minimum` or `pattern`) which its type does not enforce.
+    pub validate: bool,
+    /// Generate `#[repr(i64)]` enums deriving `Serialize_repr` and
+    /// `Deserialize_repr` for integer schemas with an `enum`, rather than
+    /// using `i64`. Requires the `serde_repr` derives to be in scope.
+    pub int_enums: bool,
+    /// Add an `Unknown(String)` variant to string enums, so that values
+    /// added to the schema later still deserialize, and serialize back
+    /// unchanged.
+    pub open_enums: bool,
+    /// Generate a unit struct, which always serializes as the value, for
+    /// string schemas with a single value in their `enum`, rather than an
+    /// enum with one variant. Ignored with `open_enums`.
+    pub single_value_structs: bool,
+    /// Deserialize string enums from values which only match one of the
+    /// schema's values when ignoring ASCII case.
+    pub case_insensitive_enums: bool,
+    /// Ignore leading and trailing whitespace when deserializing string
+    /// enums.
+    pub trim_enums: bool,
+    /// Generate a newtype around `String` for string schemas with a
+    /// `pattern`, which rejects non-matching strings when deserialized
+    /// or converted with `TryFrom`.
+    pub pattern_types: bool,
+    /// Use `schemafy_core::bounded::Bounded` for integers (and generate
+    /// newtypes for numbers) with a `minimum` or `maximum`, so that out of
+    /// range values fail to deserialize.
+    pub bounded_numbers: bool,
+    /// Generate a newtype around `String` for string schemas with a
+    /// `minLength` or `maxLength`, which rejects strings of other lengths
+    /// when deserialized or converted with `TryFrom`.
+    pub length_types: bool,
+    /// Derive `validator::Validate` for the generated structs and add
+    /// `#[validate(..)]` attributes checking the length, range and pattern
+    /// constraints of their fields.
+    pub validator_attributes: bool,
+    /// Implement `TryFrom<serde_json::Value>` for the generated structs and
+    /// enums, reporting every missing or mismatched field with its JSON
+    /// pointer rather than only the first as serde does.
+    pub try_from_value: bool,
+    /// Embed the schema in the generated code and add a `validate_json`
+    /// function to the root and definition types, which validates a JSON
+    /// value with the `jsonschema` crate before deserializing it. Requires
+    /// the `jsonschema` feature of `schemafy_core`.
+    pub embed_schema: bool,
+    /// Give fields snake case names and put a single
+    /// `#[serde(rename_all = "camelCase")]` (or another rule) on a struct
+    /// when all of its fields follow that convention, rather than keeping
+    /// the names of the schema with a `#[serde(rename)]` on every field.
+    pub rename_all: bool,
+    /// Fail, listing the JSON pointers of the offending schemas, instead of
+    /// falling back to `serde_json::Value` for schemas which can not be
+    /// represented exactly (such as an `anyOf` of several types) or
+    /// ignoring keywords such as `if` and `not`.
+    pub strict: bool,
+    /// Generate a `GENERATION_REPORT` constant listing everything which
+    /// the generated code does not represent exactly, such as constraints
+    /// which are not checked, enums generated as plain strings or ignored
+    /// keywords, each with the JSON pointer of its schema.
+    pub report: bool,
+    /// Functions applied, in order, to every schema before types are
+    /// generated from it. Keywords which are checked on the JSON itself
+    /// (by `strict`, `report` and `embed_schema`) are not affected.
+    pub preprocessors: Vec<Preprocessor>,
+    /// Derives the names of the generated types, fields and enum variants
+    /// from those of the schema.
+    pub naming_strategy: &'a dyn NamingStrategy,
+    /// Consulted, in order, for the type of every field, array item and
+    /// other subschema before the built-in rules.
+    pub type_mappers: Vec<&'a dyn TypeMapper>,
 }
 
 impl<'a, 'b> Generator<'a, 'b> {
@@ -34,46 +333,389 @@ impl<'a, 'b> Generator<'a, 'b> {
         GeneratorBuilder::default()
     }
 
+    /// Generates the types, panicking if the schema can not be read or
+    /// is invalid.
     pub fn generate(&self) -> proc_macro2::TokenStream {
-        let input_file = if self.input_file.is_relative() {
-            let crate_root = get_crate_root().unwrap();
-            crate_root.join(self.input_file)
+        self.try_generate().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_generate(&self) -> Result<proc_macro2::TokenStream, Error> {
+        let (tokens, uses_serde_repr) = if self.input_files.is_empty() {
+            let (json, schema) = self.read_input_schema()?;
+            let (items, uses_serde_repr) = self.expand(&json, &schema, Vec::new(), false)?;
+            (
+                items.into_iter().map(|(_, tokens)| tokens).collect(),
+                uses_serde_repr,
+            )
         } else {
-            PathBuf::from(self.input_file)
+            let mut tokens = proc_macro2::TokenStream::new();
+            for (module, module_tokens, uses_serde_repr) in self.expand_input_files()? {
+                tokens.extend(self.wrap_module(&module, module_tokens, true, uses_serde_repr));
+            }
+            (tokens, false)
         };
+        Ok(match &self.module {
+            Some(module) => {
+                self.wrap_module(module, tokens, self.input_files.is_empty(), uses_serde_repr)
+            }
+            None => tokens,
+        })
+    }
+
+    /// Generates the types into a module per top-level definition, which
+    /// also holds the definitions and inline types nested in it, returning
+    /// the name and the formatted source of each module. The other types
+    /// of the root schema get a module named after the root. With several
+    /// `input_files`, there is a module per file instead.
+    ///
+    /// Each module imports the others (and, for `input_files`, refers to
+    /// them) through `super`, so they are meant to be declared by the same
+    /// parent module, as `generate_to_dir` does. `module` is ignored.
+    pub fn try_generate_modules(&self) -> Result<Vec<(String, String)>, Error> {
+        let modules = if self.input_files.is_empty() {
+            let (json, schema) = self.read_input_schema()?;
+            let (items, uses_serde_repr) = self.expand(&json, &schema, Vec::new(), true)?;
+            items
+                .into_iter()
+                .map(|(module, tokens)| (module, tokens, uses_serde_repr))
+                .collect()
+        } else {
+            self.expand_input_files()?
+        };
+        let glob_import = if self.input_files.is_empty() {
+            Some(quote! {
+                #[allow(unused_imports)]
+                use super::*;
+            })
+        } else {
+            None
+        };
+        Ok(modules
+            .into_iter()
+            .map(|(module, tokens, uses_serde_repr)| {
+                let serde_repr = if uses_serde_repr {
+                    Some(quote! {
+                        #[allow(unused_imports)]
+                        use serde_repr::{Deserialize_repr, Serialize_repr};
+                    })
+                } else {
+                    None
+                };
+                let alloc = self.alloc_imports();
+                let source = quote! {
+                    #![allow(non_snake_case)]
+                    #glob_import
+                    #[allow(unused_imports)]
+                    use serde::{Deserialize, Serialize};
+                    #serde_repr
+                    #alloc
+                    #tokens
+                };
+                (module, format_source(source.to_string()))
+            })
+            .collect())
+    }
 
-        let json = std::fs::read_to_string(&input_file).unwrap_or_else(|err| {
-            panic!("Unable to read `{}`: {}", input_file.to_string_lossy(), err)
-        });
+    /// Writes the modules of `try_generate_modules` to `<module>.rs` files
+    /// in `dir`, along with a `mod.rs` declaring them. The types of the
+    /// modules of a single schema are re-exported by `mod.rs`.
+    pub fn generate_to_dir<P: ?Sized + AsRef<Path>>(&self, dir: &P) -> Result<(), Error> {
+        let dir = dir.as_ref();
+        let write = |path: PathBuf, source: &str| {
+            std::fs::write(&path, source).map_err(|err| Error::Write(path, err))
+        };
+        std::fs::create_dir_all(dir).map_err(|err| Error::Write(dir.to_path_buf(), err))?;
+        let mut declarations = String::new();
+        for (module, source) in self.try_generate_modules()? {
+            write(dir.join(format!("{}.rs", module)), &source)?;
+            if self.input_files.is_empty() {
+                declarations.push_str(&format!("mod {0};\npub use self::{0}::*;\n", module));
+            } else {
+                declarations.push_str(&format!("pub mod {};\n", module));
+            }
+        }
+        write(dir.join("mod.rs"), &declarations)
+    }
 
-        let schema = serde_json::from_str(&json).unwrap_or_else(|err| {
-            panic!(
-                "Cannot parse `{}` as JSON: {}",
-                input_file.to_string_lossy(),
-                err
-            )
-        });
-        let mut expander = Expander::new(self.root_name.as_deref(), self.schemafy_path, &schema);
-        expander.expand(&schema)
+    /// Reads and parses `input_schema` or `input_file`, returning its JSON
+    /// along with the preprocessed schema.
+    fn read_input_schema(&self) -> Result<(String, Schema), Error> {
+        let (json, source) = match self.input_schema {
+            Some(json) => (json.to_string(), "the schema".to_string()),
+            None => read_schema(self.input_file)?,
+        };
+        self.parse_schema(json, source)
     }
 
-    pub fn generate_to_file<P: ?Sized + AsRef<Path>>(&self, output_file: &'b P) -> io::Result<()> {
-        use std::process::Command;
-        
+    /// Parses the schema `json`, read from `source`, returning its JSON
+    /// along with the preprocessed schema. The schemas of an OpenAPI
+    /// document are read as the definitions of a JSON schema.
+    fn parse_schema(&self, json: String, source: String) -> Result<(String, Schema), Error> {
+        let value = serde_json::from_str::<serde_json::Value>(&json)
+            .map_err(|err| Error::Parse(source.clone(), err))?;
+        let json = if openapi::is_openapi(&value) {
+            openapi::to_json_schema(&value).to_string()
+        } else if openapi::is_crd(&value) {
+            openapi::crd_to_json_schema(&value).to_string()
+        } else {
+            json
+        };
+        let mut schema = serde_json::from_str(&json).map_err(|err| Error::Parse(source, err))?;
+        self.preprocess(&mut schema);
+        Ok((json, schema))
+    }
+
+    /// Expands each of `input_files`, returning the name of its module,
+    /// its items and whether they use the `serde_repr` derives.
+    fn expand_input_files(&self) -> Result<Vec<(String, proc_macro2::TokenStream, bool)>, Error> {
+        let documents = self
+            .input_files
+            .iter()
+            .map(|input_file| {
+                let (json, source) = read_schema(input_file)?;
+                let (json, schema) = self.parse_schema(json, source)?;
+                let file_name = input_file
+                    .file_name()
+                    .map_or(String::new(), |name| name.to_string_lossy().into_owned());
+                let module = input_file.file_stem().map_or(String::new(), |stem| {
+                    str_to_ident(&stem.to_string_lossy().to_snake_case()).to_string()
+                });
+                Ok((file_name, module, json, schema))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let mut modules = Vec::new();
+        for (i, (_, module, json, schema)) in documents.iter().enumerate() {
+            let external_schemas = documents
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| i != j)
+                .map(|(_, (file_name, module, _, schema))| ExternalSchema {
+                    file_name: file_name.clone(),
+                    id: schema.id.clone(),
+                    root: schema,
+                    module: format!("super::{}::", module),
+                })
+                .collect();
+            let (items, uses_serde_repr) = self.expand(json, schema, external_schemas, false)?;
+            let tokens = items.into_iter().map(|(_, tokens)| tokens).collect();
+            modules.push((module.clone(), tokens, uses_serde_repr));
+        }
+        Ok(modules)
+    }
+
+    fn preprocess(&self, schema: &mut Schema) {
+        for preprocessor in &self.preprocessors {
+            (preprocessor.0)(schema);
+        }
+    }
+
+    /// Expands the schema `json`, returning the generated items, split into
+    /// a module per definition if `split` is set, and whether they use the
+    /// `serde_repr` derives.
+    fn expand<'s>(
+        &'s self,
+        json: &str,
+        schema: &'s Schema,
+        external_schemas: Vec<ExternalSchema<'s>>,
+        split: bool,
+    ) -> Result<(Vec<(String, proc_macro2::TokenStream)>, bool), Error> {
+        let value = serde_json::from_str::<serde_json::Value>(json).unwrap();
+        let embedded_schema = if self.embed_schema {
+            Some(value.to_string())
+        } else {
+            None
+        };
+        let acronym_naming = AcronymNaming::new(&self.acronyms).with_inner(self.naming_strategy);
+        let mut expander = Expander::new(self.root_name.as_deref(), self.schemafy_path, schema);
+        expander.decimal_type = &self.decimal_type;
+        expander.visibility = &self.visibility;
+        expander.type_prefix = &self.prefix;
+        expander.type_suffix = &self.suffix;
+        expander.all_numbers_decimal = self.all_numbers_decimal;
+        expander.formats.clone_from(&self.formats);
+        expander.format_modules.clone_from(&self.format_modules);
+        expander.derives.clone_from(&self.derives);
+        expander.type_attributes.clone_from(&self.type_attributes);
+        expander.replacements.clone_from(&self.replacements);
+        expander.renames.clone_from(&self.renames);
+        expander.decode_json_strings = self.decode_json_strings;
+        expander.set_type = self.set_type.as_deref();
+        expander.map_type = &self.map_type;
+        expander.naming = if self.acronyms.is_empty() {
+            self.naming_strategy
+        } else {
+            &acronym_naming
+        };
+        expander.type_mappers.clone_from(&self.type_mappers);
+        expander.raw_root = Some(&value);
+        expander.empty_structs = self.empty_structs;
+        expander.deny_unknown_fields = self.deny_unknown_fields;
+        expander.non_exhaustive = self.non_exhaustive;
+        expander.read_write_only = self.read_write_only;
+        expander.borrowed_strings = self.borrowed_strings;
+        expander.derive_ord = self.derive_ord;
+        expander.builders = self.builders;
+        expander.constructors = self.constructors;
+        expander.no_std = self.no_std;
+        expander.json_schema = self.json_schema;
+        expander.arbitrary = self.arbitrary;
+        expander.raw_value = self.raw_value;
+        expander.nullable_fields = self.nullable_fields;
+        expander.ordered_float = self.ordered_float;
+        expander.read_write_variants = self.read_write_variants;
+        expander.one_or_many_arrays = self.one_or_many_arrays;
+        expander.one_or_many_type = self.one_or_many_type;
+        expander.base64_bytes = self.base64_bytes;
+        expander.union_types = self.union_types;
+        expander.dedup_types = self.dedup_types;
+        expander.title_names = self.title_names;
+        expander.pointer_names = self.pointer_names;
+        expander.raw_identifiers = self.raw_identifiers;
+        expander.copy_enums = self.copy_enums;
+        expander.validate = self.validate;
+        expander.int_enums = self.int_enums;
+        expander.open_enums = self.open_enums;
+        expander.single_value_structs = self.single_value_structs;
+        expander.case_insensitive_enums = self.case_insensitive_enums;
+        expander.trim_enums = self.trim_enums;
+        expander.pattern_types = self.pattern_types;
+        expander.bounded_numbers = self.bounded_numbers;
+        expander.length_types = self.length_types;
+        expander.validator_attributes = self.validator_attributes;
+        expander.try_from_value = self.try_from_value;
+        expander.rename_all = self.rename_all;
+        expander.embedded_schema = embedded_schema.as_deref();
+        expander.external_schemas = external_schemas;
+        // Most standalone schemas name their root type with their `title`
+        expander.root_name = expander.root_name.or_else(|| expander.title_root_name());
+        expander.check_refs().map_err(Error::Ref)?;
+        let mut items = if split {
+            expander.expand_modules(schema)
+        } else {
+            vec![(String::new(), expander.expand(schema))]
+        };
+        if self.strict || self.report {
+            let mut warnings = crate::ignored_keywords(&value);
+            warnings.extend_from_slice(expander.warnings());
+            if self.strict && !warnings.is_empty() {
+                return Err(Error::Strict(warnings));
+            }
+            if self.report {
+                warnings.extend_from_slice(expander.notes());
+                let vis = expander.visibility();
+                items.push((
+                    "generation_report".into(),
+                    quote! {
+                        /// Everything in the schema which the generated code does
+                        /// not represent exactly, each with its JSON pointer.
+                        #vis const GENERATION_REPORT: &[&str] = &[#(#warnings),*];
+                    },
+                ));
+            }
+        }
+        Ok((items, expander.uses_serde_repr))
+    }
+
+    /// Wraps `tokens` in the module `name`, which imports the derives they
+    /// use.
+    fn wrap_module(
+        &self,
+        name: &str,
+        tokens: proc_macro2::TokenStream,
+        serde: bool,
+        serde_repr: bool,
+    ) -> proc_macro2::TokenStream {
+        let module = syn::Ident::new(name, proc_macro2::Span::call_site());
+        let vis = self
+            .visibility
+            .parse::<proc_macro2::TokenStream>()
+            .unwrap_or_else(|err| panic!("Invalid visibility `{}`: {}", self.visibility, err));
+        let serde = if serde {
+            Some(quote! { use serde::{Deserialize, Serialize}; })
+        } else {
+            None
+        };
+        let serde_repr = if serde_repr {
+            Some(quote! { use serde_repr::{Deserialize_repr, Serialize_repr}; })
+        } else {
+            None
+        };
+        let alloc = self.alloc_imports();
+        quote! {
+            #vis mod #module {
+                #serde
+                #serde_repr
+                #alloc
+                #tokens
+            }
+        }
+    }
+
+    /// The imports of the `alloc` types used by the generated code, which
+    /// are not in the prelude of `no_std` crates.
+    fn alloc_imports(&self) -> Option<proc_macro2::TokenStream> {
+        if self.no_std {
+            Some(quote! {
+                #[allow(unused_imports)]
+                use alloc::{
+                    boxed::Box,
+                    string::{String, ToString},
+                    vec::Vec,
+                };
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Generates the source of a Rust file holding the types, formatted
+    /// with `rustfmt` if it is installed.
+    pub fn try_generate_source(&self) -> Result<String, Error> {
         let mut tokens = quote! {
             #![allow(non_snake_case)]
             use serde::Serialize;
             use serde::Deserialize;
         };
+        tokens.extend(self.alloc_imports());
 
-        tokens.extend(self.generate()); 
+        tokens.extend(self.try_generate()?);
 
-        let out = tokens.to_string();
-        std::fs::write(output_file, &out)?;
-        Command::new("rustfmt")
-            .arg(output_file.as_ref().as_os_str())
-            .output()?;
-        Ok(())
+        Ok(format_source(tokens.to_string()))
+    }
+
+    pub fn generate_to_file<P: ?Sized + AsRef<Path>>(&self, output_file: &'b P) -> io::Result<()> {
+        let out = self
+            .try_generate_source()
+            .unwrap_or_else(|err| panic!("{}", err));
+        std::fs::write(output_file, out)
+    }
+}
+
+/// Formats `source` with `rustfmt`, returning it unchanged if that fails.
+fn format_source(source: String) -> String {
+    use std::{
+        io::Write,
+        process::{Command, Stdio},
+    };
+
+    let child = Command::new("rustfmt")
+        .args(["--edition", "2018", "--emit", "stdout"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn();
+    let mut child = match child {
+        Ok(child) => child,
+        Err(_) => return source,
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        if stdin.write_all(source.as_bytes()).is_err() {
+            return source;
+        }
+    }
+    match child.wait_with_output() {
+        Ok(output) if output.status.success() => String::from_utf8(output.stdout).unwrap_or(source),
+        _ => source,
     }
 }
 
@@ -90,6 +732,66 @@ impl<'a, 'b> Default for GeneratorBuilder<'a, 'b> {
                 root_name: None,
                 schemafy_path: "::schemafy_core::",
                 input_file: Path::new("schema.json"),
+                input_schema: None,
+                input_files: Vec::new(),
+                decimal_type: "serde_json::Number".into(),
+                all_numbers_decimal: false,
+                formats: BTreeMap::new(),
+                format_modules: BTreeMap::new(),
+                derives: Vec::new(),
+                type_attributes: BTreeMap::new(),
+                replacements: BTreeMap::new(),
+                renames: BTreeMap::new(),
+                acronyms: Vec::new(),
+                visibility: "pub".into(),
+                prefix: String::new(),
+                suffix: String::new(),
+                module: None,
+                decode_json_strings: false,
+                set_type: None,
+                map_type: "::std::collections::BTreeMap".into(),
+                empty_structs: false,
+                deny_unknown_fields: false,
+                non_exhaustive: false,
+                read_write_only: false,
+                borrowed_strings: false,
+                derive_ord: false,
+                builders: false,
+                constructors: false,
+                no_std: false,
+                json_schema: false,
+                arbitrary: false,
+                raw_value: false,
+                nullable_fields: false,
+                ordered_float: false,
+                read_write_variants: false,
+                one_or_many_arrays: false,
+                one_or_many_type: false,
+                base64_bytes: false,
+                union_types: false,
+                dedup_types: false,
+                title_names: false,
+                pointer_names: false,
+                raw_identifiers: false,
+                copy_enums: true,
+                validate: false,
+                int_enums: false,
+                open_enums: false,
+                single_value_structs: false,
+                case_insensitive_enums: false,
+                trim_enums: false,
+                pattern_types: false,
+                bounded_numbers: false,
+                length_types: false,
+                validator_attributes: false,
+                try_from_value: false,
+                embed_schema: false,
+                rename_all: false,
+                strict: false,
+                report: false,
+                preprocessors: Vec::new(),
+                naming_strategy: &DefaultNaming,
+                type_mappers: Vec::new(),
             },
         }
     }
@@ -108,15 +810,300 @@ impl<'a, 'b> GeneratorBuilder<'a, 'b> {
         self.inner.input_file = input_file.as_ref();
         self
     }
+    pub fn with_input_files<P: ?Sized + AsRef<Path> + 'b>(
+        mut self,
+        input_files: impl IntoIterator<Item = &'b P>,
+    ) -> Self {
+        self.inner
+            .input_files
+            .extend(input_files.into_iter().map(AsRef::as_ref));
+        self
+    }
+    pub fn with_input_schema(mut self, input_schema: &'b str) -> Self {
+        self.inner.input_schema = Some(input_schema);
+        self
+    }
     pub fn with_schemafy_path(mut self, schemafy_path: &'a str) -> Self {
         self.inner.schemafy_path = schemafy_path;
         self
     }
+    pub fn with_module(mut self, module: &str) -> Self {
+        self.inner.module = Some(module.to_string());
+        self
+    }
+    pub fn with_visibility(mut self, visibility: &str) -> Self {
+        self.inner.visibility = visibility.to_string();
+        self
+    }
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.inner.prefix = prefix.to_string();
+        self
+    }
+    pub fn with_suffix(mut self, suffix: &str) -> Self {
+        self.inner.suffix = suffix.to_string();
+        self
+    }
+    pub fn with_decimal_type(mut self, decimal_type: &str) -> Self {
+        self.inner.decimal_type = decimal_type.to_string();
+        self
+    }
+    pub fn with_all_numbers_decimal(mut self, all_numbers_decimal: bool) -> Self {
+        self.inner.all_numbers_decimal = all_numbers_decimal;
+        self
+    }
+    pub fn with_format_type(mut self, format: &str, rust_type: &str) -> Self {
+        self.inner
+            .formats
+            .insert(format.to_string(), rust_type.to_string());
+        self
+    }
+    pub fn with_format_module(mut self, format: &str, module: &str) -> Self {
+        self.inner
+            .format_modules
+            .insert(format.to_string(), module.to_string());
+        self
+    }
+    pub fn with_derive(mut self, derive: &str) -> Self {
+        self.inner.derives.push(derive.to_string());
+        self
+    }
+    pub fn with_type_attribute(mut self, type_name: &str, attribute: &str) -> Self {
+        self.inner
+            .type_attributes
+            .entry(type_name.to_string())
+            .or_default()
+            .push(attribute.to_string());
+        self
+    }
+    pub fn with_replacement(mut self, type_name: &str, rust_type: &str) -> Self {
+        self.inner
+            .replacements
+            .insert(type_name.to_string(), rust_type.to_string());
+        self
+    }
+    pub fn with_rename(mut self, from: &str, to: &str) -> Self {
+        self.inner.renames.insert(from.to_string(), to.to_string());
+        self
+    }
+    pub fn with_acronym(mut self, acronym: &str) -> Self {
+        self.inner.acronyms.push(acronym.to_string());
+        self
+    }
+    /// Adds the `naming::COMMON_ACRONYMS`.
+    pub fn with_common_acronyms(mut self) -> Self {
+        self.inner
+            .acronyms
+            .extend(COMMON_ACRONYMS.iter().map(|acronym| acronym.to_string()));
+        self
+    }
+    pub fn with_decode_json_strings(mut self, decode_json_strings: bool) -> Self {
+        self.inner.decode_json_strings = decode_json_strings;
+        self
+    }
+    pub fn with_set_type(mut self, set_type: &str) -> Self {
+        self.inner.set_type = Some(set_type.to_string());
+        self
+    }
+    pub fn with_map_type(mut self, map_type: &str) -> Self {
+        self.inner.map_type = map_type.to_string();
+        self
+    }
+    pub fn with_empty_structs(mut self, empty_structs: bool) -> Self {
+        self.inner.empty_structs = empty_structs;
+        self
+    }
+    pub fn with_deny_unknown_fields(mut self, deny_unknown_fields: bool) -> Self {
+        self.inner.deny_unknown_fields = deny_unknown_fields;
+        self
+    }
+    pub fn with_non_exhaustive(mut self, non_exhaustive: bool) -> Self {
+        self.inner.non_exhaustive = non_exhaustive;
+        self
+    }
+    pub fn with_read_write_only(mut self, read_write_only: bool) -> Self {
+        self.inner.read_write_only = read_write_only;
+        self
+    }
+    pub fn with_borrowed_strings(mut self, borrowed_strings: bool) -> Self {
+        self.inner.borrowed_strings = borrowed_strings;
+        self
+    }
+    pub fn with_derive_ord(mut self, derive_ord: bool) -> Self {
+        self.inner.derive_ord = derive_ord;
+        self
+    }
+    pub fn with_builders(mut self, builders: bool) -> Self {
+        self.inner.builders = builders;
+        self
+    }
+    pub fn with_constructors(mut self, constructors: bool) -> Self {
+        self.inner.constructors = constructors;
+        self
+    }
+    pub fn with_no_std(mut self, no_std: bool) -> Self {
+        self.inner.no_std = no_std;
+        self
+    }
+    pub fn with_json_schema(mut self, json_schema: bool) -> Self {
+        self.inner.json_schema = json_schema;
+        self
+    }
+    pub fn with_arbitrary(mut self, arbitrary: bool) -> Self {
+        self.inner.arbitrary = arbitrary;
+        self
+    }
+    pub fn with_raw_value(mut self, raw_value: bool) -> Self {
+        self.inner.raw_value = raw_value;
+        self
+    }
+    pub fn with_nullable_fields(mut self, nullable_fields: bool) -> Self {
+        self.inner.nullable_fields = nullable_fields;
+        self
+    }
+    pub fn with_ordered_float(mut self, ordered_float: bool) -> Self {
+        self.inner.ordered_float = ordered_float;
+        self
+    }
+    pub fn with_read_write_variants(mut self, read_write_variants: bool) -> Self {
+        self.inner.read_write_variants = read_write_variants;
+        self
+    }
+    pub fn with_one_or_many_arrays(mut self, one_or_many_arrays: bool) -> Self {
+        self.inner.one_or_many_arrays = one_or_many_arrays;
+        self
+    }
+    pub fn with_one_or_many_type(mut self, one_or_many_type: bool) -> Self {
+        self.inner.one_or_many_type = one_or_many_type;
+        self
+    }
+    pub fn with_base64_bytes(mut self, base64_bytes: bool) -> Self {
+        self.inner.base64_bytes = base64_bytes;
+        self
+    }
+    pub fn with_union_types(mut self, union_types: bool) -> Self {
+        self.inner.union_types = union_types;
+        self
+    }
+    pub fn with_dedup_types(mut self, dedup_types: bool) -> Self {
+        self.inner.dedup_types = dedup_types;
+        self
+    }
+    pub fn with_title_names(mut self, title_names: bool) -> Self {
+        self.inner.title_names = title_names;
+        self
+    }
+    pub fn with_pointer_names(mut self, pointer_names: bool) -> Self {
+        self.inner.pointer_names = pointer_names;
+        self
+    }
+    pub fn with_raw_identifiers(mut self, raw_identifiers: bool) -> Self {
+        self.inner.raw_identifiers = raw_identifiers;
+        self
+    }
+    pub fn with_copy_enums(mut self, copy_enums: bool) -> Self {
+        self.inner.copy_enums = copy_enums;
+        self
+    }
+    pub fn with_validate(mut self, validate: bool) -> Self {
+        self.inner.validate = validate;
+        self
+    }
+    pub fn with_int_enums(mut self, int_enums: bool) -> Self {
+        self.inner.int_enums = int_enums;
+        self
+    }
+    pub fn with_open_enums(mut self, open_enums: bool) -> Self {
+        self.inner.open_enums = open_enums;
+        self
+    }
+    pub fn with_single_value_structs(mut self, single_value_structs: bool) -> Self {
+        self.inner.single_value_structs = single_value_structs;
+        self
+    }
+    pub fn with_case_insensitive_enums(mut self, case_insensitive_enums: bool) -> Self {
+        self.inner.case_insensitive_enums = case_insensitive_enums;
+        self
+    }
+    pub fn with_trim_enums(mut self, trim_enums: bool) -> Self {
+        self.inner.trim_enums = trim_enums;
+        self
+    }
+    pub fn with_pattern_types(mut self, pattern_types: bool) -> Self {
+        self.inner.pattern_types = pattern_types;
+        self
+    }
+    pub fn with_bounded_numbers(mut self, bounded_numbers: bool) -> Self {
+        self.inner.bounded_numbers = bounded_numbers;
+        self
+    }
+    pub fn with_length_types(mut self, length_types: bool) -> Self {
+        self.inner.length_types = length_types;
+        self
+    }
+    pub fn with_validator_attributes(mut self, validator_attributes: bool) -> Self {
+        self.inner.validator_attributes = validator_attributes;
+        self
+    }
+    pub fn with_try_from_value(mut self, try_from_value: bool) -> Self {
+        self.inner.try_from_value = try_from_value;
+        self
+    }
+    pub fn with_embed_schema(mut self, embed_schema: bool) -> Self {
+        self.inner.embed_schema = embed_schema;
+        self
+    }
+    pub fn with_rename_all(mut self, rename_all: bool) -> Self {
+        self.inner.rename_all = rename_all;
+        self
+    }
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.inner.strict = strict;
+        self
+    }
+    pub fn with_report(mut self, report: bool) -> Self {
+        self.inner.report = report;
+        self
+    }
+    /// Adds a function rewriting the schema before types are generated
+    /// from it, such as to remove definitions or to fix a schema which can
+    /// not be changed on disk.
+    pub fn with_preprocessor<F: Fn(&mut Schema) + 'static>(mut self, preprocessor: F) -> Self {
+        self.inner
+            .preprocessors
+            .push(Preprocessor::new(preprocessor));
+        self
+    }
+    pub fn with_naming_strategy(mut self, naming_strategy: &'a dyn NamingStrategy) -> Self {
+        self.inner.naming_strategy = naming_strategy;
+        self
+    }
+    pub fn with_type_mapper(mut self, type_mapper: &'a dyn TypeMapper) -> Self {
+        self.inner.type_mappers.push(type_mapper);
+        self
+    }
     pub fn build(self) -> Generator<'a, 'b> {
         self.inner
     }
 }
 
+/// Reads the schema file `input_file`, relative to the crate root, returning
+/// it along with a description of where it came from.
+fn read_schema(input_file: &Path) -> Result<(String, String), Error> {
+    let input_file = if input_file.is_relative() {
+        let crate_root = get_crate_root().unwrap();
+        crate_root.join(input_file)
+    } else {
+        PathBuf::from(input_file)
+    };
+    match std::fs::read_to_string(&input_file) {
+        Ok(json) => {
+            let source = format!("`{}`", input_file.to_string_lossy());
+            Ok((json, source))
+        }
+        Err(err) => Err(Error::Read(input_file, err)),
+    }
+}
+
 fn get_crate_root() -> std::io::Result<PathBuf> {
     if let Ok(path) = std::env::var("CARGO_MANIFEST_DIR") {
         return Ok(PathBuf::from(path));
@@ -126,7 +1113,6 @@ fn get_crate_root() -> std::io::Result<PathBuf> {
 
     for p in current_dir.ancestors() {
         if std::fs::read_dir(p)?
-            .into_iter()
             .filter_map(Result::ok)
             .any(|p| p.file_name().eq("Cargo.toml"))
         {
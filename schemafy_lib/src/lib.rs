@@ -6,8 +6,9 @@
 //! This is a Rust crate which can take a [json schema (draft
 //! 4)](http://json-schema.org/) and generate Rust types which are
 //! serializable with [serde](https://serde.rs/). No checking such as
-//! `min_value` are done but instead only the structure of the schema
-//! is followed as closely as possible.
+//! `minimum` is done during deserialization but instead only the
+//! structure of the schema is followed as closely as possible (the
+//! `validate` option generates code to check such constraints).
 //!
 //! As a schema could be arbitrarily complex this crate makes no
 //! guarantee that it can generate good types or even any types at all
@@ -22,7 +23,16 @@
 //!
 //! ## Usage
 //!
-//! Rust code is generated by providing a [`Schema`](./struct.Schema.html) struct (which can be deserialized from JSON).
+//! The simplest way to generate the source of the types from a build
+//! script or another tool is the [`generate`](fn.generate.html) function:
+//!
+//! ```rust
+//! let json = r#"{ "definitions": { "point": { "type": "object" } } }"#;
+//! let source = schemafy_lib::generate(json).unwrap();
+//! assert!(source.contains("pub type Point"));
+//! ```
+//!
+//! For more control, Rust code is generated by providing a [`Schema`](./struct.Schema.html) struct (which can be deserialized from JSON).
 //!
 //! A proc macro is available in [`schemafy`](https://docs.rs/schemafy) crate
 //!
@@ -53,37 +63,119 @@ extern crate serde_derive;
 extern crate quote;
 
 pub mod generator;
+pub mod mapping;
+pub mod naming;
+pub mod openapi;
 
 /// Types from the JSON Schema meta-schema (draft 4).
 ///
 /// This module is itself generated from a JSON schema.
 mod schema;
 
-use std::{borrow::Cow, convert::TryFrom};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+    convert::TryFrom,
+};
 
 use inflector::Inflector;
 
 use serde_json::Value;
 
+use syn::ext::IdentExt;
+
 use uriparse::{Fragment, URI};
 
-pub use schema::{Schema, SimpleTypes};
+pub use schema::{Schema, SchemaDiscriminator, SimpleTypes};
+
+pub use generator::{Error, Generator, GeneratorBuilder, Preprocessor};
+pub use mapping::TypeMapper;
+pub use naming::NamingStrategy;
+
+/// Generates the source of a Rust file with the types of the JSON schema
+/// `schema`, using the default options. Use [`Generator::builder`] to
+/// change them.
+pub fn generate(schema: &str) -> Result<String, Error> {
+    Generator::builder()
+        .with_input_schema(schema)
+        .build()
+        .try_generate_source()
+}
+
+use proc_macro2::{Delimiter, Group, Ident, Span, TokenStream, TokenTree};
 
-pub use generator::{Generator, GeneratorBuilder};
+/// The ASCII spelling of the Latin letters with diacritics, so that
+/// `größe` is named `groesse`.
+fn transliterate_char(c: char) -> Option<&'static str> {
+    Some(match c {
+        'à' | 'á' | 'â' | 'ã' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'ä' | 'æ' => "ae",
+        'ç' | 'ć' | 'č' => "c",
+        'ď' | 'đ' | 'ð' => "d",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ė' | 'ę' | 'ě' => "e",
+        'ğ' => "g",
+        'ì' | 'í' | 'î' | 'ï' | 'ī' | 'į' | 'ı' => "i",
+        'ł' | 'ľ' | 'ĺ' => "l",
+        'ñ' | 'ń' | 'ň' => "n",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ø' | 'ō' | 'ő' => "o",
+        'ö' | 'œ' => "oe",
+        'ř' | 'ŕ' => "r",
+        'ś' | 'š' | 'ş' | 'ș' => "s",
+        'ß' => "ss",
+        'ť' | 'ţ' | 'ț' => "t",
+        'þ' => "th",
+        'ù' | 'ú' | 'û' | 'ů' | 'ū' | 'ű' | 'ų' => "u",
+        'ü' => "ue",
+        'ý' | 'ÿ' => "y",
+        'ź' | 'ż' | 'ž' => "z",
+        _ => return None,
+    })
+}
 
-use proc_macro2::{Span, TokenStream};
+/// `s` with the Latin letters with diacritics spelled in ASCII, and the
+/// other non-ASCII characters which can not be part of an identifier
+/// escaped as their code point (`€` becomes `_u20ac`). Letters of other
+/// scripts are kept, as Rust identifiers may contain them.
+fn transliterate(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        let lower = c.to_lowercase().next().unwrap_or(c);
+        if let Some(ascii) = transliterate_char(lower) {
+            if c == lower {
+                result.push_str(ascii);
+            } else {
+                let mut ascii = ascii.chars();
+                result.extend(ascii.next().map(|c| c.to_ascii_uppercase()));
+                result.extend(ascii);
+            }
+        } else if c.is_ascii() || unicode_ident::is_xid_continue(c) {
+            result.push(c);
+        } else {
+            result.push_str(&format!("_u{:04x}", c as u32));
+            if matches!(chars.peek(), Some(c) if c.is_alphanumeric()) {
+                result.push('_');
+            }
+        }
+    }
+    result
+}
 
 fn replace_invalid_identifier_chars(s: &str) -> String {
-    
-    let replaced = s.strip_prefix('$')
-        .unwrap_or(s)
-        .replace(|c: char| !c.is_alphanumeric() && c != '_', "_");
-    
+    let replaced = transliterate(s.strip_prefix('$').unwrap_or(s))
+        .replace(|c: char| !unicode_ident::is_xid_continue(c), "_");
+
     replaced
 }
 
+/// `s` prefixed with `_` if it starts with a character which can not start
+/// an identifier, such as a digit.
 fn replace_numeric_start(s: &str) -> String {
-    if s.chars().next().map(|c| c.is_numeric()).unwrap_or(false) {
+    if s.chars()
+        .next()
+        .map(|c| c != '_' && !unicode_ident::is_xid_start(c))
+        .unwrap_or(false)
+    {
         format!("_{}", s)
     } else {
         s.to_string()
@@ -103,6 +195,14 @@ fn remove_excess_underscores(s: &str) -> String {
     result
 }
 
+const KEYWORDS: &[&str] = &[
+    "as", "break", "const", "continue", "crate", "else", "enum", "extern", "false", "fn", "for",
+    "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return",
+    "self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where",
+    "while", "abstract", "become", "box", "do", "final", "macro", "override", "priv", "typeof",
+    "unsized", "virtual", "yield", "async", "await", "try",
+];
+
 pub fn str_to_ident(s: &str) -> syn::Ident {
     if s.is_empty() {
         return syn::Ident::new("empty_", Span::call_site());
@@ -120,62 +220,186 @@ pub fn str_to_ident(s: &str) -> syn::Ident {
         return syn::Ident::new("invalid_", Span::call_site());
     }
 
-    let keywords = [
-        "as", "break", "const", "continue", "crate", "else", "enum", "extern", "false", "fn",
-        "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
-        "return", "self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use",
-        "where", "while", "abstract", "become", "box", "do", "final", "macro", "override", "priv",
-        "typeof", "unsized", "virtual", "yield", "async", "await", "try",
-    ];
-    if keywords.iter().any(|&keyword| keyword == s) {
+    if KEYWORDS.contains(&&s[..]) {
         return syn::Ident::new(&format!("{}_", s), Span::call_site());
     }
 
     syn::Ident::new(&s, Span::call_site())
 }
 
-fn rename_keyword(prefix: &str, s: &str) -> Option<TokenStream> {
-    let n = str_to_ident(s);
-
-    if n == s {
+/// The raw identifier (`r#type`) for `ident` if it is a keyword which was
+/// given a trailing underscore (`type_`) by `str_to_ident`.
+fn raw_ident(ident: &syn::Ident) -> Option<syn::Ident> {
+    let name = ident.to_string();
+    let keyword = name.strip_suffix('_')?;
+    // These keywords can not be raw identifiers
+    if !KEYWORDS.contains(&keyword) || ["crate", "self", "super"].contains(&keyword) {
         return None;
     }
+    Some(syn::Ident::new_raw(keyword, ident.span()))
+}
 
-    if prefix.is_empty() {
-        Some(quote! {
-            #[serde(rename = #s)]
-            #n
-        })
-    } else {
-        let prefix = syn::Ident::new(prefix, Span::call_site());
-        Some(quote! {
+/// The declaration of the field `s`, named `name`, with the visibility
+/// `vis`.
+fn field(s: &str, name: &str, vis: &TokenStream) -> TokenStream {
+    if name != s {
+        let field = str_to_ident(name);
+        return quote! {
             #[serde(rename = #s)]
-            #prefix #n
-        })
+            #vis #field
+        };
     }
-}
 
-fn field(s: &str) -> TokenStream {
-    if let Some(t) = rename_keyword("pub", s) {
-        return t;
-    }
-    
     let snake = s.to_snake_case();
-    if snake == s && !snake.contains(|c: char| c == '$' || c == '#') {
+    if snake == s && !snake.contains(['$', '#']) {
         let field = syn::Ident::new(s, Span::call_site());
-        return quote!( pub #field );
+        return quote!( #vis #field );
     }
 
     let field = if snake.is_empty() {
         syn::Ident::new("underscore", Span::call_site())
     } else {
         //str_to_ident(&snake)
-        str_to_ident(&s)
+        str_to_ident(s)
     };
 
     quote! {
         #[serde(rename = #s)]
-        pub #field
+        #vis #field
+    }
+}
+
+/// The serde `rename_all` rules which `rename_rule` may choose.
+const RENAME_RULES: &[&str] = &[
+    "camelCase",
+    "PascalCase",
+    "kebab-case",
+    "SCREAMING_SNAKE_CASE",
+    "SCREAMING-KEBAB-CASE",
+];
+
+/// The name serde gives the snake case field `s` under `rule`.
+fn rename(rule: &str, s: &str) -> String {
+    match rule {
+        "camelCase" => {
+            let pascal = rename_pascal_case(s);
+            let mut chars = pascal.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first.to_ascii_lowercase().to_string() + chars.as_str()
+            })
+        }
+        "PascalCase" => rename_pascal_case(s),
+        "kebab-case" => s.replace('_', "-"),
+        "SCREAMING_SNAKE_CASE" => s.to_ascii_uppercase(),
+        "SCREAMING-KEBAB-CASE" => s.to_ascii_uppercase().replace('_', "-"),
+        _ => s.to_owned(),
+    }
+}
+
+fn rename_pascal_case(s: &str) -> String {
+    let mut pascal = String::new();
+    let mut capitalize = true;
+    for c in s.chars() {
+        if c == '_' {
+            capitalize = true;
+        } else if capitalize {
+            pascal.push(c.to_ascii_uppercase());
+            capitalize = false;
+        } else {
+            pascal.push(c);
+        }
+    }
+    pascal
+}
+
+/// The `rename_all` rule which turns the snake case identifiers of the
+/// fields `names` back into the names themselves, if there is one and
+/// any of the names is not already snake case.
+fn rename_rule<'s>(names: impl Iterator<Item = &'s String>) -> Option<&'static str> {
+    let fields = names
+        .map(|name| (name, str_to_ident(&name.to_snake_case()).to_string()))
+        .collect::<Vec<_>>();
+    if fields.iter().all(|(name, ident)| *name == ident) {
+        return None;
+    }
+    RENAME_RULES
+        .iter()
+        .find(|rule| {
+            fields
+                .iter()
+                .all(|(name, ident)| rename(rule, ident) == **name)
+        })
+        .copied()
+}
+
+/// The JSON pointer to a subschema, given by the path `segments` from the
+/// root.
+fn json_pointer(segments: &[String]) -> String {
+    let mut pointer = String::from("#");
+    for segment in segments {
+        pointer.push('/');
+        pointer.push_str(&segment.replace('~', "~0").replace('/', "~1"));
+    }
+    pointer
+}
+
+/// Keywords which are ignored when generating types.
+const IGNORED_KEYWORDS: &[&str] = &[
+    "if",
+    "then",
+    "else",
+    "not",
+    "dependencies",
+    "dependentRequired",
+    "dependentSchemas",
+    "unevaluatedItems",
+    "unevaluatedProperties",
+    "$dynamicRef",
+    "$recursiveRef",
+];
+
+/// Lists every use of a keyword in the JSON schema `schema` which is
+/// ignored when generating types, along with its JSON pointer.
+pub fn ignored_keywords(schema: &Value) -> Vec<String> {
+    let mut found = Vec::new();
+    find_ignored_keywords(schema, &mut Vec::new(), &mut found);
+    found
+}
+
+fn find_ignored_keywords(schema: &Value, pointer: &mut Vec<String>, found: &mut Vec<String>) {
+    let object = match schema {
+        Value::Object(object) => object,
+        _ => return,
+    };
+    for (key, value) in object {
+        pointer.push(key.clone());
+        if IGNORED_KEYWORDS.contains(&&key[..]) {
+            found.push(format!("{}: `{}` is ignored", json_pointer(pointer), key));
+        }
+        match (&key[..], value) {
+            // Values rather than schemas
+            ("enum", _) | ("const", _) | ("default", _) | ("examples", _) => (),
+            // Maps of schemas, whose keys may be anything
+            ("properties", Value::Object(schemas))
+            | ("patternProperties", Value::Object(schemas))
+            | ("definitions", Value::Object(schemas))
+            | ("$defs", Value::Object(schemas)) => {
+                for (name, schema) in schemas {
+                    pointer.push(name.clone());
+                    find_ignored_keywords(schema, pointer, found);
+                    pointer.pop();
+                }
+            }
+            (_, Value::Array(schemas)) => {
+                for (i, schema) in schemas.iter().enumerate() {
+                    pointer.push(i.to_string());
+                    find_ignored_keywords(schema, pointer, found);
+                    pointer.pop();
+                }
+            }
+            _ => find_ignored_keywords(value, pointer, found),
+        }
+        pointer.pop();
     }
 }
 
@@ -185,8 +409,8 @@ where
     T: Clone,
 {
     *result = match (&mut result, r) {
-        (&mut &mut Some(ref mut result), &Some(ref r)) => return f(result, r),
-        (&mut &mut None, &Some(ref r)) => Some(r.clone()),
+        (&mut &mut Some(ref mut result), Some(r)) => return f(result, r),
+        (&mut &mut None, Some(r)) => Some(r.clone()),
         _ => return,
     };
 }
@@ -218,6 +442,318 @@ fn merge_all_of(result: &mut Schema, r: &Schema) {
     result.type_.retain(|e| r.type_.contains(e));
 }
 
+/// Returns the other variant of an `anyOf`/`oneOf` pair where one variant
+/// only allows `null`.
+fn non_null_variant(variants: &[Schema]) -> Option<&Schema> {
+    match variants {
+        [a, b] if b.type_ == [SimpleTypes::Null] => Some(a),
+        [a, b] if a.type_ == [SimpleTypes::Null] => Some(b),
+        _ => None,
+    }
+}
+
+/// Serializes `schema` to compact JSON, leaving out the empty keywords
+/// which the `Schema` struct always serializes.
+fn schema_json(schema: &Schema) -> String {
+    fn strip_empty(value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                map.retain(|_, v| match v {
+                    Value::Object(o) => !o.is_empty(),
+                    Value::Array(a) => !a.is_empty(),
+                    _ => true,
+                });
+                map.values_mut().for_each(strip_empty);
+            }
+            Value::Array(values) => values.iter_mut().for_each(strip_empty),
+            _ => (),
+        }
+    }
+    let mut value = serde_json::to_value(schema).unwrap();
+    strip_empty(&mut value);
+    value.to_string()
+}
+
+/// The variant names given by `enumNames` or `x-enum-varnames`.
+fn enum_names(schema: &Schema) -> Option<&[String]> {
+    schema
+        .enum_names
+        .as_ref()
+        .or(schema.x_enum_varnames.as_ref())
+        .filter(|names| !names.is_empty())
+        .map(|names| &names[..])
+}
+
+/// `#[deprecated]` for a schema with `"deprecated": true` or
+/// `x-deprecated`, which may be the note instead of the description.
+fn deprecated(schema: &Schema) -> Option<TokenStream> {
+    let note = match &schema.x_deprecated {
+        Some(Value::String(note)) => Some(note),
+        Some(Value::Bool(true)) => schema.description.as_ref(),
+        _ if schema.deprecated == Some(true) => schema.description.as_ref(),
+        _ => return None,
+    };
+    Some(match note {
+        Some(note) => quote!(#[deprecated(note = #note)]),
+        None => quote!(#[deprecated]),
+    })
+}
+
+/// Adds `#[allow(deprecated)]` to each of the `items`, which may use the
+/// deprecated types and fields.
+fn allow_deprecated(items: TokenStream) -> TokenStream {
+    let mut tokens = items.into_iter().peekable();
+    let mut result = TokenStream::new();
+    let mut item_start = true;
+    while let Some(token) = tokens.next() {
+        if item_start {
+            result.extend(quote!(#[allow(deprecated)]));
+        }
+        // Items end with `;` or their body, unless it is an expression
+        item_start = match &token {
+            TokenTree::Punct(punct) => punct.as_char() == ';',
+            TokenTree::Group(group) => {
+                group.delimiter() == Delimiter::Brace
+                    && !matches!(tokens.peek(), Some(TokenTree::Punct(p)) if p.as_char() == ';')
+            }
+            _ => false,
+        };
+        result.extend(std::iter::once(token));
+    }
+    result
+}
+
+/// The `#[schemars(with)]` attribute of a field (de)serialized in a custom
+/// way, which `schemars` would otherwise take for the type of its schema.
+fn schemars_with(field_type: &FieldType, schemafy_path: &str) -> Option<TokenStream> {
+    let json_string = format!(r#"with="{}json_string""#, schemafy_path);
+    let typ = if field_type.attributes.contains(&json_string) {
+        if field_type.typ.starts_with("Option<") {
+            "Option<String>"
+        } else {
+            "String"
+        }
+    } else if field_type
+        .attributes
+        .iter()
+        .any(|attr| attr.starts_with("with=") || attr.starts_with("deserialize_with="))
+    {
+        &field_type.typ
+    } else {
+        return None;
+    };
+    Some(quote!(#[schemars(with = #typ)]))
+}
+
+/// Replaces the `std` paths in `tokens` by the `core` or `alloc` ones, for
+/// `no_std` crates.
+fn no_std_paths(tokens: TokenStream) -> TokenStream {
+    let tokens = tokens.into_iter().collect::<Vec<_>>();
+    let is_path_sep = |i: usize| {
+        matches!(
+            (tokens.get(i), tokens.get(i + 1)),
+            (Some(TokenTree::Punct(a)), Some(TokenTree::Punct(b)))
+                if a.as_char() == ':' && b.as_char() == ':'
+        )
+    };
+    (0..tokens.len())
+        .map(|i| match &tokens[i] {
+            TokenTree::Group(group) => {
+                let mut result = Group::new(group.delimiter(), no_std_paths(group.stream()));
+                result.set_span(group.span());
+                result.into()
+            }
+            TokenTree::Ident(ident) if ident == "std" && is_path_sep(i + 1) => {
+                let module = tokens.get(i + 3).map(|t| t.to_string());
+                let krate = match module.as_deref() {
+                    Some("borrow" | "boxed" | "collections" | "string" | "vec") => "alloc",
+                    _ => "core",
+                };
+                Ident::new(krate, ident.span()).into()
+            }
+            token => token.clone(),
+        })
+        .collect()
+}
+
+/// The doc comment of the variant for `value`, from `x-enumDescriptions`
+/// (in the same order as `enum`) or `meta:enum` (keyed by the value).
+fn enum_description(schema: &Schema, value: &Value) -> Option<TokenStream> {
+    let from_list = schema
+        .x_enum_descriptions
+        .as_ref()
+        .and_then(|descriptions| {
+            let idx = schema.enum_.iter().flatten().position(|v| v == value)?;
+            descriptions.get(idx)
+        });
+    let from_map = || {
+        let key = match value {
+            Value::String(s) => s.clone(),
+            value => value.to_string(),
+        };
+        schema.meta_enum.as_ref()?.get(&key)
+    };
+    from_list
+        .or_else(from_map)
+        .map(|description| make_doc_comment(description, LINE_LENGTH - INDENT_LENGTH))
+}
+
+/// The names of the variants for each of `values`, taken from `names` if
+/// given. Values whose names collide (such as `foo-bar` and `foo_bar`, or
+/// one of the `reserved` names) are disambiguated by a numeric suffix.
+fn enum_variant_names(
+    naming: &dyn NamingStrategy,
+    values: &[Value],
+    names: Option<&[String]>,
+    reserved: &[&str],
+) -> Vec<String> {
+    let mut used = reserved
+        .iter()
+        .map(|name| name.to_string())
+        .collect::<BTreeSet<_>>();
+    values
+        .iter()
+        .enumerate()
+        .map(|(idx, value)| {
+            let name = match (names, value) {
+                (Some(names), _) => naming.variant_name(&names[idx]),
+                (None, Value::String(s)) => naming.variant_name(s),
+                (None, Value::Number(n)) => match n.as_i64() {
+                    Some(n) if n < 0 => format!("NumberMinus{}", n.unsigned_abs()),
+                    _ => format!("Number{}", n),
+                },
+                _ => return String::new(),
+            };
+            let mut unique = name.clone();
+            let mut suffix = 2;
+            while !used.insert(str_to_ident(&unique).to_string()) {
+                unique = format!("{}{}", name, suffix);
+                suffix += 1;
+            }
+            unique
+        })
+        .collect()
+}
+
+/// The value of the property of `discriminator` for the variant referring
+/// to `ref_`: its key in the `mapping`, or else the name of the definition.
+fn discriminator_value(discriminator: &SchemaDiscriminator, ref_: &str) -> String {
+    discriminator
+        .mapping
+        .iter()
+        .flatten()
+        .find(|(_, mapped)| *mapped == ref_)
+        .map(|(value, _)| value.clone())
+        .unwrap_or_else(|| ref_.rsplit('/').next().unwrap_or(ref_).to_string())
+}
+
+/// The `Deserialize` implementation of the enum `name`, which has the
+/// `variants` chosen by the `tags` value of `property`.
+fn expand_discriminator_deserialize(
+    name: &syn::Ident,
+    property: &str,
+    tags: &[&String],
+    variants: &[syn::Ident],
+) -> TokenStream {
+    quote! {
+        impl<'de> serde::Deserialize<'de> for #name {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                let value = <serde_json::Value as serde::Deserialize>::deserialize(deserializer)?;
+                let tag = match value.get(#property) {
+                    Some(serde_json::Value::String(tag)) => tag.clone(),
+                    _ => return Err(serde::de::Error::missing_field(#property)),
+                };
+                match tag.as_str() {
+                    #(
+                        #tags => serde_json::from_value(value)
+                            .map(#name::#variants)
+                            .map_err(serde::de::Error::custom),
+                    )*
+                    _ => Err(serde::de::Error::unknown_variant(&tag, &[#(#tags),*])),
+                }
+            }
+        }
+    }
+}
+
+/// The name of the field holding the members of `schema` which are not
+/// listed in its `properties`, if it has one.
+fn other_members_field(schema: &Schema) -> Option<&'static str> {
+    if !schema.pattern_properties.is_empty() {
+        Some("pattern_properties")
+    } else if matches!(
+        schema.additional_properties,
+        Some(Value::Bool(true)) | Some(Value::Object(_))
+    ) || (schema.additional_properties.is_none()
+        && schema.x_kubernetes_preserve_unknown_fields == Some(true))
+    {
+        Some("additional_properties")
+    } else {
+        None
+    }
+}
+
+/// Whether the `enum` of `schema` only lists integers (and possibly `null`).
+fn is_integer_enum(schema: &Schema) -> bool {
+    schema
+        .enum_
+        .iter()
+        .flatten()
+        .all(|v| v.is_i64() || v.is_null())
+}
+
+fn is_fixed_length_tuple(schema: &Schema) -> bool {
+    let len = schema.items.len() as i64;
+    len >= 2
+        && schema.max_items == Some(len)
+        && schema.min_items.as_ref().and_then(Value::as_i64) == Some(len)
+}
+
+/// Whether `typ` implements the traits needed to be stored in a set
+/// (`Eq`, `Ord` and `Hash`).
+fn is_set_element(typ: &str) -> bool {
+    matches!(typ, "String" | "i64" | "bool")
+}
+
+/// The types which may appear in generated types and implement `Eq`,
+/// `Hash` and `Ord`. `serde_json::Number` is left out as it may hold a float.
+const EQ_HASH_TYPES: &[&str] = &[
+    "String",
+    "bool",
+    "i64",
+    "u64",
+    "Option",
+    "Vec",
+    "Box",
+    "BTreeMap",
+    "BTreeSet",
+    "::std::collections::BTreeMap",
+    "::std::collections::BTreeSet",
+    "std::collections::BTreeMap",
+    "std::collections::BTreeSet",
+    "::std::borrow::Cow",
+    "str",
+];
+
+/// The type of numbers with `ordered_float`.
+const ORDERED_FLOAT: &str = "ordered_float::OrderedFloat<f64>";
+
+/// The type of strings with `borrowed_strings`.
+const COW_STR: &str = "::std::borrow::Cow<'a, str>";
+
+/// The paths of the types in the Rust type `typ`, such as `Vec` and
+/// `String` for `Vec<String>`.
+fn type_paths(typ: &str) -> impl Iterator<Item = &str> {
+    typ.split(|c: char| !(c.is_alphanumeric() || c == '_' || c == ':' || c == '\''))
+        .filter(|path| {
+            // Lifetimes and array lengths are not types
+            !path.is_empty() && !path.starts_with(|c: char| c.is_ascii_digit() || c == '\'')
+        })
+}
+
 const LINE_LENGTH: usize = 100;
 const INDENT_LENGTH: usize = 4;
 
@@ -260,27 +796,237 @@ fn make_doc_comment(mut comment: &str, remaining_line: usize) -> TokenStream {
 
 struct FieldExpander<'a, 'r: 'a> {
     default: bool,
+    rename_all: Option<&'static str>,
+    checks: Vec<TokenStream>,
+    conversions: Vec<TokenStream>,
+    /// The types of the fields.
+    member_types: Vec<String>,
+    struct_fields: Vec<StructField>,
     expander: &'a mut Expander<'r>,
 }
 
+/// A field of a generated struct, for the methods generated along with it.
+struct StructField {
+    name: String,
+    ident: syn::Ident,
+    typ: String,
+    /// The value of the field when it is not given, if it has one.
+    default: Option<FieldDefault>,
+    read_only: bool,
+    write_only: bool,
+}
+
+enum FieldDefault {
+    /// The `Default` of the field's type, such as `None`.
+    Type,
+    /// The expression of the `default` of the schema.
+    Schema(TokenStream),
+}
+
+impl FieldDefault {
+    fn initializer(&self) -> TokenStream {
+        match self {
+            FieldDefault::Type => quote!(Default::default()),
+            FieldDefault::Schema(value) => value.clone(),
+        }
+    }
+}
+
+/// A `new` function for the struct `name` taking the fields without a
+/// default.
+fn expand_constructor(
+    name: &syn::Ident,
+    lifetime: Option<&TokenStream>,
+    fields: &[StructField],
+) -> TokenStream {
+    let params = fields
+        .iter()
+        .filter(|field| field.default.is_none())
+        .map(|field| {
+            let ident = &field.ident;
+            let typ = field.typ.parse::<TokenStream>().unwrap();
+            quote!(#ident: impl Into<#typ>)
+        });
+    let values = fields.iter().map(|field| {
+        let ident = &field.ident;
+        match &field.default {
+            Some(default) => {
+                let initializer = default.initializer();
+                quote!(#ident: #initializer)
+            }
+            None => quote!(#ident: #ident.into()),
+        }
+    });
+    quote! {
+        impl #lifetime #name #lifetime {
+            #[allow(clippy::too_many_arguments)]
+            pub fn new(#(#params),*) -> Self {
+                #name {
+                    #(#values),*
+                }
+            }
+        }
+    }
+}
+
 impl<'a, 'r> FieldExpander<'a, 'r> {
     fn expand_fields(&mut self, type_name: &str, schema: &Schema) -> Vec<TokenStream> {
         let schema = self.expander.schema(schema);
-        schema
+        if self.expander.rename_all {
+            self.rename_all = rename_rule(schema.properties.keys());
+        }
+        // The map of the other members keeps its name
+        let mut used_fields = other_members_field(&schema)
+            .map(String::from)
+            .into_iter()
+            .collect::<BTreeSet<_>>();
+        let mut fields = schema
             .properties
             .iter()
             .map(|(field_name, value)| {
                 self.expander.current_field.clone_from(field_name);
-                let key = field(field_name);
+                let pointer_len = self.expander.pointer.len();
+                self.expander
+                    .pointer
+                    .extend(["properties".to_string(), field_name.clone()]);
+                let vis = self.expander.visibility();
+                let (ident, key) = if let Some(name) = self.expander.field_rename(type_name, field_name)
+                {
+                    let ident = self.expander.field_ident(&name);
+                    (ident.clone(), quote!(#[serde(rename = #field_name)] #vis #ident))
+                } else if self.rename_all.is_some() {
+                    let ident = self.expander.field_ident(&field_name.to_snake_case());
+                    let key = quote!(#vis #ident);
+                    (ident, key)
+                } else {
+                    let name = self.expander.naming.field_name(field_name);
+                    let ident = self.expander.field_ident(&name);
+                    // Serde names raw identifiers without their `r#`
+                    let key = if ident.to_string().starts_with("r#") {
+                        if ident.unraw() == field_name {
+                            quote!(#vis #ident)
+                        } else {
+                            quote!(#[serde(rename = #field_name)] #vis #ident)
+                        }
+                    } else {
+                        field(field_name, &name, &vis)
+                    };
+                    (ident, key)
+                };
+                // Properties such as `fooBar` and `foo_bar` can be given the
+                // same name, the later ones are numbered
+                let name = ident.unraw().to_string();
+                let (ident, key) = if used_fields.insert(name.clone()) {
+                    (ident, key)
+                } else {
+                    let mut suffix = 2;
+                    let mut unique = format!("{}_{}", name, suffix);
+                    while !used_fields.insert(unique.clone()) {
+                        suffix += 1;
+                        unique = format!("{}_{}", name, suffix);
+                    }
+                    self.expander.warn(&format!(
+                        "`{}` is generated as `{}` as `{}` is already taken",
+                        field_name, unique, name
+                    ));
+                    let ident = str_to_ident(&unique);
+                    let key = quote!(#[serde(rename = #field_name)] #vis #ident);
+                    (ident, key)
+                };
                 let required = schema
                     .required
                     .iter()
                     .flat_map(|a| a.iter())
                     .any(|req| req == field_name);
-                let field_type = self.expander.expand_type(type_name, required, value);
-                if !field_type.typ.starts_with("Option<") {
+                let mut field_type = self.expander.expand_type(type_name, required, value);
+                field_type.typ = self.expander.with_lifetimes(&field_type.typ);
+                if field_type.typ.contains("'a") {
+                    field_type.attributes.push("borrow".into());
+                }
+                if field_type.typ == format!("Option<{}>", COW_STR) {
+                    field_type.attributes.push(format!(
+                        r#"deserialize_with="{}borrow::deserialize_option""#,
+                        self.expander.schemafy_path
+                    ));
+                    field_type.attributes.push("default".into());
+                }
+                if self.expander.read_write_only {
+                    if value.read_only == Some(true) {
+                        field_type
+                            .attributes
+                            .retain(|attr| !attr.starts_with("skip_serializing_if"));
+                        field_type.attributes.push("skip_serializing".into());
+                    }
+                    if value.write_only == Some(true) {
+                        field_type.attributes.push("skip_deserializing".into());
+                    }
+                }
+                // The members of the referenced object are inlined in the JSON
+                if value.x_flatten == Some(true) {
+                    field_type.attributes.push("flatten".into());
+                }
+                // `""` is sent in place of an absent value
+                if value.x_empty_string_as_none == Some(true) {
+                    let custom = field_type.attributes.iter().any(|attr| attr.contains("with="));
+                    if custom || !field_type.typ.starts_with("Option<") && field_type.default {
+                        self.expander.warn(
+                            "`x-empty-string-as-none` is ignored on a field which is not an `Option`",
+                        );
+                    } else {
+                        if !field_type.typ.starts_with("Option<") {
+                            field_type.typ = format!("Option<{}>", field_type.typ);
+                        }
+                        field_type.attributes.push(format!(
+                            r#"with="{}empty_string_as_none""#,
+                            self.expander.schemafy_path
+                        ));
+                        field_type.attributes.push("default".into());
+                    }
+                }
+                // A list of the values is accepted in place of the map
+                if value.x_map_or_list == Some(true) {
+                    let optional = field_type.typ.starts_with("Option<");
+                    let map = field_type
+                        .typ
+                        .strip_prefix("Option<")
+                        .unwrap_or(&field_type.typ)
+                        .starts_with(&format!("{}<", self.expander.map_type));
+                    if map && !field_type.attributes.iter().any(|attr| attr.contains("with=")) {
+                        field_type.attributes.push(format!(
+                            r#"deserialize_with="{}map_or_list::deserialize""#,
+                            self.expander.schemafy_path
+                        ));
+                        if optional || field_type.default {
+                            field_type.attributes.push("default".into());
+                        }
+                    } else {
+                        self.expander
+                            .warn("`x-map-or-list` is ignored on a field which is not a map");
+                    }
+                }
+                self.member_types.push(field_type.typ.clone());
+                if self.expander.optional_inner(&field_type.typ).is_none() {
                     self.default = false;
                 }
+                let default_value = value
+                    .default
+                    .as_ref()
+                    .and_then(|default| self.expander.default_value(&field_type.typ, default));
+                let default = match default_value {
+                    Some(default_value) => Some(FieldDefault::Schema(default_value)),
+                    None if field_type.typ.starts_with("Option<") || field_type.default => {
+                        Some(FieldDefault::Type)
+                    }
+                    None => None,
+                };
+                self.struct_fields.push(StructField {
+                    name: field_name.clone(),
+                    ident: ident.clone(),
+                    typ: field_type.typ.clone(),
+                    default,
+                    read_only: value.read_only == Some(true),
+                    write_only: value.write_only == Some(true),
+                });
                 let typ = field_type.typ.parse::<TokenStream>().unwrap();
 
                 let default = if field_type.default {
@@ -299,28 +1045,272 @@ impl<'a, 'r> FieldExpander<'a, 'r> {
                         #[serde( #(#attributes),* )]
                     })
                 };
-                let comment = value
-                    .description
+                let constraints = self.expander.constraints(value, &field_type.typ);
+                if !self.expander.validate && !self.expander.validator_attributes {
+                    for constraint in &constraints {
+                        let constraint = constraint.trim_start_matches("- ");
+                        self.expander
+                            .note(&format!("{} is not checked", constraint));
+                    }
+                }
+                let description = if constraints.is_empty() {
+                    value.description.clone()
+                } else {
+                    let constraints = format!("Constraints:\n{}", constraints.join("\n"));
+                    Some(match &value.description {
+                        Some(description) => format!("{}\n\n{}", description, constraints),
+                        None => constraints,
+                    })
+                };
+                if self.expander.validate {
+                    let validation = self.expander.validation_path();
+                    let checks = self.expander.validation_checks(
+                        value,
+                        &field_type.typ,
+                        &quote!(&#validation::join(path, #field_name)),
+                        0,
+                    );
+                    if !checks.is_empty() {
+                        self.checks.push(quote! {
+                            let value = &self.#ident;
+                            #checks
+                        });
+                    }
+                }
+                if self.expander.try_from_value {
+                    let validation = self.expander.validation_path();
+                    let path = quote!(#validation::join(path, #field_name));
+                    // Fields (de)serialized in a custom way are left to serde
+                    // and flattened ones are not a member of the object
+                    let flatten = field_type.attributes.iter().any(|attr| attr == "flatten");
+                    let custom = flatten
+                        || field_type.attributes.iter().any(|attr| {
+                            attr.starts_with("with=") || attr.starts_with("deserialize_with=")
+                        });
+                    let check = if !custom {
+                        let check = self.expander.json_check(value, &field_type.typ);
+                        Some(quote! {
+                            let path = &#path;
+                            #check
+                        })
+                    } else {
+                        None
+                    };
+                    let missing =
+                        if field_type.typ.starts_with("Option<") || field_type.default || flatten {
+                            None
+                        } else {
+                            Some(quote! { errors.push(#path, "missing field") })
+                        };
+                    self.conversions.extend(match (check, missing) {
+                        (Some(check), Some(missing)) => Some(quote! {
+                            match object.get(#field_name) {
+                                Some(value) => { #check }
+                                None => #missing,
+                            }
+                        }),
+                        (Some(check), None) => Some(quote! {
+                            if let Some(value) = object.get(#field_name) {
+                                #check
+                            }
+                        }),
+                        (None, Some(missing)) => Some(quote! {
+                            if !object.contains_key(#field_name) {
+                                #missing;
+                            }
+                        }),
+                        (None, None) => None,
+                    });
+                }
+                let validator_attributes = if self.expander.validator_attributes {
+                    self.expander.validator_attributes(value, &field_type.typ)
+                } else {
+                    None
+                };
+                let schemars_attribute = if self.expander.json_schema {
+                    schemars_with(&field_type, self.expander.schemafy_path)
+                } else {
+                    None
+                };
+                let comment = description
                     .as_ref()
                     .map(|comment| make_doc_comment(comment, LINE_LENGTH - INDENT_LENGTH));
+                let deprecated = deprecated(value);
+                self.expander.uses_deprecated |= deprecated.is_some();
+                self.expander.pointer.truncate(pointer_len);
                 quote! {
                     #comment
+                    #deprecated
                     #default
                     #attributes
+                    #validator_attributes
+                    #schemars_attribute
                     #key : #typ
                 }
             })
-            .collect()
+            .collect::<Vec<_>>();
+        // Members which are not listed in `properties` are kept in a map
+        if let Some(field_name) = other_members_field(&schema).filter(|_| !fields.is_empty()) {
+            self.expander.current_field = field_name.into();
+            let value_type = if schema.pattern_properties.is_empty() {
+                self.expander.expand_additional_properties(&schema)
+            } else {
+                self.expander.expand_pattern_properties(&schema)
+            };
+            if let Some(value_type) = value_type {
+                let field_name = syn::Ident::new(field_name, Span::call_site());
+                let vis = self.expander.visibility();
+                let typ = format!("{}<String, {}>", self.expander.map_type, value_type);
+                self.member_types.push(typ.clone());
+                let typ = self.expander.with_lifetimes(&typ);
+                let borrow = if typ.contains("'a") {
+                    Some(quote!(#[serde(borrow)]))
+                } else {
+                    None
+                };
+                let typ = typ.parse::<TokenStream>().unwrap();
+                self.struct_fields.push(StructField {
+                    name: field_name.to_string(),
+                    ident: field_name.clone(),
+                    typ: typ.to_string(),
+                    default: Some(FieldDefault::Type),
+                    read_only: false,
+                    write_only: false,
+                });
+                fields.push(quote! {
+                    #[serde(flatten)]
+                    #borrow
+                    #vis #field_name: #typ
+                });
+            }
+        }
+        fields
     }
 }
 
+/// Another schema generated along with the one being expanded, which
+/// `$ref`s may point into.
+#[derive(Clone)]
+struct ExternalSchema<'r> {
+    file_name: String,
+    id: Option<String>,
+    root: &'r Schema,
+    /// The path of the module holding its types, such as `super::events::`.
+    module: String,
+}
+
+#[derive(Clone)]
 pub struct Expander<'r> {
     root_name: Option<&'r str>,
     schemafy_path: &'r str,
     root: &'r Schema,
+    naming: &'r dyn NamingStrategy,
+    type_mappers: Vec<&'r dyn TypeMapper>,
+    /// The JSON of the schema, which `type_mappers` are given.
+    raw_root: Option<&'r Value>,
+    decimal_type: &'r str,
+    all_numbers_decimal: bool,
+    formats: BTreeMap<String, String>,
+    format_modules: BTreeMap<String, String>,
+    decode_json_strings: bool,
+    set_type: Option<&'r str>,
+    map_type: &'r str,
+    empty_structs: bool,
+    deny_unknown_fields: bool,
+    non_exhaustive: bool,
+    read_write_only: bool,
+    borrowed_strings: bool,
+    derive_ord: bool,
+    builders: bool,
+    constructors: bool,
+    no_std: bool,
+    json_schema: bool,
+    arbitrary: bool,
+    raw_value: bool,
+    nullable_fields: bool,
+    ordered_float: bool,
+    read_write_variants: bool,
+    one_or_many_arrays: bool,
+    one_or_many_type: bool,
+    base64_bytes: bool,
+    union_types: bool,
+    dedup_types: bool,
+    title_names: bool,
+    pointer_names: bool,
+    raw_identifiers: bool,
+    copy_enums: bool,
+    validate: bool,
+    int_enums: bool,
+    open_enums: bool,
+    single_value_structs: bool,
+    case_insensitive_enums: bool,
+    trim_enums: bool,
+    pattern_types: bool,
+    bounded_numbers: bool,
+    length_types: bool,
+    validator_attributes: bool,
+    try_from_value: bool,
+    rename_all: bool,
+    derives: Vec<String>,
+    type_attributes: BTreeMap<String, Vec<String>>,
+    replacements: BTreeMap<String, String>,
+    renames: BTreeMap<String, String>,
+    /// The names of the definitions whose type would be named like another
+    /// one's, such as `fooBar` and `FooBar`, and the names given instead.
+    renamed_definitions: BTreeMap<String, String>,
+    /// The structs generated for inline objects by their schema, for
+    /// `dedup_types`.
+    inline_types: BTreeMap<String, String>,
+    /// The names of the types of the definitions, and of the inline types
+    /// named after their `title`, for `title_names`.
+    titled_types: BTreeSet<String>,
+    visibility: &'r str,
+    type_prefix: &'r str,
+    type_suffix: &'r str,
+    uses_serde_repr: bool,
+    /// Whether a type or field is `#[deprecated]`.
+    uses_deprecated: bool,
+    embedded_schema: Option<&'r str>,
+    external_schemas: Vec<ExternalSchema<'r>>,
+    pointer: Vec<String>,
+    warnings: Vec<String>,
+    notes: Vec<String>,
+    schema_pointer: Option<String>,
+    definitions_pointer: Option<String>,
     current_type: String,
     current_field: String,
     types: Vec<(String, TokenStream)>,
+    /// How deep in `definitions` the expansion is.
+    definition_depth: usize,
+    /// The top-level definitions with the range of `types` generated for
+    /// each of them.
+    definition_types: Vec<(String, std::ops::Range<usize>)>,
+    /// The types of the fields or variants of each generated type (or the
+    /// aliased type of a type alias), by the name of the type.
+    member_types: BTreeMap<String, Vec<String>>,
+    /// The generated types which can derive `Eq` and `Hash`.
+    eq_types: BTreeSet<String>,
+    /// The generated types which can derive `PartialOrd`, when `derive_ord`
+    /// is set.
+    partial_ord_types: BTreeSet<String>,
+    /// The generated types which can derive `Arbitrary`, when `arbitrary`
+    /// is set.
+    arbitrary_types: BTreeSet<String>,
+    /// The generated types used as the keys of maps, which derive
+    /// `PartialOrd` and `Ord` even without `derive_ord`.
+    map_key_types: BTreeSet<String>,
+    /// The generated types which hold borrowed strings, directly or not,
+    /// and so have a lifetime.
+    borrowing_types: BTreeSet<String>,
+    /// The variant of each value of the generated enums, by the name of the
+    /// enum.
+    enum_variants: BTreeMap<String, Vec<(Value, syn::Ident)>>,
+    /// The name, identifier and type of the fields of each generated
+    /// struct, by the name of the struct.
+    struct_fields: BTreeMap<String, Vec<(String, syn::Ident, String)>>,
+    /// The structs which are the items of a list map, and so implement
+    /// `list_map::Keyed`.
+    keyed_types: BTreeSet<String>,
 }
 
 struct FieldType {
@@ -329,6 +1319,32 @@ struct FieldType {
     default: bool,
 }
 
+impl FieldType {
+    /// The `Option` of this type, for a schema which also allows `null`.
+    ///
+    /// The attributes are kept, as the modules of `schemafy_core` which they
+    /// name (de)serialize `Option`s too, `one_or_many` through its `option`
+    /// modules.
+    fn nullable(self) -> FieldType {
+        let attributes = self
+            .attributes
+            .into_iter()
+            .map(|attr| match attr.strip_suffix("one_or_many\"") {
+                Some(with) => format!("{}one_or_many::option\"", with),
+                None => match attr.strip_suffix("one_or_many::array\"") {
+                    Some(with) => format!("{}one_or_many::array::option\"", with),
+                    None => attr,
+                },
+            })
+            .collect();
+        FieldType {
+            typ: format!("Option<{}>", self.typ),
+            attributes,
+            default: true,
+        }
+    }
+}
+
 impl<S> From<S> for FieldType
 where
     S: Into<String>,
@@ -352,13 +1368,95 @@ impl<'r> Expander<'r> {
             root_name,
             root,
             schemafy_path,
+            naming: &naming::DefaultNaming,
+            type_mappers: Vec::new(),
+            raw_root: None,
+            decimal_type: "serde_json::Number",
+            all_numbers_decimal: false,
+            formats: BTreeMap::new(),
+            format_modules: BTreeMap::new(),
+            decode_json_strings: false,
+            set_type: None,
+            map_type: "::std::collections::BTreeMap",
+            empty_structs: false,
+            deny_unknown_fields: false,
+            non_exhaustive: false,
+            read_write_only: false,
+            borrowed_strings: false,
+            derive_ord: false,
+            builders: false,
+            constructors: false,
+            no_std: false,
+            json_schema: false,
+            arbitrary: false,
+            raw_value: false,
+            nullable_fields: false,
+            ordered_float: false,
+            read_write_variants: false,
+            one_or_many_arrays: false,
+            one_or_many_type: false,
+            base64_bytes: false,
+            union_types: false,
+            dedup_types: false,
+            title_names: false,
+            pointer_names: false,
+            raw_identifiers: false,
+            copy_enums: true,
+            validate: false,
+            int_enums: false,
+            open_enums: false,
+            single_value_structs: false,
+            case_insensitive_enums: false,
+            trim_enums: false,
+            pattern_types: false,
+            bounded_numbers: false,
+            length_types: false,
+            validator_attributes: false,
+            try_from_value: false,
+            rename_all: false,
+            derives: Vec::new(),
+            type_attributes: BTreeMap::new(),
+            replacements: BTreeMap::new(),
+            renames: BTreeMap::new(),
+            renamed_definitions: BTreeMap::new(),
+            inline_types: BTreeMap::new(),
+            titled_types: BTreeSet::new(),
+            visibility: "pub",
+            type_prefix: "",
+            type_suffix: "",
+            uses_serde_repr: false,
+            uses_deprecated: false,
+            embedded_schema: None,
+            external_schemas: Vec::new(),
+            pointer: Vec::new(),
+            warnings: Vec::new(),
+            notes: Vec::new(),
+            schema_pointer: None,
+            definitions_pointer: Some("#".into()),
             current_field: "".into(),
             current_type: "".into(),
             types: Vec::new(),
+            definition_depth: 0,
+            definition_types: Vec::new(),
+            member_types: BTreeMap::new(),
+            eq_types: BTreeSet::new(),
+            partial_ord_types: BTreeSet::new(),
+            arbitrary_types: BTreeSet::new(),
+            map_key_types: BTreeSet::new(),
+            enum_variants: BTreeMap::new(),
+            borrowing_types: BTreeSet::new(),
+            struct_fields: BTreeMap::new(),
+            keyed_types: BTreeSet::new(),
         }
     }
 
     fn type_ref(&self, s: &str) -> String {
+        let external = self.external_schema(s);
+        // Only the fragment identifies the type within another schema
+        let s = match external {
+            Some(_) => &s[s.find('#').unwrap_or(s.len())..],
+            None => s,
+        };
         // ref is supposed to be be a valid URI, however we should better have a fallback plan
         let fragment = URI::try_from(s)
             .map(|uri| uri.fragment().map(Fragment::to_owned))
@@ -374,164 +1472,2197 @@ impl<'r> Expander<'r> {
         let ref_ = if fragment.is_empty() {
             self.root_name.expect("No root name specified for schema")
         } else {
-            fragment.split('/').last().expect("Component")
+            fragment.split('/').next_back().expect("Component")
         };
 
-        let ref_ = ref_.to_pascal_case();
-        let ref_ = replace_invalid_identifier_chars(&ref_);
-        replace_numeric_start(&ref_)
+        if let Some(replacement) = self.replacement(ref_) {
+            return replacement.clone();
+        }
+        let module = external.map_or("", |external| &external.module[..]);
+        let name = match external {
+            Some(_) => self.naming.type_name(ref_),
+            None => self.definition_type_name(ref_),
+        };
+        format!("{}{}", module, self.type_name(&name))
     }
 
-    fn schema(&self, schema: &'r Schema) -> Cow<'r, Schema> {
-        let schema = match schema.ref_ {
-            Some(ref ref_) => self.schema_ref(ref_),
-            None => schema,
-        };
-        match schema.all_of {
-            Some(ref all_of) if !all_of.is_empty() => {
-                all_of
-                    .iter()
-                    .skip(1)
-                    .fold(self.schema(&all_of[0]).clone(), |mut result, def| {
-                        merge_all_of(result.to_mut(), &self.schema(def));
-                        result
-                    })
-            }
-            _ => Cow::Borrowed(schema),
+    /// The other schema the `$ref` `s` points into, if any.
+    fn external_schema(&self, s: &str) -> Option<&ExternalSchema<'r>> {
+        let document = s.split('#').next().unwrap_or("");
+        if document.is_empty() {
+            return None;
         }
+        self.external_schemas.iter().find(|external| {
+            external.id.as_deref().map(|id| id.trim_end_matches('#')) == Some(document)
+                || document.rsplit('/').next() == Some(&external.file_name[..])
+        })
     }
 
-    fn schema_ref(&self, s: &str) -> &'r Schema {
-        s.split('/').fold(self.root, |schema, comp| {
-            if comp.ends_with('#') {
-                self.root
-            } else if comp == "definitions" {
-                schema
-            } else {
-                schema
-                    .definitions
-                    .get(comp)
-                    .unwrap_or_else(|| panic!("Expected definition: `{}` {}", s, comp))
-            }
-        })
+    /// The name of the root type when none is given: the `title` of the
+    /// root schema, unless the schema only holds definitions or its title
+    /// is the name of one of them.
+    fn title_root_name(&self) -> Option<&'r str> {
+        let schema = self.root;
+        let title = schema.title.as_deref()?;
+        let has_type = !schema.type_.is_empty()
+            || !schema.properties.is_empty()
+            || schema.ref_.is_some()
+            || schema.enum_.is_some()
+            || !schema.items.is_empty()
+            || schema.all_of.is_some()
+            || schema.any_of.is_some()
+            || schema.one_of.is_some();
+        let type_name = self.naming.type_name(title);
+        let is_definition = schema
+            .definitions
+            .keys()
+            .any(|name| self.naming.type_name(name) == type_name);
+        if has_type && !is_definition {
+            Some(title)
+        } else {
+            None
+        }
     }
 
-    fn expand_type(&mut self, type_name: &str, required: bool, typ: &Schema) -> FieldType {
-        let saved_type = self.current_type.clone();
-        let mut result = self.expand_type_(typ);
-        self.current_type = saved_type;
-        if type_name.to_pascal_case() == result.typ.to_pascal_case() {
-            result.typ = format!("Box<{}>", result.typ)
+    /// The identifier of a field named `name`, which is raw (`r#type`)
+    /// rather than suffixed (`type_`) with `raw_identifiers`.
+    fn field_ident(&self, name: &str) -> syn::Ident {
+        let ident = str_to_ident(name);
+        match raw_ident(&ident) {
+            Some(raw) if self.raw_identifiers => raw,
+            _ => ident,
         }
-        if !required {
-            if !result.default {
+    }
+
+    /// The name of the type of the definition `name`, before the prefix and
+    /// suffix.
+    fn definition_type_name(&self, name: &str) -> String {
+        match self.renamed_definitions.get(name) {
+            Some(renamed) => renamed.clone(),
+            None => self.naming.type_name(name),
+        }
+    }
+
+    /// Finds the definitions of `schema`, including the nested ones, whose
+    /// type would be named like the root or an earlier definition, and gives
+    /// them a numbered name instead, such as `FooBar2`.
+    fn rename_colliding_definitions(&mut self, schema: &Schema) {
+        fn visit<'s>(
+            schema: &'s Schema,
+            pointer: &mut Vec<String>,
+            definitions: &mut Vec<(Vec<String>, &'s str)>,
+        ) {
+            for (name, def) in &schema.definitions {
+                pointer.extend(["definitions".to_string(), name.clone()]);
+                definitions.push((pointer.clone(), name));
+                visit(def, pointer, definitions);
+                pointer.truncate(pointer.len() - 2);
+            }
+        }
+        let mut definitions = Vec::new();
+        visit(schema, &mut Vec::new(), &mut definitions);
+
+        let mut used = BTreeSet::new();
+        if let Some(root_name) = self.root_name {
+            used.insert(self.naming.type_name(root_name));
+        }
+        // The definitions renamed explicitly keep their names
+        for (from, to) in &self.renames {
+            let definition = from.trim_start_matches("#/");
+            if let Some(name) = definition
+                .strip_prefix("definitions/")
+                .or_else(|| definition.strip_prefix("$defs/"))
+            {
+                used.insert(to.clone());
+                self.renamed_definitions
+                    .insert(name.to_string(), to.clone());
+            }
+        }
+        let names = definitions
+            .iter()
+            .map(|(_, name)| self.naming.type_name(name))
+            .collect::<BTreeSet<_>>();
+        // A definition is referred to by its name alone, wherever it is
+        let mut seen = BTreeSet::new();
+        for (pointer, name) in definitions {
+            if self.replacement(name).is_some()
+                || self.renamed_definitions.contains_key(name)
+                || !seen.insert(name)
+            {
+                continue;
+            }
+            let type_name = self.naming.type_name(name);
+            if used.insert(type_name.clone()) {
+                continue;
+            }
+            let renamed = (2..)
+                .map(|i| format!("{}{}", type_name, i))
+                .find(|renamed| !names.contains(renamed) && !used.contains(renamed))
+                .unwrap();
+            used.insert(renamed.clone());
+            let saved_pointer = std::mem::replace(&mut self.pointer, pointer);
+            self.warn(&format!(
+                "`{}` is generated as `{}` as `{}` is already taken",
+                name, renamed, type_name
+            ));
+            self.pointer = saved_pointer;
+            self.renamed_definitions.insert(name.to_string(), renamed);
+        }
+        self.titled_types = used;
+    }
+
+    /// The name given by `renames` to the field `field` of the type
+    /// `type_name`, which may also be given by its Rust name.
+    fn field_rename(&self, type_name: &str, field: &str) -> Option<String> {
+        let rust_name = self.definition_type_name(type_name);
+        [type_name, &rust_name]
+            .iter()
+            .find_map(|typ| self.renames.get(&format!("{}.{}", typ, field)))
+            .cloned()
+    }
+
+    /// The name of the anonymous type of the schema being expanded. With
+    /// `pointer_names`, this is the name of the definition (or root) holding
+    /// it followed by the path to it, such as `ConfigServersItemTls` for
+    /// `#/definitions/config/properties/servers/items/properties/tls`, and
+    /// otherwise the name of its parent type followed by its field.
+    fn anonymous_type_name(&self) -> String {
+        if !self.pointer_names {
+            return format!(
+                "{}{}",
+                self.current_type.to_pascal_case(),
+                self.current_field.to_pascal_case()
+            );
+        }
+        let mut name = self
+            .root_name
+            .map(|root| self.definition_type_name(root))
+            .unwrap_or_default();
+        let mut segments = self.pointer.iter().map(String::as_str).peekable();
+        while let Some(segment) = segments.next() {
+            match segment {
+                "definitions" | "$defs" => {
+                    if let Some(definition) = segments.next() {
+                        name = self.definition_type_name(definition);
+                    }
+                }
+                "properties" => {
+                    if let Some(property) = segments.next() {
+                        name.push_str(&property.to_pascal_case());
+                    }
+                }
+                "patternProperties" => {
+                    segments.next();
+                    name.push_str("Value");
+                }
+                "additionalProperties" => name.push_str("Value"),
+                "items" => {
+                    name.push_str("Item");
+                    if let Some(index) = segments.next_if(|s| s.parse::<usize>().is_ok()) {
+                        name.push_str(index);
+                    }
+                }
+                "anyOf" | "oneOf" => {
+                    if let Some(index) = segments.next() {
+                        name.push_str("Variant");
+                        name.push_str(index);
+                    }
+                }
+                segment => name.push_str(&segment.to_pascal_case()),
+            }
+        }
+        name
+    }
+
+    /// The name of the inline type for `schema`, which is its `title` with
+    /// `title_names` unless another type is named so, and `name` otherwise.
+    fn inline_type_name(&mut self, schema: &Schema, name: String) -> String {
+        if !self.title_names {
+            return name;
+        }
+        match schema
+            .title
+            .as_deref()
+            .map(|title| self.naming.type_name(title))
+        {
+            Some(title)
+                if syn::parse_str::<syn::Ident>(&title).is_ok()
+                    && self.titled_types.insert(title.clone()) =>
+            {
+                title
+            }
+            _ => name,
+        }
+    }
+
+    /// The existing Rust type which replaces the definition `name` (given
+    /// by its name in the schema or in Rust), if any.
+    fn replacement(&self, name: &str) -> Option<&String> {
+        self.replacements.get(name).or_else(|| {
+            let name = self.naming.type_name(name);
+            self.replacements.get(&name)
+        })
+    }
+
+    /// Runs `f` with the pointer extended by `segments`, the path from the
+    /// schema being expanded to one of its subschemas.
+    fn at<T>(&mut self, segments: &[&str], f: impl FnOnce(&mut Self) -> T) -> T {
+        let len = self.pointer.len();
+        self.pointer.extend(segments.iter().map(|s| s.to_string()));
+        let result = f(self);
+        self.pointer.truncate(len);
+        result
+    }
+
+    /// Records that the schema being expanded is not represented exactly
+    /// by the generated code.
+    fn warn(&mut self, message: &str) {
+        let warning = format!("{}: {}", json_pointer(&self.pointer), message);
+        self.warnings.push(warning);
+    }
+
+    /// The places where the generated code does not represent the schema
+    /// exactly, such as unions generated as `serde_json::Value`, each
+    /// starting with the JSON pointer of the schema.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// Records that some information of the schema being expanded, such as
+    /// a constraint, is lost in the generated code.
+    fn note(&mut self, message: &str) {
+        let note = format!("{}: {}", json_pointer(&self.pointer), message);
+        self.notes.push(note);
+    }
+
+    /// The information of the schema lost in the generated code besides
+    /// the `warnings`, such as constraints which are not checked or enums
+    /// generated as plain strings.
+    pub fn notes(&self) -> &[String] {
+        &self.notes
+    }
+
+    /// The name of the generated type `name`, with the `prefix` and
+    /// `suffix` options applied.
+    fn type_name(&self, name: &str) -> String {
+        format!("{}{}{}", self.type_prefix, name, self.type_suffix)
+    }
+
+    fn schema(&self, schema: &'r Schema) -> Cow<'r, Schema> {
+        let schema = match schema.ref_ {
+            Some(ref ref_) => self.schema_ref(ref_),
+            None => schema,
+        };
+        match schema.all_of {
+            Some(ref all_of) if !all_of.is_empty() => {
+                all_of
+                    .iter()
+                    .skip(1)
+                    .fold(self.schema(&all_of[0]).clone(), |mut result, def| {
+                        merge_all_of(result.to_mut(), &self.schema(def));
+                        result
+                    })
+            }
+            _ => Cow::Borrowed(schema),
+        }
+    }
+
+    fn schema_ref(&self, s: &str) -> &'r Schema {
+        self.lookup_ref(s)
+            .unwrap_or_else(|| panic!("Expected definition: `{}`", s))
+    }
+
+    fn lookup_ref(&self, s: &str) -> Option<&'r Schema> {
+        let (root, s) = match self.external_schema(s) {
+            Some(external) => (external.root, &s[s.find('#').unwrap_or(s.len())..]),
+            None => (self.root, s),
+        };
+        s.split('/').try_fold(root, |schema, comp| {
+            if comp.is_empty() || comp.ends_with('#') {
+                Some(root)
+            } else if comp == "definitions" {
+                Some(schema)
+            } else {
+                schema.definitions.get(comp)
+            }
+        })
+    }
+
+    /// Checks that every `$ref` of the schema points to its root or one of
+    /// its definitions, so that expanding it does not fail half way.
+    pub fn check_refs(&self) -> Result<(), String> {
+        let root = serde_json::to_value(self.root).expect("Serialize schema");
+        self.check_refs_in(&root)
+    }
+
+    fn check_refs_in(&self, schema: &Value) -> Result<(), String> {
+        match schema {
+            Value::Object(object) => {
+                for (key, value) in object {
+                    match (&key[..], value) {
+                        ("$ref", Value::String(ref_)) => {
+                            if self.lookup_ref(ref_).is_none() {
+                                return Err(format!(
+                                    "`$ref` `{}` does not point to a definition of the schema",
+                                    ref_
+                                ));
+                            }
+                            let root = ref_.ends_with('#')
+                                || (!ref_.contains('#') && self.external_schema(ref_).is_some());
+                            if root && self.root_name.is_none() {
+                                return Err(format!(
+                                    "`$ref` `{}` points to the root of the schema, which needs \
+                                     a `root` name",
+                                    ref_
+                                ));
+                            }
+                        }
+                        // Values rather than schemas
+                        ("enum", _) | ("const", _) | ("default", _) | ("examples", _) => (),
+                        // Maps of schemas, whose keys may be anything
+                        ("properties", Value::Object(schemas))
+                        | ("patternProperties", Value::Object(schemas))
+                        | ("definitions", Value::Object(schemas)) => {
+                            for schema in schemas.values() {
+                                self.check_refs_in(schema)?;
+                            }
+                        }
+                        _ => self.check_refs_in(value)?,
+                    }
+                }
+            }
+            Value::Array(schemas) => {
+                for schema in schemas {
+                    self.check_refs_in(schema)?;
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    fn expand_type(&mut self, type_name: &str, required: bool, typ: &Schema) -> FieldType {
+        let saved_type = self.current_type.clone();
+        let mut result = self.expand_type_(typ);
+        self.current_type = saved_type;
+        if self
+            .type_name(&self.definition_type_name(type_name))
+            .to_pascal_case()
+            == result.typ.to_pascal_case()
+        {
+            result.typ = format!("Box<{}>", result.typ)
+        }
+        if !required
+            && self.nullable_fields
+            && !result.attributes.iter().any(|a| a.contains("with="))
+        {
+            if let Some(inner) = result
+                .typ
+                .strip_prefix("Option<")
+                .and_then(|t| t.strip_suffix('>'))
+            {
+                let nullable = format!("{}Nullable", self.schemafy_path);
+                result.typ = format!("{}<{}>", nullable, inner);
+                result.default = true;
+                result
+                    .attributes
+                    .push(format!(r#"skip_serializing_if="{}::is_absent""#, nullable));
+                return result;
+            }
+        }
+        if !required {
+            if !result.default {
                 result.typ = format!("Option<{}>", result.typ);
+                // A field using `with` is not implicitly optional
+                if result
+                    .attributes
+                    .iter()
+                    .any(|attr| attr.starts_with("with=") || attr.starts_with("deserialize_with="))
+                {
+                    result.attributes.push("default".into());
+                }
+            }
+            if result.typ.starts_with("Option<") {
+                result
+                    .attributes
+                    .push("skip_serializing_if=\"Option::is_none\"".into());
+            }
+        }
+        result
+    }
+
+    /// The type chosen for `typ` by the first of the `type_mappers` which
+    /// maps it.
+    fn mapped_type(&self, typ: &Schema) -> Option<String> {
+        if self.type_mappers.is_empty() {
+            return None;
+        }
+        // The pointer may lead elsewhere for schemas which are not part of
+        // the document as they are, such as merged `allOf`s
+        let raw = self
+            .raw_root
+            .and_then(|root| root.pointer(&json_pointer(&self.pointer)[1..]))
+            .filter(|raw| {
+                serde_json::from_value::<Schema>((*raw).clone())
+                    .ok()
+                    .as_ref()
+                    == Some(typ)
+            });
+        self.type_mappers
+            .iter()
+            .find_map(|mapper| mapper.map_type(typ, raw))
+    }
+
+    fn is_number_type(&self, typ: &str) -> bool {
+        const NUMBERS: &[&str] = &[
+            "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize",
+            "f32", "f64",
+        ];
+        NUMBERS.contains(&typ) || typ == self.decimal_type || typ == ORDERED_FLOAT
+    }
+
+    /// A number transmitted as a JSON string.
+    fn stringified(&self, typ: String) -> FieldType {
+        FieldType {
+            typ,
+            attributes: vec![format!(r#"with="{}stringified""#, self.schemafy_path)],
+            default: false,
+        }
+    }
+
+    /// `schemafy_core::Either` or `Union3` of the types of two or three
+    /// `schemas`, found under `keyword` in the current schema if any.
+    fn union_type(&mut self, schemas: &[Schema], keyword: Option<&str>) -> String {
+        let saved_type = self.current_type.clone();
+        let variants = schemas
+            .iter()
+            .enumerate()
+            .map(|(i, schema)| {
+                self.current_type = format!("{}Variant{}", saved_type, i);
+                match keyword {
+                    Some(keyword) => {
+                        self.at(&[keyword, &i.to_string()], |this| this.expand_type_(schema))
+                    }
+                    None => self.expand_type_(schema),
+                }
+                .typ
+            })
+            .collect::<Vec<_>>();
+        self.current_type = saved_type;
+        let union = if variants.len() == 2 {
+            "Either"
+        } else {
+            "Union3"
+        };
+        format!("{}{}<{}>", self.schemafy_path, union, variants.join(", "))
+    }
+
+    fn expand_type_(&mut self, typ: &Schema) -> FieldType {
+        if let Some(typ) = self.mapped_type(typ) {
+            typ.into()
+        } else if typ.nullable == Some(true) {
+            // The `nullable` of OpenAPI, like a `type` with `null`
+            let mut non_null = typ.clone();
+            non_null.nullable = None;
+            non_null.type_.retain(|x| *x != SimpleTypes::Null);
+            self.expand_type_(&non_null).nullable()
+        } else if typ.x_kubernetes_int_or_string == Some(true) {
+            format!("{}IntOrString", self.schemafy_path).into()
+        } else if let Some(ref ref_) = typ.ref_ {
+            self.type_ref(ref_).into()
+        } else if let Some(non_null) = typ
+            .any_of
+            .as_deref()
+            .or(typ.one_of.as_deref())
+            .and_then(non_null_variant)
+        {
+            self.expand_type_(non_null).nullable()
+        } else if typ.any_of.as_ref().is_some_and(|a| a.len() >= 2) {
+            let any_of = typ.any_of.as_ref().unwrap();
+            let simple = self.schema(&any_of[0]);
+            let array = self.schema(&any_of[1]);
+            if !array.type_.is_empty() {
+                if let SimpleTypes::Array = array.type_[0] {
+                    if simple == self.schema(&array.items[0]) {
+                        let item = self.expand_type_(&any_of[0]).typ;
+                        if self.one_or_many_type {
+                            return FieldType {
+                                typ: format!("{}OneOrMany<{}>", self.schemafy_path, item),
+                                attributes: vec![],
+                                default: true,
+                            };
+                        }
+                        return FieldType {
+                            typ: format!("Vec<{}>", item),
+                            attributes: vec![format!(
+                                r#"with="{}one_or_many{}""#,
+                                self.schemafy_path,
+                                if self.one_or_many_arrays {
+                                    "::array"
+                                } else {
+                                    ""
+                                }
+                            )],
+                            default: true,
+                        };
+                    }
+                }
+            }
+            if self.union_types && any_of.len() <= 3 {
+                return self.union_type(any_of, Some("anyOf")).into();
+            }
+            self.warn("`anyOf` is generated as `serde_json::Value`");
+            self.any_type().into()
+        } else if typ.one_of.as_ref().is_some_and(|a| a.len() >= 2) {
+            let schemas = typ.one_of.as_ref().unwrap();
+            let (type_name, type_def) = self.expand_one_of(schemas, typ.discriminator.as_ref());
+            self.types.push((type_name.clone(), type_def));
+            type_name.into()
+        } else if self.union_types
+            && (2..=3).contains(
+                &typ.type_
+                    .iter()
+                    .filter(|t| **t != SimpleTypes::Null)
+                    .count(),
+            )
+        {
+            let types = typ
+                .type_
+                .iter()
+                .filter(|t| **t != SimpleTypes::Null)
+                .map(|t| Schema {
+                    type_: vec![t.clone()],
+                    ..typ.clone()
+                })
+                .collect::<Vec<_>>();
+            let union = self.union_type(&types, None);
+            if types.len() < typ.type_.len() {
+                FieldType {
+                    typ: format!("Option<{}>", union),
+                    attributes: vec![],
+                    default: true,
+                }
+            } else {
+                union.into()
+            }
+        } else if typ.type_.len() == 2 {
+            if typ.type_[0] == SimpleTypes::Null || typ.type_[1] == SimpleTypes::Null {
+                let mut ty = typ.clone();
+                ty.type_.retain(|x| *x != SimpleTypes::Null);
+                self.expand_type_(&ty).nullable()
+            } else {
+                self.warn("a `type` with several types is generated as `serde_json::Value`");
+                self.any_type().into()
+            }
+        } else if typ.type_.len() == 1 {
+            if let Some(format_type) = typ.format.as_ref().and_then(|f| self.formats.get(f)) {
+                if let Some(module) = typ.format.as_ref().and_then(|f| self.format_modules.get(f)) {
+                    return FieldType {
+                        typ: format_type.clone(),
+                        attributes: vec![format!(r#"with="{}""#, module)],
+                        default: false,
+                    };
+                }
+                if typ.type_[0] == SimpleTypes::String && self.is_number_type(format_type) {
+                    return self.stringified(format_type.clone());
+                }
+                return format_type.clone().into();
+            }
+            if typ.x_stringified == Some(true)
+                && matches!(typ.type_[0], SimpleTypes::Integer | SimpleTypes::Number)
+            {
+                let number = Schema {
+                    x_stringified: None,
+                    ..typ.clone()
+                };
+                let number = self.expand_type_(&number).typ;
+                return self.stringified(number);
+            }
+            // Arrays of scalars sent as a single string, such as `a,b`
+            if let Some(format) = typ.collection_format.as_deref() {
+                if typ.type_[0] == SimpleTypes::Array
+                    && ["csv", "ssv", "tsv", "pipes"].contains(&format)
+                {
+                    let scalar = typ.items.len() == 1
+                        && matches!(
+                            self.schema(&typ.items[0]).type_.first(),
+                            Some(
+                                SimpleTypes::String
+                                    | SimpleTypes::Integer
+                                    | SimpleTypes::Number
+                                    | SimpleTypes::Boolean
+                            )
+                        );
+                    if scalar {
+                        let array = Schema {
+                            collection_format: None,
+                            ..typ.clone()
+                        };
+                        return FieldType {
+                            typ: self.expand_type_(&array).typ,
+                            attributes: vec![format!(
+                                r#"with="{}delimited::{}""#,
+                                self.schemafy_path, format
+                            )],
+                            default: false,
+                        };
+                    }
+                    self.warn(&format!(
+                        "`collectionFormat` `{}` is ignored on an array which does not hold \
+                         strings, numbers or booleans",
+                        format
+                    ));
+                }
+            }
+            // Lists of objects told apart by some of their fields
+            if typ.type_[0] == SimpleTypes::Array
+                && typ.x_kubernetes_list_type.as_deref() == Some("map")
+            {
+                let list = Schema {
+                    x_kubernetes_list_type: None,
+                    ..typ.clone()
+                };
+                let list = self.expand_type_(&list);
+                let keys = typ
+                    .x_kubernetes_list_map_keys
+                    .as_deref()
+                    .unwrap_or_default();
+                if let Some(map) = self.expand_list_map(&list.typ, keys) {
+                    return FieldType {
+                        typ: map,
+                        attributes: vec![format!(r#"with="{}list_map""#, self.schemafy_path)],
+                        default: false,
+                    };
+                }
+                self.warn(
+                    "`\"x-kubernetes-list-type\": \"map\"` is ignored on a list which does not \
+                     hold structs with ordered fields of `x-kubernetes-list-map-keys`",
+                );
+                return list;
+            }
+            if typ.x_lenient == Some(true) {
+                let kind = match typ.type_[0] {
+                    SimpleTypes::Boolean => Some("boolean"),
+                    SimpleTypes::Integer | SimpleTypes::Number => Some("number"),
+                    _ => None,
+                };
+                if let Some(kind) = kind {
+                    let strict = Schema {
+                        x_lenient: None,
+                        ..typ.clone()
+                    };
+                    return FieldType {
+                        typ: self.expand_type_(&strict).typ,
+                        attributes: vec![format!(
+                            r#"deserialize_with="{}lenient::{}::deserialize""#,
+                            self.schemafy_path, kind
+                        )],
+                        default: false,
+                    };
+                }
+            }
+            match typ.type_[0] {
+                SimpleTypes::String
+                    if self.decode_json_strings
+                        && typ.content_media_type.as_deref() == Some("application/json") =>
+                {
+                    let content_type = match typ.content_schema {
+                        Some(ref content_schema) => {
+                            self.at(&["contentSchema"], |this| this.expand_type_(content_schema))
+                                .typ
+                        }
+                        None => self.any_type(),
+                    };
+                    FieldType {
+                        typ: content_type,
+                        attributes: vec![format!(r#"with="{}json_string""#, self.schemafy_path)],
+                        default: false,
+                    }
+                }
+                SimpleTypes::String
+                    if self.base64_bytes && typ.format.as_deref() == Some("byte") =>
+                {
+                    FieldType {
+                        typ: "Vec<u8>".into(),
+                        attributes: vec![format!(r#"with="{}base64_bytes""#, self.schemafy_path)],
+                        default: false,
+                    }
+                }
+                SimpleTypes::String if self.is_string_newtype(typ) => {
+                    let name = self.anonymous_type_name();
+                    let name = self.type_name(&name);
+                    let tokens = self.expand_string_newtype(&name, typ);
+                    self.types.push((name.clone(), tokens));
+                    name.into()
+                }
+                SimpleTypes::String => match typ.enum_ {
+                    Some(ref enum_) if enum_.is_empty() => self.any_type().into(),
+                    Some(_) => {
+                        self.note("`enum` is generated as `String`");
+                        self.string_type().into()
+                    }
+                    None => self.string_type().into(),
+                },
+                SimpleTypes::Integer
+                    if self.int_enums && typ.enum_.as_ref().is_some_and(|e| !e.is_empty()) =>
+                {
+                    let name = self.anonymous_type_name();
+                    let tokens = self.expand_schema(&name, typ);
+                    self.types.push((name.clone(), tokens));
+                    self.type_name(&name).into()
+                }
+                SimpleTypes::Integer if self.is_bounded(typ) => {
+                    let bound = |bound: Option<f64>, exclusive: Option<bool>, sign: f64| match bound
+                        .map(|b| b * sign)
+                    {
+                        Some(b) if exclusive == Some(true) => (b.floor() + 1.0) * sign,
+                        Some(b) => b.ceil() * sign,
+                        None => -sign * f64::INFINITY,
+                    };
+                    let as_arg = |bound: f64| match bound {
+                        b if b <= i64::MIN as f64 => "{ i64::MIN }".to_string(),
+                        b if b >= i64::MAX as f64 => "{ i64::MAX }".to_string(),
+                        b if b < 0.0 => format!("{{ {} }}", b as i64),
+                        b => (b as i64).to_string(),
+                    };
+                    let min = bound(typ.minimum, typ.exclusive_minimum, 1.0);
+                    let max = bound(typ.maximum, typ.exclusive_maximum, -1.0);
+                    format!(
+                        "{}bounded::Bounded<{}, {}>",
+                        self.schemafy_path,
+                        as_arg(min),
+                        as_arg(max)
+                    )
+                    .into()
+                }
+                SimpleTypes::Integer => {
+                    if typ.enum_.as_ref().is_some_and(|e| !e.is_empty()) {
+                        self.note("`enum` is generated as `i64`");
+                    }
+                    "i64".into()
+                }
+                SimpleTypes::Boolean => "bool".into(),
+                SimpleTypes::Number
+                    if self.all_numbers_decimal || typ.format.as_deref() == Some("decimal") =>
+                {
+                    self.decimal_type.into()
+                }
+                SimpleTypes::Number if self.is_bounded(typ) => {
+                    let name = self.anonymous_type_name();
+                    let name = self.type_name(&name);
+                    let tokens = self.expand_bounded_newtype(&name, typ);
+                    self.types.push((name.clone(), tokens));
+                    name.into()
+                }
+                SimpleTypes::Number if self.ordered_float => ORDERED_FLOAT.into(),
+                SimpleTypes::Number => "f64".into(),
+                // Handle objects defined inline
+                SimpleTypes::Object
+                    if !typ.properties.is_empty()
+                        || (typ.additional_properties == Some(Value::Bool(false))
+                            && typ.pattern_properties.is_empty())
+                        || self.is_empty_struct(typ) =>
+                {
+                    let name = self.anonymous_type_name();
+                    let key = if self.dedup_types {
+                        Some(serde_json::to_string(typ).unwrap())
+                    } else {
+                        None
+                    };
+                    if let Some(existing) = key.as_ref().and_then(|key| self.inline_types.get(key))
+                    {
+                        let existing = existing.clone();
+                        let alias = self.type_name(&name);
+                        if alias != existing {
+                            self.member_types
+                                .insert(alias.clone(), vec![existing.clone()]);
+                            let vis = self.visibility();
+                            let lifetime = self.lifetime(&existing);
+                            let alias = syn::Ident::new(&alias, Span::call_site());
+                            let typ = syn::Ident::new(&existing, Span::call_site());
+                            self.types
+                                .push((name, quote!(#vis type #alias #lifetime = #typ #lifetime;)));
+                        }
+                        return existing.into();
+                    }
+                    let name = self.inline_type_name(typ, name);
+                    let tokens = self.expand_schema(&name, typ);
+                    self.types.push((name.clone(), tokens));
+                    let type_name = self.type_name(&name);
+                    if let Some(key) = key {
+                        self.inline_types.insert(key, type_name.clone());
+                    }
+                    type_name.into()
+                }
+                SimpleTypes::Object => {
+                    let prop = match self.expand_pattern_properties(typ) {
+                        Some(prop) => prop,
+                        None => self
+                            .expand_additional_properties(typ)
+                            .unwrap_or_else(|| self.any_type()),
+                    };
+                    let key = self.expand_map_key(typ);
+                    let result = format!("{}<{}, {}>", self.map_type, key, prop);
+                    FieldType {
+                        typ: result,
+                        attributes: Vec::new(),
+                        default: typ.default == Some(Value::Object(Default::default())),
+                    }
+                }
+                // Positional `items` with a fixed length are expanded to a tuple
+                SimpleTypes::Array if is_fixed_length_tuple(typ) => {
+                    let saved_type = self.current_type.clone();
+                    let item_types = typ
+                        .items
+                        .iter()
+                        .enumerate()
+                        .map(|(i, item)| {
+                            self.current_type = format!("{}Item{}", saved_type, i);
+                            self.at(&["items", &i.to_string()], |this| this.expand_type_(item))
+                                .typ
+                        })
+                        .collect::<Vec<_>>();
+                    self.current_type = saved_type;
+                    format!("({})", item_types.join(", ")).into()
+                }
+                SimpleTypes::Array => {
+                    let item_type = typ.items.first().map_or(self.any_type(), |item| {
+                        self.current_type = format!("{}Item", self.current_type);
+                        self.at(&["items"], |this| this.expand_type_(item)).typ
+                    });
+                    match self.set_type {
+                        Some(set_type)
+                            if typ.unique_items == Some(true) && is_set_element(&item_type) =>
+                        {
+                            format!("{}<{}>", set_type, item_type).into()
+                        }
+                        _ => format!("Vec<{}>", item_type).into(),
+                    }
+                }
+                _ => self.any_type().into(),
+            }
+        } else {
+            if !typ.properties.is_empty() || !typ.items.is_empty() || typ.enum_.is_some() {
+                self.warn("a schema without `type` is generated as `serde_json::Value`");
+            }
+            self.any_type().into()
+        }
+    }
+
+    /// The map from the values of the `keys` fields of the items of the
+    /// list `list_type` to the items, generating the `list_map::Keyed` impl
+    /// of the items, or `None` if they are not a struct with these fields
+    /// or if the fields can not be ordered, such as numbers.
+    fn expand_list_map(&mut self, list_type: &str, keys: &[String]) -> Option<String> {
+        let item = list_type.strip_prefix("Vec<")?.strip_suffix('>')?;
+        if keys.is_empty() || self.lifetime(item).is_some() {
+            return None;
+        }
+        let fields = self.struct_fields.get(item)?;
+        let (idents, key_types): (Vec<_>, Vec<_>) = keys
+            .iter()
+            .map(|key| {
+                let (_, ident, typ) = fields.iter().find(|(name, ..)| name == key)?;
+                Some((ident.clone(), typ.clone()))
+            })
+            .collect::<Option<Vec<_>>>()?
+            .into_iter()
+            .unzip();
+        // The keys are compared, so the types generated for them derive `Ord`
+        for path in key_types.iter().flat_map(|typ| type_paths(typ)) {
+            if self.member_types.contains_key(path) {
+                self.map_key_types.insert(path.to_string());
+            }
+        }
+        let ord = |typ: &String| {
+            type_paths(typ).all(|path| EQ_HASH_TYPES.contains(&path) || self.derives_ord(path))
+        };
+        if !key_types.iter().all(ord) {
+            return None;
+        }
+        let key_type = if key_types.len() == 1 {
+            key_types[0].clone()
+        } else {
+            format!("({})", key_types.join(", "))
+        };
+        if self.keyed_types.insert(item.to_string()) {
+            let keyed = format!("{}list_map::Keyed", self.schemafy_path)
+                .parse::<TokenStream>()
+                .unwrap();
+            let name = syn::Ident::new(item, Span::call_site());
+            let key_tokens = key_type.parse::<TokenStream>().unwrap();
+            let key = if idents.len() == 1 {
+                let ident = &idents[0];
+                quote!(self.#ident.clone())
+            } else {
+                quote!((#(self.#idents.clone()),*))
+            };
+            self.types.push((
+                item.to_string(),
+                quote! {
+                    impl #keyed for #name {
+                        type Key = #key_tokens;
+                        fn key(&self) -> Self::Key {
+                            #key
+                        }
+                    }
+                },
+            ));
+        }
+        Some(format!("{}<{}, {}>", self.map_type, key_type, item))
+    }
+
+    /// The type held by the `Option` (or, with `nullable_fields`, the
+    /// `Nullable`) `typ`, if it is one.
+    fn optional_inner<'t>(&self, typ: &'t str) -> Option<&'t str> {
+        let nullable = format!("{}Nullable<", self.schemafy_path);
+        typ.strip_prefix("Option<")
+            .or_else(|| typ.strip_prefix(&nullable[..]))
+            .and_then(|t| t.strip_suffix('>'))
+    }
+
+    /// The type of free-form JSON values.
+    fn any_type(&self) -> String {
+        if self.raw_value {
+            format!("{}raw::RawJson", self.schemafy_path)
+        } else {
+            "serde_json::Value".into()
+        }
+    }
+
+    /// Whether `typ` is an object without any properties which should be
+    /// generated as an empty struct rather than a map.
+    fn is_empty_struct(&self, typ: &Schema) -> bool {
+        self.empty_structs
+            && typ.type_ == [SimpleTypes::Object]
+            && typ.properties.is_empty()
+            && typ.pattern_properties.is_empty()
+            && typ.additional_properties.is_none()
+    }
+
+    /// Expands the type of the members allowed by `additionalProperties`,
+    /// or `None` if it is absent or `false`. The members of a schema with
+    /// `"x-kubernetes-preserve-unknown-fields": true` are kept as they are.
+    fn expand_additional_properties(&mut self, typ: &Schema) -> Option<String> {
+        match typ.additional_properties {
+            None if typ.x_kubernetes_preserve_unknown_fields == Some(true) => Some(self.any_type()),
+            Some(ref props) if props.is_object() => {
+                let prop = serde_json::from_value(props.clone()).unwrap();
+                Some(
+                    self.at(&["additionalProperties"], |this| this.expand_type_(&prop))
+                        .typ,
+                )
+            }
+            Some(Value::Bool(true)) => Some(self.any_type()),
+            _ => None,
+        }
+    }
+
+    /// Expands the value type of the map holding the members matched by
+    /// `patternProperties`, or `None` if the schema has no patterns.
+    ///
+    /// Keys are not checked against the patterns, so if the patterns (and
+    /// a schema in `additionalProperties`) do not all expand to the same
+    /// type the values fall back to `serde_json::Value`.
+    fn expand_pattern_properties(&mut self, typ: &Schema) -> Option<String> {
+        if typ.pattern_properties.is_empty() {
+            return None;
+        }
+        let saved_field = self.current_field.clone();
+        let mut value_types = Vec::new();
+        for (i, (key, pattern)) in typ.pattern_properties.iter().enumerate() {
+            if typ.pattern_properties.len() > 1 {
+                self.current_field = format!("{}Pattern{}", saved_field, i);
+            }
+            let value_type = self.at(&["patternProperties", key], |this| {
+                this.expand_type_(pattern)
+            });
+            value_types.push(value_type.typ);
+        }
+        self.current_field = saved_field;
+        value_types.extend(self.expand_additional_properties(typ));
+        let mut seen = BTreeSet::new();
+        value_types.retain(|typ| seen.insert(typ.clone()));
+        Some(if value_types.len() == 1 {
+            value_types.remove(0)
+        } else {
+            self.warn(
+                "`patternProperties` of different types are generated as `serde_json::Value`",
+            );
+            self.any_type()
+        })
+    }
+
+    /// Expands the key type of a map. Keys restricted by the `enum` or
+    /// `pattern` of `propertyNames` get their own type so that invalid keys
+    /// are rejected when deserializing.
+    fn expand_map_key(&mut self, typ: &Schema) -> String {
+        let property_names = match typ.property_names {
+            Some(ref property_names) => property_names,
+            None => return "String".into(),
+        };
+        if let Some(ref ref_) = property_names.ref_ {
+            let key = self.type_ref(ref_);
+            self.map_key_types.insert(key.clone());
+            return key;
+        }
+        let saved_type = self.current_type.clone();
+        let name = format!("{}Key", self.anonymous_type_name());
+        let tokens = if property_names.enum_.as_ref().is_some_and(|e| !e.is_empty()) {
+            self.at(&["propertyNames"], |this| {
+                this.expand_schema(&name, property_names)
+            })
+        } else if property_names.pattern.is_some() {
+            self.expand_string_newtype(&self.type_name(&name), property_names)
+        } else {
+            return "String".into();
+        };
+        self.current_type = saved_type;
+        self.types.push((name.clone(), tokens));
+        let key = self.type_name(&name);
+        self.map_key_types.insert(key.clone());
+        key
+    }
+
+    /// Whether `typ` is a number with bounds which are checked when it is
+    /// deserialized.
+    fn is_bounded(&self, typ: &Schema) -> bool {
+        self.bounded_numbers && (typ.minimum.is_some() || typ.maximum.is_some())
+    }
+
+    /// A newtype around `f64` which can only hold numbers within the
+    /// `minimum` and `maximum` of `typ`.
+    fn expand_bounded_newtype(&self, name: &str, typ: &Schema) -> TokenStream {
+        let name = syn::Ident::new(name, Span::call_site());
+        let bounded = format!("{}bounded", self.schemafy_path)
+            .parse::<TokenStream>()
+            .unwrap();
+        let bound = |bound: Option<f64>, exclusive: Option<bool>| match bound {
+            Some(bound) => {
+                let bound = proc_macro2::Literal::f64_suffixed(bound);
+                let exclusive = exclusive == Some(true);
+                quote!(Some((#bound, #exclusive)))
+            }
+            None => quote!(None),
+        };
+        let minimum = bound(typ.minimum, typ.exclusive_minimum);
+        let maximum = bound(typ.maximum, typ.exclusive_maximum);
+        let vis = self.visibility();
+        let derive = self.derive(&[
+            "Clone",
+            "Copy",
+            "PartialEq",
+            "PartialOrd",
+            "Debug",
+            "Deserialize",
+            "Serialize",
+        ]);
+        quote! {
+            #derive
+            #[serde(try_from = "f64")]
+            #vis struct #name(f64);
+
+            impl ::std::convert::TryFrom<f64> for #name {
+                type Error = #bounded::OutOfBounds;
+                fn try_from(value: f64) -> Result<Self, Self::Error> {
+                    #bounded::check_f64(value, #minimum, #maximum).map(#name)
+                }
+            }
+
+            impl ::std::ops::Deref for #name {
+                type Target = f64;
+                fn deref(&self) -> &f64 {
+                    &self.0
+                }
+            }
+
+            impl From<#name> for f64 {
+                fn from(value: #name) -> f64 {
+                    value.0
+                }
+            }
+        }
+    }
+
+    /// Whether `typ` is a string with constraints which are checked when it
+    /// is deserialized.
+    fn is_string_newtype(&self, typ: &Schema) -> bool {
+        (self.pattern_types && typ.pattern.is_some())
+            || (self.length_types && (typ.min_length.is_some() || typ.max_length.is_some()))
+    }
+
+    /// A newtype around `String` which can only hold strings matching the
+    /// `pattern` of `typ` and within its `minLength` and `maxLength`.
+    fn expand_string_newtype(&self, name: &str, typ: &Schema) -> TokenStream {
+        let name = syn::Ident::new(name, Span::call_site());
+        let path = |path: &str| {
+            format!("{}{}", self.schemafy_path, path)
+                .parse::<TokenStream>()
+                .unwrap()
+        };
+        let pattern_check = typ.pattern.as_ref().map(|pattern| {
+            let check = path("pattern::check");
+            quote!(#check(#pattern, &value)?;)
+        });
+        let min_length = typ.min_length.as_ref().and_then(Value::as_u64);
+        let max_length = typ.max_length;
+        let length_check = if min_length.is_some() || max_length.is_some() {
+            let check = path("length::check");
+            let bound = |bound: Option<usize>| match bound {
+                Some(bound) => quote!(Some(#bound)),
+                None => quote!(None),
+            };
+            let min_length = bound(min_length.map(|min| min as usize));
+            let max_length = bound(max_length.map(|max| max as usize));
+            Some(quote!(#check(&value, #min_length, #max_length)?;))
+        } else {
+            None
+        };
+        let error = match (&pattern_check, &length_check) {
+            (Some(_), Some(_)) => path("length::StringError"),
+            (None, Some(_)) => path("length::LengthError"),
+            _ => path("pattern::PatternError"),
+        };
+        let vis = self.visibility();
+        let derive = self.derive(&[
+            "Clone",
+            "PartialEq",
+            "Eq",
+            "PartialOrd",
+            "Ord",
+            "Hash",
+            "Debug",
+            "Deserialize",
+            "Serialize",
+        ]);
+        quote! {
+            #derive
+            #[serde(try_from = "String")]
+            #vis struct #name(String);
+
+            impl ::std::convert::TryFrom<String> for #name {
+                type Error = #error;
+                fn try_from(value: String) -> Result<Self, Self::Error> {
+                    #pattern_check
+                    #length_check
+                    Ok(#name(value))
+                }
+            }
+
+            impl ::std::ops::Deref for #name {
+                type Target = str;
+                fn deref(&self) -> &str {
+                    &self.0
+                }
+            }
+
+            impl From<#name> for String {
+                fn from(value: #name) -> String {
+                    value.0
+                }
+            }
+        }
+    }
+
+    /// Checks that the JSON `value` at `path` converts to `typ`, descending
+    /// into arrays and generated structs to report every problem.
+    fn json_check(&self, schema: &Schema, typ: &str) -> TokenStream {
+        let convert = format!("{}convert", self.schemafy_path)
+            .parse::<TokenStream>()
+            .unwrap();
+        let schema = match schema.ref_ {
+            Some(ref ref_) => self.schema(self.schema_ref(ref_)),
+            None => Cow::Borrowed(schema),
+        };
+        if let Some(inner) = self.optional_inner(typ) {
+            let inner_schema = schema
+                .any_of
+                .as_deref()
+                .or(schema.one_of.as_deref())
+                .and_then(non_null_variant)
+                .unwrap_or(&schema);
+            let check = self.json_check(inner_schema, inner);
+            return quote! {
+                if !value.is_null() {
+                    #check
+                }
+            };
+        }
+        if let Some(inner) = typ.strip_prefix("Box<").and_then(|t| t.strip_suffix('>')) {
+            return self.json_check(&schema, inner);
+        }
+        if let (Some(inner), Some(items)) = (
+            typ.strip_prefix("Vec<").and_then(|t| t.strip_suffix('>')),
+            schema.items.first(),
+        ) {
+            let check = self.json_check(items, inner);
+            return quote! {
+                #convert::check_items(value, path, errors, |value, path, errors| {
+                    #check
+                })
+            };
+        }
+        let typ = typ.parse::<TokenStream>().unwrap();
+        if self.is_generated_struct(&schema) && syn::parse2::<syn::Ident>(typ.clone()).is_ok() {
+            quote! {
+                <#typ as #convert::CheckJson>::check_json(value, path, errors)
+            }
+        } else {
+            quote! {
+                #convert::check::<#typ>(value, path, errors)
+            }
+        }
+    }
+
+    /// `CheckJson` and `TryFrom<serde_json::Value>` for the type `name`,
+    /// where `check` checks the JSON `value` at `path`.
+    fn expand_try_from_value(&self, name: &syn::Ident, check: TokenStream) -> TokenStream {
+        let convert = format!("{}convert", self.schemafy_path)
+            .parse::<TokenStream>()
+            .unwrap();
+        let validation = self.validation_path();
+        quote! {
+            impl #convert::CheckJson for #name {
+                fn check_json(
+                    value: &serde_json::Value,
+                    path: &str,
+                    errors: &mut #validation::ValidationErrors,
+                ) {
+                    #check
+                }
+            }
+            impl std::convert::TryFrom<serde_json::Value> for #name {
+                type Error = #validation::ValidationErrors;
+                fn try_from(value: serde_json::Value) -> Result<Self, Self::Error> {
+                    #convert::try_from_value(value)
+                }
+            }
+        }
+    }
+
+    /// The `#[derive(..)]` attribute of a generated type, adding the
+    /// configured derives to `defaults`. A configured derive with the same
+    /// name as a default one (such as `serde::Deserialize`) replaces it.
+    fn derive(&self, defaults: &[&str]) -> TokenStream {
+        let mut derives = defaults.iter().map(|d| d.to_string()).collect::<Vec<_>>();
+        if self.json_schema {
+            derives.push("schemars::JsonSchema".into());
+        }
+        for derive in &self.derives {
+            let name = derive.rsplit("::").next().unwrap_or(derive).trim();
+            match derives.iter_mut().find(|d| d.as_str() == name) {
+                Some(default) => default.clone_from(derive),
+                // serde traits are implemented by hand when not derived
+                None if name == "Serialize" || name == "Deserialize" => (),
+                None => derives.push(derive.clone()),
+            }
+        }
+        let derives = derives.iter().map(|d| d.parse::<TokenStream>().unwrap());
+        quote! { #[derive(#(#derives),*)] }
+    }
+
+    /// The derives of the generated type `name`, which are `defaults` and
+    /// also `Eq` and `Hash` (and `PartialOrd` and `Ord` with `derive_ord`,
+    /// or for the keys of maps) when the types of all its members implement
+    /// them.
+    fn derive_with_members(&self, name: &str, defaults: &[&str]) -> TokenStream {
+        let eq = self.eq_types.contains(name);
+        let ord = self.derives_ord(name);
+        let partial_ord = (self.derive_ord && self.partial_ord_types.contains(name)) || ord;
+        let extra = [
+            ("Eq", eq),
+            ("PartialOrd", partial_ord),
+            ("Ord", ord),
+            ("Hash", eq),
+        ];
+        let mut defaults = defaults.to_vec();
+        let partial_eq = defaults.iter().position(|d| *d == "PartialEq");
+        let i = partial_eq.map_or(defaults.len(), |i| i + 1);
+        defaults.splice(
+            i..i,
+            extra
+                .iter()
+                .filter(|(_, derived)| *derived)
+                .map(|(derive, _)| *derive),
+        );
+        let derive = self.derive(&defaults);
+        let arbitrary = self.derive_arbitrary(name);
+        quote!(#derive #arbitrary)
+    }
+
+    /// Whether the generated type `name` derives `Ord`.
+    fn derives_ord(&self, name: &str) -> bool {
+        self.eq_types.contains(name)
+            && ((self.derive_ord && self.partial_ord_types.contains(name))
+                || self.map_key_types.contains(name))
+    }
+
+    /// The derive of `Arbitrary` for the generated type `name`, behind the
+    /// `arbitrary` feature of the crate holding the generated code.
+    fn derive_arbitrary(&self, name: &str) -> Option<TokenStream> {
+        if self.arbitrary && self.arbitrary_types.contains(name) {
+            Some(quote!(#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]))
+        } else {
+            None
+        }
+    }
+
+    /// The generated types whose members only contain types for which
+    /// `implements` holds, directly or through other generated types.
+    fn derivable_types(&self, implements: impl Fn(&str) -> bool) -> BTreeSet<String> {
+        let mut types = self.member_types.keys().cloned().collect::<BTreeSet<_>>();
+        // Types are removed until the remaining ones only refer to each
+        // other, which keeps recursive types
+        loop {
+            let removed = types
+                .iter()
+                .filter(|name| {
+                    !self.member_types[*name].iter().all(|typ| {
+                        type_paths(typ).all(|path| implements(path) || types.contains(path))
+                    })
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+            if removed.is_empty() {
+                return types;
+            }
+            for name in removed {
+                types.remove(&name);
+            }
+        }
+    }
+
+    /// Methods returning the fields of the struct `name` which are boxed to
+    /// break a cycle without their `Box`.
+    fn expand_box_accessors(
+        &self,
+        name: &syn::Ident,
+        lifetime: Option<&TokenStream>,
+        fields: &[StructField],
+    ) -> TokenStream {
+        let accessors = fields
+            .iter()
+            .filter(|field| {
+                // Named like another generated method
+                !(self.constructors && field.ident == "new"
+                    || self.builders && field.ident == "builder")
+            })
+            .filter_map(|field| {
+                let ident = &field.ident;
+                let boxed = |typ: &str| typ.parse::<TokenStream>().unwrap();
+                let (typ, value) = if let Some(typ) = field.typ.strip_prefix("Option<Box<") {
+                    let typ = boxed(typ.strip_suffix(">>")?);
+                    (quote!(Option<&#typ>), quote!(self.#ident.as_deref()))
+                } else {
+                    let typ = boxed(field.typ.strip_prefix("Box<")?.strip_suffix('>')?);
+                    (quote!(&#typ), quote!(&self.#ident))
+                };
+                let doc = format!("The `{}` field, without its `Box`.", ident);
+                Some(quote! {
+                    #[doc = #doc]
+                    pub fn #ident(&self) -> #typ {
+                        #value
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        if accessors.is_empty() {
+            return TokenStream::new();
+        }
+        quote! {
+            impl #lifetime #name #lifetime {
+                #(#accessors)*
+            }
+        }
+    }
+
+    /// A builder for the struct `name`, which has the fields `fields`.
+    fn expand_builder(
+        &self,
+        name: &syn::Ident,
+        lifetime: Option<&TokenStream>,
+        fields: &[StructField],
+    ) -> TokenStream {
+        let builder = format_ident!("{}Builder", name);
+        let vis = self.visibility();
+        let missing_field = format!("{}builder::MissingField", self.schemafy_path)
+            .parse::<TokenStream>()
+            .unwrap();
+        let nullable = format!("{}Nullable", self.schemafy_path)
+            .parse::<TokenStream>()
+            .unwrap();
+        let idents = fields.iter().map(|field| &field.ident).collect::<Vec<_>>();
+        // Required fields are kept in an `Option` until they are set, the
+        // others start with their default value
+        let builder_fields = fields.iter().map(|field| {
+            let ident = &field.ident;
+            let typ = field.typ.parse::<TokenStream>().unwrap();
+            match field.default {
+                Some(_) => quote!(#ident: #typ),
+                None => quote!(#ident: Option<#typ>),
+            }
+        });
+        let initializers = fields.iter().map(|field| match &field.default {
+            Some(default) => default.initializer(),
+            None => quote!(None),
+        });
+        let setters = fields.iter().map(|field| {
+            let ident = &field.ident;
+            // The setter of a field named `build` would clash with `build`
+            let setter = if ident == "build" {
+                format_ident!("build_")
+            } else {
+                ident.clone()
+            };
+            let (typ, value) = match self.optional_inner(&field.typ) {
+                Some(typ) if field.typ.starts_with("Option<") => (typ, quote!(Some(value.into()))),
+                Some(typ) => (typ, quote!(#nullable::Value(value.into()))),
+                None if field.default.is_some() => (&field.typ[..], quote!(value.into())),
+                None => (&field.typ[..], quote!(Some(value.into()))),
+            };
+            let typ = typ.parse::<TokenStream>().unwrap();
+            quote! {
+                pub fn #setter(mut self, value: impl Into<#typ>) -> Self {
+                    self.#ident = #value;
+                    self
+                }
             }
-            if result.typ.starts_with("Option<") {
-                result
-                    .attributes
-                    .push("skip_serializing_if=\"Option::is_none\"".into());
+        });
+        let values = fields.iter().map(|field| {
+            let ident = &field.ident;
+            let name = &field.name;
+            match field.default {
+                Some(_) => quote!(self.#ident),
+                None => quote!(self.#ident.ok_or(#missing_field(#name))?),
+            }
+        });
+        quote! {
+            impl #lifetime #name #lifetime {
+                pub fn builder() -> #builder #lifetime {
+                    #builder {
+                        #(#idents: #initializers),*
+                    }
+                }
+            }
+
+            #[derive(Clone, Debug)]
+            #vis struct #builder #lifetime {
+                #(#builder_fields),*
+            }
+
+            impl #lifetime #builder #lifetime {
+                #(#setters)*
+
+                pub fn build(self) -> Result<#name #lifetime, #missing_field> {
+                    Ok(#name {
+                        #(#idents: #values),*
+                    })
+                }
             }
         }
+    }
+
+    /// The type of strings.
+    fn string_type(&self) -> &'static str {
+        if self.borrowed_strings {
+            COW_STR
+        } else {
+            "String"
+        }
+    }
+
+    /// The lifetime of the generated type `name`, if it has one.
+    fn lifetime(&self, name: &str) -> Option<TokenStream> {
+        if self.borrowing_types.contains(name) {
+            Some(quote!(<'a>))
+        } else {
+            None
+        }
+    }
+
+    /// `typ` with the lifetime of every generated type in it.
+    fn with_lifetimes(&self, typ: &str) -> String {
+        if self.borrowing_types.is_empty() {
+            return typ.to_string();
+        }
+        let mut result = String::new();
+        let mut path = String::new();
+        for c in typ.chars().chain(std::iter::once(' ')) {
+            if c.is_alphanumeric() || c == '_' || c == ':' || c == '\'' {
+                path.push(c);
+                continue;
+            }
+            result.push_str(&path);
+            if self.borrowing_types.contains(&path) {
+                result.push_str("<'a>");
+            }
+            path.clear();
+            result.push(c);
+        }
+        result.pop();
         result
     }
 
-    fn expand_type_(&mut self, typ: &Schema) -> FieldType {
-        if let Some(ref ref_) = typ.ref_ {
-            self.type_ref(ref_).into()
-        } else if typ.any_of.as_ref().map_or(false, |a| a.len() >= 2) {
-            let any_of = typ.any_of.as_ref().unwrap();
-            let simple = self.schema(&any_of[0]);
-            let array = self.schema(&any_of[1]);
-            if !array.type_.is_empty() {
-                if let SimpleTypes::Array = array.type_[0] {
-                    if simple == self.schema(&array.items[0]) {
-                        return FieldType {
-                            typ: format!("Vec<{}>", self.expand_type_(&any_of[0]).typ),
-                            attributes: vec![format!(
-                                r#"with="{}one_or_many""#,
-                                self.schemafy_path
-                            )],
-                            default: true,
-                        };
-                    }
-                }
+    /// The generated types which hold a borrowed string, directly or
+    /// through other generated types.
+    fn borrowing_types(&self) -> BTreeSet<String> {
+        let mut types = BTreeSet::new();
+        loop {
+            let added = self
+                .member_types
+                .iter()
+                .filter(|(name, member_types)| {
+                    !types.contains(*name)
+                        && member_types.iter().any(|typ| {
+                            typ.contains("Cow<'a")
+                                || type_paths(typ).any(|path| types.contains(path))
+                        })
+                })
+                .map(|(name, _)| name.clone())
+                .collect::<Vec<_>>();
+            if added.is_empty() {
+                return types;
             }
-            "serde_json::Value".into()
-        } else if typ.one_of.as_ref().map_or(false, |a| a.len() >= 2) {
-            let schemas = typ.one_of.as_ref().unwrap();
-            let (type_name, type_def) = self.expand_one_of(schemas);
-            self.types.push((type_name.clone(), type_def));
-            type_name.into()
-        } else if typ.type_.len() == 2 {
-            if typ.type_[0] == SimpleTypes::Null || typ.type_[1] == SimpleTypes::Null {
-                let mut ty = typ.clone();
-                ty.type_.retain(|x| *x != SimpleTypes::Null);
+            types.extend(added);
+        }
+    }
 
-                FieldType {
-                    typ: format!("Option<{}>", self.expand_type_(&ty).typ),
-                    attributes: vec![],
-                    default: true,
+    /// The expression of the `default` of a field of type `typ`, if it can
+    /// be written as one: a string, number, boolean or enum variant.
+    fn default_value(&self, typ: &str, default: &Value) -> Option<TokenStream> {
+        if !typ.starts_with("Option<") {
+            if let Some(typ) = self.optional_inner(typ) {
+                let nullable = format!("{}Nullable", self.schemafy_path)
+                    .parse::<TokenStream>()
+                    .unwrap();
+                return match default {
+                    Value::Null => Some(quote!(#nullable::Null)),
+                    _ => self
+                        .default_value(typ, default)
+                        .map(|value| quote!(#nullable::Value(#value))),
+                };
+            }
+        }
+        if let Some(typ) = typ
+            .strip_prefix("Option<")
+            .and_then(|t| t.strip_suffix('>'))
+        {
+            return match default {
+                Value::Null => Some(quote!(None)),
+                _ => self
+                    .default_value(typ, default)
+                    .map(|value| quote!(Some(#value))),
+            };
+        }
+        match (typ, default) {
+            ("String", Value::String(s)) => Some(quote!(#s.to_string())),
+            (COW_STR, Value::String(s)) => Some(quote!(::std::borrow::Cow::Borrowed(#s))),
+            ("bool", Value::Bool(b)) => Some(quote!(#b)),
+            ("i64", Value::Number(n)) => n.as_i64().map(|n| quote!(#n)),
+            ("u64", Value::Number(n)) => n.as_u64().map(|n| quote!(#n)),
+            ("f64", Value::Number(n)) => n.as_f64().map(|n| quote!(#n)),
+            (ORDERED_FLOAT, Value::Number(n)) => {
+                n.as_f64().map(|n| quote!(ordered_float::OrderedFloat(#n)))
+            }
+            _ => {
+                let variants = self.enum_variants.get(typ)?;
+                let (_, variant) = variants.iter().find(|(value, _)| value == default)?;
+                let typ = syn::Ident::new(typ, Span::call_site());
+                Some(quote!(#typ::#variant))
+            }
+        }
+    }
+
+    /// The visibility of the generated types and their fields.
+    pub(crate) fn visibility(&self) -> TokenStream {
+        self.visibility
+            .parse()
+            .unwrap_or_else(|err| panic!("Invalid visibility `{}`: {}", self.visibility, err))
+    }
+
+    /// The extra attributes configured for the type generated from the
+    /// definition `original_name`, which may also be given by its Rust
+    /// name `name`.
+    fn type_attributes(&self, original_name: &str, name: &str) -> TokenStream {
+        let mut names = vec![original_name];
+        if name != original_name {
+            names.push(name);
+        }
+        names
+            .into_iter()
+            .filter_map(|name| self.type_attributes.get(name))
+            .flatten()
+            .map(|attr| {
+                attr.parse::<TokenStream>()
+                    .unwrap_or_else(|err| panic!("Invalid attribute `{}`: {}", attr, err))
+            })
+            .collect()
+    }
+
+    /// `validate_json` for the type `name`, validating values against the
+    /// schema at `pointer` in the embedded schema.
+    fn expand_validate_json(&self, name: &syn::Ident, pointer: &str) -> TokenStream {
+        let schema = match self.embedded_schema {
+            Some(schema) => schema,
+            None => return TokenStream::new(),
+        };
+        let path = |path: &str| {
+            format!("{}{}", self.schemafy_path, path)
+                .parse::<TokenStream>()
+                .unwrap()
+        };
+        let errors = path("validation::ValidationErrors");
+        let validate_json = path("embedded::validate_json");
+        quote! {
+            impl #name {
+                /// Validates `value` against the schema, then deserializes it.
+                pub fn validate_json(value: &serde_json::Value) -> Result<Self, #errors> {
+                    #validate_json(#schema, #pointer, value)
                 }
+            }
+        }
+    }
+
+    fn validation_path(&self) -> TokenStream {
+        format!("{}validation", self.schemafy_path)
+            .parse::<TokenStream>()
+            .unwrap()
+    }
+
+    /// Checks of the constraints of `schema` against `value`, a variable of
+    /// type `&{typ}` at the JSON pointer `path`. `depth` counts the
+    /// enclosing loops, to give each loop index a distinct name.
+    fn validation_checks(
+        &self,
+        schema: &Schema,
+        typ: &str,
+        path: &TokenStream,
+        depth: usize,
+    ) -> TokenStream {
+        let validation = self.validation_path();
+        let schema = match schema.ref_ {
+            Some(ref ref_) => self.schema(self.schema_ref(ref_)),
+            None => Cow::Borrowed(schema),
+        };
+        if let Some(inner) = self.optional_inner(typ) {
+            let inner_schema = schema
+                .any_of
+                .as_deref()
+                .or(schema.one_of.as_deref())
+                .and_then(non_null_variant)
+                .unwrap_or(&schema);
+            let checks = self.validation_checks(inner_schema, inner, path, depth);
+            if checks.is_empty() {
+                return checks;
+            }
+            let value = if typ.starts_with("Option<") {
+                quote!(value)
             } else {
-                "serde_json::Value".into()
+                quote!(value.as_option())
+            };
+            return quote! {
+                if let Some(value) = #value {
+                    #checks
+                }
+            };
+        }
+
+        let mut checks = Vec::new();
+        let mut check = |condition: TokenStream, message: String| {
+            checks.push(quote! {
+                if #condition {
+                    errors.push(#path, #message);
+                }
+            });
+        };
+        let as_f64 = match typ {
+            "i64" => Some(quote!((*value as f64))),
+            "f64" => Some(quote!(*value)),
+            ORDERED_FLOAT => Some(quote!(value.0)),
+            _ => None,
+        };
+        if let Some(v) = as_f64 {
+            if let Some(min) = schema.minimum {
+                let lit = proc_macro2::Literal::f64_unsuffixed(min);
+                if schema.exclusive_minimum == Some(true) {
+                    check(quote!(#v <= #lit), format!("must be greater than {}", min));
+                } else {
+                    check(quote!(#v < #lit), format!("must be at least {}", min));
+                }
             }
-        } else if typ.type_.len() == 1 {
-            match typ.type_[0] {
-                SimpleTypes::String => {
-                    if typ.enum_.as_ref().map_or(false, |e| e.is_empty()) {
-                        "serde_json::Value".into()
-                    } else {
-                        "String".into()
-                    }
+            if let Some(max) = schema.maximum {
+                let lit = proc_macro2::Literal::f64_unsuffixed(max);
+                if schema.exclusive_maximum == Some(true) {
+                    check(quote!(#v >= #lit), format!("must be less than {}", max));
+                } else {
+                    check(quote!(#v > #lit), format!("must be at most {}", max));
                 }
-                SimpleTypes::Integer => "i64".into(),
-                SimpleTypes::Boolean => "bool".into(),
-                SimpleTypes::Number => "f64".into(),
-                // Handle objects defined inline
-                SimpleTypes::Object
-                    if !typ.properties.is_empty()
-                        || typ.additional_properties == Some(Value::Bool(false)) =>
-                {
-                    let name = format!(
-                        "{}{}",
-                        self.current_type.to_pascal_case(),
-                        self.current_field.to_pascal_case()
-                    );
-                    let tokens = self.expand_schema(&name, typ);
-                    self.types.push((name.clone(), tokens));
-                    name.into()
+            }
+            if let Some(n) = schema.multiple_of {
+                let message = format!("must be a multiple of {}", n);
+                if typ == "i64" && n.fract() == 0.0 && (1.0..=i64::MAX as f64).contains(&n) {
+                    let lit = proc_macro2::Literal::i64_unsuffixed(n as i64);
+                    check(quote!(*value % #lit != 0), message);
+                } else {
+                    let lit = proc_macro2::Literal::f64_unsuffixed(n);
+                    check(quote!(!#validation::is_multiple_of(#v, #lit)), message);
                 }
-                SimpleTypes::Object => {
-                    let prop = match typ.additional_properties {
-                        Some(ref props) if props.is_object() => {
-                            let prop = serde_json::from_value(props.clone()).unwrap();
-                            self.expand_type_(&prop).typ
+            }
+        }
+        if typ == "String" {
+            if let Some(min) = schema.min_length.as_ref().and_then(Value::as_u64) {
+                let lit = proc_macro2::Literal::u64_unsuffixed(min);
+                check(
+                    quote!(value.chars().count() < #lit),
+                    format!("must be at least {} characters long", min),
+                );
+            }
+            if let Some(max) = schema.max_length {
+                let lit = proc_macro2::Literal::i64_unsuffixed(max);
+                check(
+                    quote!(value.chars().count() > #lit),
+                    format!("must be at most {} characters long", max),
+                );
+            }
+            if let Some(pattern) = &schema.pattern {
+                let pattern_path = format!("{}pattern", self.schemafy_path)
+                    .parse::<TokenStream>()
+                    .unwrap();
+                check(
+                    quote!(!#pattern_path::is_match(#pattern, value)),
+                    format!("must match the pattern `{}`", pattern),
+                );
+            }
+        }
+        let is_array = typ.starts_with("Vec<")
+            || self
+                .set_type
+                .is_some_and(|set_type| typ.starts_with(set_type));
+        let is_map = typ.starts_with(&format!("{}<", self.map_type));
+        if is_array {
+            if let Some(min) = schema.min_items.as_ref().and_then(Value::as_u64) {
+                let lit = proc_macro2::Literal::u64_unsuffixed(min);
+                check(
+                    quote!(value.len() < #lit),
+                    format!("must have at least {} items", min),
+                );
+            }
+            if let Some(max) = schema.max_items {
+                let lit = proc_macro2::Literal::i64_unsuffixed(max);
+                check(
+                    quote!(value.len() > #lit),
+                    format!("must have at most {} items", max),
+                );
+            }
+            if let Some(contains) = &schema.contains {
+                let contains = schema_json(contains);
+                check(
+                    quote!(!#validation::contains(#contains, value)),
+                    format!("does not contain an item matching `{}`", contains),
+                );
+            }
+        }
+        if is_map {
+            if let Some(min) = schema.min_properties.as_ref().and_then(Value::as_u64) {
+                let lit = proc_macro2::Literal::u64_unsuffixed(min);
+                check(
+                    quote!(value.len() < #lit),
+                    format!("must have at least {} properties", min),
+                );
+            }
+            if let Some(max) = schema.max_properties {
+                let lit = proc_macro2::Literal::i64_unsuffixed(max);
+                check(
+                    quote!(value.len() > #lit),
+                    format!("must have at most {} properties", max),
+                );
+            }
+        }
+        if typ.starts_with("Vec<") {
+            if schema.unique_items == Some(true) {
+                check(
+                    quote!(value
+                        .iter()
+                        .enumerate()
+                        .any(|(i, item)| value[..i].contains(item))),
+                    "must not contain duplicate items".into(),
+                );
+            }
+            let item_type = &typ["Vec<".len()..typ.len() - 1];
+            if let [item_schema] = &schema.items[..] {
+                let i = syn::Ident::new(&format!("i{}", depth), Span::call_site());
+                let item_path = quote!(&#validation::join(#path, #i));
+                let item_checks =
+                    self.validation_checks(item_schema, item_type, &item_path, depth + 1);
+                if !item_checks.is_empty() {
+                    checks.push(quote! {
+                        for (#i, value) in value.iter().enumerate() {
+                            #item_checks
                         }
-                        _ => "serde_json::Value".into(),
-                    };
-                    let result = format!("::std::collections::BTreeMap<String, {}>", prop);
-                    FieldType {
-                        typ: result,
-                        attributes: Vec::new(),
-                        default: typ.default == Some(Value::Object(Default::default())),
+                    });
+                }
+            }
+        }
+        if self.is_generated_struct(&schema) {
+            checks.push(quote! {
+                #validation::Validate::validate_into(value, #path, errors);
+            });
+        }
+        quote!(#(#checks)*)
+    }
+
+    /// Lists the constraints of a field with the schema `schema` which its
+    /// type `typ` does not enforce, as markdown list items.
+    fn constraints(&self, schema: &Schema, typ: &str) -> Vec<String> {
+        let schema = self.schema(schema);
+        let (schema, typ) = match self.optional_inner(typ) {
+            Some(inner) => {
+                let variant = schema
+                    .any_of
+                    .as_deref()
+                    .or(schema.one_of.as_deref())
+                    .and_then(non_null_variant);
+                match variant {
+                    Some(variant) => (self.schema(variant), inner),
+                    None => (schema, inner),
+                }
+            }
+            None => (schema, typ),
+        };
+
+        let mut constraints = Vec::new();
+        let mut push = |keyword: &str, value: String| {
+            constraints.push(format!("- `{}`: {}", keyword, value));
+        };
+        let is_array = typ.starts_with("Vec<");
+        let is_set = self
+            .set_type
+            .is_some_and(|set_type| typ.starts_with(set_type));
+        if typ == "String" {
+            if let Some(format) = &schema.format {
+                push("format", format!("`{}`", format));
+            }
+            if let Some(pattern) = &schema.pattern {
+                push("pattern", format!("`{}`", pattern));
+            }
+            if let Some(min) = schema.min_length.as_ref().and_then(Value::as_u64) {
+                push("minLength", min.to_string());
+            }
+            if let Some(max) = schema.max_length {
+                push("maxLength", max.to_string());
+            }
+        } else if typ == "i64" || typ == "f64" || typ == ORDERED_FLOAT {
+            let exclusive = |exclusive: Option<bool>| {
+                if exclusive == Some(true) {
+                    " (exclusive)"
+                } else {
+                    ""
+                }
+            };
+            if let Some(min) = schema.minimum {
+                push(
+                    "minimum",
+                    format!("{}{}", min, exclusive(schema.exclusive_minimum)),
+                );
+            }
+            if let Some(max) = schema.maximum {
+                push(
+                    "maximum",
+                    format!("{}{}", max, exclusive(schema.exclusive_maximum)),
+                );
+            }
+            if let Some(n) = schema.multiple_of {
+                push("multipleOf", n.to_string());
+            }
+        } else if is_array || is_set {
+            if let Some(min) = schema.min_items.as_ref().and_then(Value::as_u64) {
+                push("minItems", min.to_string());
+            }
+            if let Some(max) = schema.max_items {
+                push("maxItems", max.to_string());
+            }
+            if is_array && schema.unique_items == Some(true) {
+                push("uniqueItems", "true".into());
+            }
+            if let Some(contains) = &schema.contains {
+                push("contains", format!("`{}`", schema_json(contains)));
+            }
+        } else if typ.starts_with(&format!("{}<", self.map_type)) {
+            if let Some(min) = schema.min_properties.as_ref().and_then(Value::as_u64) {
+                push("minProperties", min.to_string());
+            }
+            if let Some(max) = schema.max_properties {
+                push("maxProperties", max.to_string());
+            }
+        }
+        constraints
+    }
+
+    /// The `#[validate(..)]` attribute of the `validator` crate checking
+    /// the constraints of a field with the schema `schema` and type `typ`.
+    fn validator_attributes(&self, schema: &Schema, typ: &str) -> Option<TokenStream> {
+        let schema = match schema.ref_ {
+            Some(ref ref_) => self.schema(self.schema_ref(ref_)),
+            None => Cow::Borrowed(schema),
+        };
+        let (schema, typ) = match typ
+            .strip_prefix("Option<")
+            .and_then(|t| t.strip_suffix('>'))
+        {
+            Some(inner) => {
+                let variant = schema
+                    .any_of
+                    .as_deref()
+                    .or(schema.one_of.as_deref())
+                    .and_then(non_null_variant);
+                let inner_schema = match variant {
+                    Some(variant) => Cow::Owned(match variant.ref_ {
+                        Some(ref ref_) => self.schema(self.schema_ref(ref_)).into_owned(),
+                        None => variant.clone(),
+                    }),
+                    None => schema,
+                };
+                (inner_schema, inner)
+            }
+            None => (schema, typ),
+        };
+
+        let mut validators = Vec::new();
+        let length = |min: Option<u64>, max: Option<u64>| {
+            let bounds = min
+                .map(|min| quote!(min = #min))
+                .into_iter()
+                .chain(max.map(|max| quote!(max = #max)))
+                .collect::<Vec<_>>();
+            if bounds.is_empty() {
+                None
+            } else {
+                Some(quote!(length( #(#bounds),* )))
+            }
+        };
+        let is_array = typ.starts_with("Vec<")
+            || self
+                .set_type
+                .is_some_and(|set_type| typ.starts_with(set_type));
+        if typ == "String" {
+            let max = schema.max_length.and_then(|max| u64::try_from(max).ok());
+            validators.extend(length(
+                schema.min_length.as_ref().and_then(Value::as_u64),
+                max,
+            ));
+            if let Some(pattern) = &schema.pattern {
+                let regex = format!("{}pattern::regex", self.schemafy_path)
+                    .parse::<TokenStream>()
+                    .unwrap();
+                validators.push(quote!(regex(path = #regex(#pattern))));
+            }
+        } else if is_array {
+            let max = schema.max_items.and_then(|max| u64::try_from(max).ok());
+            validators.extend(length(
+                schema.min_items.as_ref().and_then(Value::as_u64),
+                max,
+            ));
+        } else if typ.starts_with(&format!("{}<", self.map_type)) {
+            validators.extend(length(
+                schema.min_properties.as_ref().and_then(Value::as_u64),
+                schema
+                    .max_properties
+                    .and_then(|max| u64::try_from(max).ok()),
+            ));
+        } else if typ == "i64" || typ == "f64" {
+            let lit = |value: f64| {
+                if typ == "i64" {
+                    proc_macro2::Literal::i64_unsuffixed(value as i64)
+                } else {
+                    proc_macro2::Literal::f64_unsuffixed(value)
+                }
+            };
+            let mut bounds = Vec::new();
+            if let Some(min) = schema.minimum {
+                let min = lit(min);
+                bounds.push(if schema.exclusive_minimum == Some(true) {
+                    quote!(exclusive_min = #min)
+                } else {
+                    quote!(min = #min)
+                });
+            }
+            if let Some(max) = schema.maximum {
+                let max = lit(max);
+                bounds.push(if schema.exclusive_maximum == Some(true) {
+                    quote!(exclusive_max = #max)
+                } else {
+                    quote!(max = #max)
+                });
+            }
+            if !bounds.is_empty() {
+                validators.push(quote!(range( #(#bounds),* )));
+            }
+        }
+
+        // Generated structs derive `Validate` themselves
+        let items = schema
+            .items
+            .first()
+            .filter(|_| is_array)
+            .map(|items| match items.ref_ {
+                Some(ref ref_) => self.schema(self.schema_ref(ref_)),
+                None => Cow::Borrowed(items),
+            });
+        if self.is_generated_struct(&schema)
+            || items.is_some_and(|items| self.is_generated_struct(&items))
+        {
+            validators.push(quote!(nested));
+        }
+
+        if validators.is_empty() {
+            None
+        } else {
+            Some(quote! {
+                #[validate( #(#validators),* )]
+            })
+        }
+    }
+
+    /// Whether `schema` is expanded into a struct (which implements
+    /// `Validate` when validation is enabled).
+    fn is_generated_struct(&self, schema: &Schema) -> bool {
+        let is_object = schema.type_.is_empty() || schema.type_ == [SimpleTypes::Object];
+        let has_format_type = schema
+            .format
+            .as_ref()
+            .is_some_and(|format| self.formats.contains_key(format));
+        is_object
+            && !has_format_type
+            && schema.enum_.is_none()
+            && (!schema.properties.is_empty()
+                || (schema.additional_properties == Some(Value::Bool(false))
+                    && schema.pattern_properties.is_empty())
+                || self.is_empty_struct(schema))
+    }
+
+    /// `as_str`, `Display` and `FromStr` for an enum whose variants
+    /// serialize as the given strings. With `open_enums`, the `Unknown`
+    /// variant holds any other string and the enum is serialized with
+    /// `as_str`.
+    fn expand_string_enum_impls(
+        &self,
+        name: &syn::Ident,
+        variants: &[(syn::Ident, &String)],
+    ) -> TokenStream {
+        let enums = format!("{}enums", self.schemafy_path)
+            .parse::<TokenStream>()
+            .unwrap();
+        let idents = variants.iter().map(|(ident, _)| ident).collect::<Vec<_>>();
+        let values = variants.iter().map(|(_, value)| value).collect::<Vec<_>>();
+        let (str_lifetime, unknown_str, unknown_parse, serialize) = if self.open_enums {
+            (
+                None,
+                Some(quote!(#name::Unknown(value) => value,)),
+                quote!(Ok(#name::Unknown(s.to_string()))),
+                Some(quote! {
+                    impl serde::Serialize for #name {
+                        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+                        where
+                            S: serde::Serializer,
+                        {
+                            serializer.serialize_str(self.as_str())
+                        }
+                    }
+                }),
+            )
+        } else {
+            (
+                Some(quote!('static)),
+                None,
+                quote! {
+                    Err(#enums::ParseEnumError {
+                        value: s.to_string(),
+                        expected: &[#(#values),*],
+                    })
+                },
+                None,
+            )
+        };
+        quote! {
+            impl #name {
+                pub fn as_str(&self) -> &#str_lifetime str {
+                    match self {
+                        #(#name::#idents => #values,)*
+                        #unknown_str
                     }
                 }
-                SimpleTypes::Array => {
-                    let item_type = typ.items.get(0).map_or("serde_json::Value".into(), |item| {
-                        self.current_type = format!("{}Item", self.current_type);
-                        self.expand_type_(item).typ
-                    });
-                    format!("Vec<{}>", item_type).into()
+            }
+            #serialize
+            impl ::std::fmt::Display for #name {
+                fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                    f.write_str(self.as_str())
+                }
+            }
+            impl ::std::str::FromStr for #name {
+                type Err = #enums::ParseEnumError;
+                fn from_str(s: &str) -> Result<Self, Self::Err> {
+                    match s {
+                        #(#values => Ok(#name::#idents),)*
+                        _ => #unknown_parse,
+                    }
                 }
-                _ => "serde_json::Value".into(),
             }
+        }
+    }
+
+    /// A `Deserialize` impl for a string enum which ignores the case of the
+    /// value and/or surrounding whitespace, or which is open.
+    fn expand_lenient_enum_deserialize(
+        &self,
+        name: &syn::Ident,
+        variants: &[(syn::Ident, &String)],
+    ) -> TokenStream {
+        let idents = variants.iter().map(|(ident, _)| ident);
+        let values = variants.iter().map(|(_, value)| value).collect::<Vec<_>>();
+        let trim = if self.trim_enums {
+            Some(quote!(let s = s.trim();))
         } else {
-            "serde_json::Value".into()
+            None
+        };
+        let matches = if self.case_insensitive_enums {
+            quote!(s.eq_ignore_ascii_case)
+        } else {
+            quote!(s.eq)
+        };
+        // Unknown values are kept as they were received
+        let unknown = if self.open_enums {
+            quote!(Ok(#name::Unknown(value)))
+        } else {
+            quote!(Err(serde::de::Error::unknown_variant(s, &[#(#values),*])))
+        };
+        quote! {
+            impl<'de> serde::Deserialize<'de> for #name {
+                fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+                where
+                    D: serde::Deserializer<'de>,
+                {
+                    let value = <String as serde::Deserialize>::deserialize(deserializer)?;
+                    let s = value.as_str();
+                    #trim
+                    #(
+                        if #matches(#values) {
+                            return Ok(#name::#idents);
+                        }
+                    )*
+                    #unknown
+                }
+            }
         }
     }
 
-    fn expand_one_of(&mut self, schemas: &[Schema]) -> (String, TokenStream) {
+    /// The untagged enum of the `oneOf` `schemas`. With a `discriminator`,
+    /// the variants referring to a definition are named after the value
+    /// of its property for them.
+    fn expand_one_of(
+        &mut self,
+        schemas: &[Schema],
+        discriminator: Option<&SchemaDiscriminator>,
+    ) -> (String, TokenStream) {
         let current_field = if self.current_field.is_empty() {
             "".to_owned()
         } else {
@@ -539,40 +3670,100 @@ impl<'r> Expander<'r> {
                 .to_string()
                 .to_pascal_case()
         };
-        let saved_type = format!("{}{}", self.current_type, current_field);
+        let saved_type = if self.pointer_names {
+            self.anonymous_type_name()
+        } else {
+            format!("{}{}", self.current_type, current_field)
+        };
         if schemas.is_empty() {
-            return (saved_type, TokenStream::new());
+            return (self.type_name(&saved_type), TokenStream::new());
         }
+        let tags = schemas
+            .iter()
+            .map(|schema| Some(discriminator_value(discriminator?, schema.ref_.as_ref()?)))
+            .collect::<Vec<_>>();
         let (variant_names, variant_types): (Vec<_>, Vec<_>) = schemas
             .iter()
             .enumerate()
             .map(|(i, schema)| {
-                let name = schema.id.clone().unwrap_or_else(|| format!("Variant{}", i));
+                let name = match (&schema.id, &tags[i]) {
+                    (Some(id), _) => id.clone(),
+                    (None, Some(tag)) => str_to_ident(&self.naming.variant_name(tag)).to_string(),
+                    _ => format!("Variant{}", i),
+                };
                 if let Some(ref_) = &schema.ref_ {
                     let type_ = self.type_ref(ref_);
                     (format_ident!("{}", &name), format_ident!("{}", &type_))
                 } else {
-                    let type_name = format!("{}{}", saved_type, &name);
-                    let field_type = self.expand_schema(&type_name, schema);
+                    let type_name =
+                        self.inline_type_name(schema, format!("{}{}", saved_type, &name));
+                    let field_type = self.at(&["oneOf", &i.to_string()], |this| {
+                        this.expand_schema(&type_name, schema)
+                    });
                     self.types.push((type_name.clone(), field_type));
+                    let type_name = self.type_name(&type_name);
                     (format_ident!("{}", &name), format_ident!("{}", &type_name))
                 }
             })
             .unzip();
+        let saved_type = self.type_name(&saved_type);
+        self.member_types.insert(
+            saved_type.clone(),
+            variant_types.iter().map(|t| t.to_string()).collect(),
+        );
         let type_name_ident = syn::Ident::new(&saved_type, Span::call_site());
+        let lifetime = self.lifetime(&saved_type);
+        let variant_types = variant_types
+            .iter()
+            .map(|typ| match self.lifetime(&typ.to_string()) {
+                Some(lifetime) => quote!(#[serde(borrow)] #typ #lifetime),
+                None => quote!(#typ),
+            });
+        let vis = self.visibility();
+        // The variant is chosen by the value of the discriminator property,
+        // rather than by trying each in turn
+        let tagged = match discriminator {
+            Some(discriminator) if lifetime.is_none() && tags.iter().all(Option::is_some) => {
+                let tags = tags.iter().flatten().collect::<Vec<_>>();
+                Some(expand_discriminator_deserialize(
+                    &type_name_ident,
+                    &discriminator.property_name,
+                    &tags,
+                    &variant_names,
+                ))
+            }
+            _ => None,
+        };
+        let derives: &[&str] = if tagged.is_some() {
+            &["Clone", "PartialEq", "Debug", "Serialize"]
+        } else {
+            &["Clone", "PartialEq", "Debug", "Deserialize", "Serialize"]
+        };
+        let derive = self.derive_with_members(&saved_type, derives);
         let type_def = quote! {
-            #[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+            #derive
             #[serde(untagged)]
-            pub enum #type_name_ident {
+            #vis enum #type_name_ident #lifetime {
                 #(#variant_names(#variant_types)),*
             }
+            #tagged
         };
         (saved_type, type_def)
     }
 
     fn expand_definitions(&mut self, schema: &Schema) {
         for (name, def) in &schema.definitions {
-            let type_decl = self.expand_schema(name, def);
+            if self.replacement(name).is_some() {
+                continue;
+            }
+            self.schema_pointer = self.definitions_pointer.as_ref().map(|pointer| {
+                let name = name.replace('~', "~0").replace('/', "~1");
+                format!("{}/definitions/{}", pointer, name)
+            });
+            let start = self.types.len();
+            self.definition_depth += 1;
+            let type_decl = self.at(&["definitions", name], |this| this.expand_schema(name, def));
+            self.definition_depth -= 1;
             let definition_tokens = match def.description {
                 Some(ref comment) => {
                     let t = make_doc_comment(comment, LINE_LENGTH);
@@ -584,25 +3775,72 @@ impl<'r> Expander<'r> {
                 None => type_decl,
             };
             self.types.push((name.to_string(), definition_tokens));
+            if self.definition_depth == 0 {
+                self.definition_types
+                    .push((name.to_string(), start..self.types.len()));
+            }
         }
     }
 
     fn expand_schema(&mut self, original_name: &str, schema: &Schema) -> TokenStream {
+        // Only the schemas expanded from the root or its definitions can be
+        // located in the embedded schema
+        let pointer = self.schema_pointer.take();
+        let definitions_pointer = std::mem::replace(&mut self.definitions_pointer, pointer.clone());
         self.expand_definitions(schema);
+        self.definitions_pointer = definitions_pointer;
 
-        let pascal_case_name = replace_invalid_identifier_chars(&original_name.to_pascal_case());
+        let pascal_case_name = self.definition_type_name(original_name);
         self.current_type.clone_from(&pascal_case_name);
-        let (fields, default) = {
+        let (fields, default, rename_all, checks, conversions, member_types, struct_fields) = {
             let mut field_expander = FieldExpander {
                 default: true,
+                rename_all: None,
+                checks: Vec::new(),
+                conversions: Vec::new(),
+                member_types: Vec::new(),
+                struct_fields: Vec::new(),
                 expander: self,
             };
             let fields = field_expander.expand_fields(original_name, schema);
-            (fields, field_expander.default)
+            (
+                fields,
+                field_expander.default,
+                field_expander.rename_all,
+                field_expander.checks,
+                field_expander.conversions,
+                field_expander.member_types,
+                field_expander.struct_fields,
+            )
+        };
+        // An `impl Default` is only needed for the `default`s of the schema,
+        // as the other fields default to `None`
+        let default_fields = if struct_fields
+            .iter()
+            .any(|field| matches!(field.default, Some(FieldDefault::Schema(_))))
+        {
+            struct_fields
+                .iter()
+                .map(|field| {
+                    let ident = &field.ident;
+                    let initializer = field.default.as_ref()?.initializer();
+                    Some(quote!(#ident: #initializer))
+                })
+                .collect::<Option<Vec<_>>>()
+        } else {
+            None
         };
-        let name = syn::Ident::new(&pascal_case_name, Span::call_site());
-        let is_struct =
-            !fields.is_empty() || schema.additional_properties == Some(Value::Bool(false));
+        let default = default && default_fields.is_none();
+        let type_name = self.type_name(&pascal_case_name);
+        let name = syn::Ident::new(&type_name, Span::call_site());
+        let attrs = self.type_attributes(original_name, &pascal_case_name);
+        let deprecated = deprecated(schema);
+        self.uses_deprecated |= deprecated.is_some();
+        let vis = self.visibility();
+        let is_struct = !fields.is_empty()
+            || (schema.additional_properties == Some(Value::Bool(false))
+                && schema.pattern_properties.is_empty())
+            || self.is_empty_struct(schema);
         let serde_rename = if name == original_name {
             None
         } else {
@@ -610,40 +3848,270 @@ impl<'r> Expander<'r> {
                 #[serde(rename = #original_name)]
             })
         };
-        let is_enum = schema.enum_.as_ref().map_or(false, |e| !e.is_empty());
+        let serde_rename_all = rename_all.map(|rule| quote! { #[serde(rename_all = #rule)] });
+        let is_enum = schema.enum_.as_ref().is_some_and(|e| !e.is_empty())
+            && (self.int_enums || enum_names(schema).is_some() || !is_integer_enum(schema));
+        let lifetime = self.lifetime(&type_name);
         let type_decl = if is_struct {
-            let serde_deny_unknown = if schema.additional_properties == Some(Value::Bool(false))
-                && schema.pattern_properties.is_empty()
+            self.member_types.insert(type_name.clone(), member_types);
+            self.struct_fields.insert(
+                type_name.clone(),
+                struct_fields
+                    .iter()
+                    .map(|field| (field.name.clone(), field.ident.clone(), field.typ.clone()))
+                    .collect(),
+            );
+            let collects_other_members = other_members_field(schema).is_some();
+            let serde_deny_unknown = if !collects_other_members
+                && (self.deny_unknown_fields
+                    || schema.additional_properties == Some(Value::Bool(false)))
             {
                 Some(quote! { #[serde(deny_unknown_fields)] })
             } else {
                 None
             };
-            if default {
+            let non_exhaustive = if self.non_exhaustive
+                && schema.additional_properties != Some(Value::Bool(false))
+            {
+                Some(quote!(#[non_exhaustive]))
+            } else {
+                None
+            };
+            let validator_derive = if self.validator_attributes {
+                Some(quote! { #[derive(validator::Validate)] })
+            } else {
+                None
+            };
+            let struct_decl = if default {
+                let derive = self.derive_with_members(
+                    &type_name,
+                    &[
+                        "Clone",
+                        "PartialEq",
+                        "Debug",
+                        "Default",
+                        "Deserialize",
+                        "Serialize",
+                    ],
+                );
                 quote! {
-                    #[derive(Clone, PartialEq, Debug, Default, Deserialize, Serialize)]
+                    #derive
+                    #validator_derive
+                    #non_exhaustive
+                    #deprecated
+                    #attrs
                     #serde_rename
+                    #serde_rename_all
                     #serde_deny_unknown
-                    pub struct #name {
+                    #vis struct #name #lifetime {
                         #(#fields),*
                     }
                 }
             } else {
+                let derive = self.derive_with_members(
+                    &type_name,
+                    &["Clone", "PartialEq", "Debug", "Deserialize", "Serialize"],
+                );
                 quote! {
-                    #[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+                    #derive
+                    #validator_derive
+                    #non_exhaustive
+                    #deprecated
+                    #attrs
                     #serde_rename
+                    #serde_rename_all
                     #serde_deny_unknown
-                    pub struct #name {
+                    #vis struct #name #lifetime {
                         #(#fields),*
                     }
                 }
+            };
+            let struct_decl = if self.read_write_variants
+                && struct_fields
+                    .iter()
+                    .any(|field| field.read_only || field.write_only)
+            {
+                let mut derives = vec!["Clone", "PartialEq", "Debug"];
+                if default {
+                    derives.push("Default");
+                }
+                derives.extend(["Deserialize", "Serialize"]);
+                let derive = self.derive_with_members(&type_name, &derives);
+                let variant = |suffix: &str, skip: fn(&StructField) -> bool| {
+                    let variant = format_ident!("{}{}", name, suffix);
+                    let (fields, idents): (Vec<_>, Vec<_>) = fields
+                        .iter()
+                        .zip(&struct_fields)
+                        .filter(|(_, field)| !skip(field))
+                        .map(|(tokens, field)| (tokens, &field.ident))
+                        .unzip();
+                    quote! {
+                        #derive
+                        #non_exhaustive
+                        #serde_rename_all
+                        #serde_deny_unknown
+                        #vis struct #variant #lifetime {
+                            #(#fields),*
+                        }
+                        impl #lifetime From<#name #lifetime> for #variant #lifetime {
+                            fn from(value: #name #lifetime) -> Self {
+                                #variant {
+                                    #(#idents: value.#idents),*
+                                }
+                            }
+                        }
+                    }
+                };
+                let read = variant("Read", |field| field.write_only);
+                let write = variant("Write", |field| field.read_only);
+                quote! {
+                    #struct_decl
+                    #read
+                    #write
+                }
+            } else {
+                struct_decl
+            };
+            let struct_decl = match default_fields {
+                Some(default_fields) => quote! {
+                    #struct_decl
+                    impl #lifetime Default for #name #lifetime {
+                        fn default() -> Self {
+                            #name {
+                                #(#default_fields),*
+                            }
+                        }
+                    }
+                },
+                None => struct_decl,
+            };
+            let struct_decl = if self.builders {
+                let builder = self.expand_builder(&name, lifetime.as_ref(), &struct_fields);
+                quote! {
+                    #struct_decl
+                    #builder
+                }
+            } else {
+                struct_decl
+            };
+            let accessors = self.expand_box_accessors(&name, lifetime.as_ref(), &struct_fields);
+            let struct_decl = quote! {
+                #struct_decl
+                #accessors
+            };
+            let struct_decl = if self.constructors {
+                let constructor = expand_constructor(&name, lifetime.as_ref(), &struct_fields);
+                quote! {
+                    #struct_decl
+                    #constructor
+                }
+            } else {
+                struct_decl
+            };
+            let struct_decl = match &pointer {
+                Some(pointer) => {
+                    let validate_json = self.expand_validate_json(&name, pointer);
+                    quote! {
+                        #struct_decl
+                        #validate_json
+                    }
+                }
+                None => struct_decl,
+            };
+            let struct_decl = if self.try_from_value {
+                let convert = format!("{}convert", self.schemafy_path)
+                    .parse::<TokenStream>()
+                    .unwrap();
+                let check = if conversions.is_empty() {
+                    quote! { #convert::object(value, path, errors); }
+                } else {
+                    quote! {
+                        if let Some(object) = #convert::object(value, path, errors) {
+                            #(#conversions)*
+                        }
+                    }
+                };
+                let try_from = self.expand_try_from_value(&name, check);
+                quote! {
+                    #struct_decl
+                    #try_from
+                }
+            } else {
+                struct_decl
+            };
+            if self.validate {
+                let validation = self.validation_path();
+                let (path, errors) = if checks.is_empty() {
+                    (quote!(_path), quote!(_errors))
+                } else {
+                    (quote!(path), quote!(errors))
+                };
+                quote! {
+                    #struct_decl
+                    impl #lifetime #validation::Validate for #name #lifetime {
+                        fn validate_into(
+                            &self,
+                            #path: &str,
+                            #errors: &mut #validation::ValidationErrors,
+                        ) {
+                            #(#checks)*
+                        }
+                    }
+                }
+            } else {
+                struct_decl
+            }
+        } else if let Some([Value::String(value)]) = schema
+            .enum_
+            .as_deref()
+            .filter(|_| self.single_value_structs && !self.open_enums)
+        {
+            // A single value can only be serialized one way, so the type needs no data
+            self.member_types.insert(type_name.clone(), Vec::new());
+            let derive = self.derive_with_members(
+                &type_name,
+                &["Clone", "Copy", "PartialEq", "Debug", "Default"],
+            );
+            quote! {
+                #derive
+                #attrs
+                #vis struct #name;
+                impl #name {
+                    pub const VALUE: &'static str = #value;
+                }
+                impl serde::Serialize for #name {
+                    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+                    where
+                        S: serde::Serializer,
+                    {
+                        serializer.serialize_str(#name::VALUE)
+                    }
+                }
+                impl<'de> serde::Deserialize<'de> for #name {
+                    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+                    where
+                        D: serde::Deserializer<'de>,
+                    {
+                        let s = <String as serde::Deserialize>::deserialize(deserializer)?;
+                        if s == #name::VALUE {
+                            Ok(#name)
+                        } else {
+                            Err(serde::de::Error::invalid_value(
+                                serde::de::Unexpected::Str(&s),
+                                &#name::VALUE,
+                            ))
+                        }
+                    }
+                }
             }
         } else if is_enum {
             let mut optional = false;
             let mut repr_i64 = false;
-            let variants = if schema.enum_names.as_ref().map_or(false, |e| !e.is_empty()) {
-                let values = schema.enum_.as_ref().map_or(&[][..], |v| v);
-                let names = schema.enum_names.as_ref().map_or(&[][..], |v| v);
+            let mut string_variants = Vec::new();
+            let mut variant_idents = Vec::new();
+            let values = schema.enum_.as_ref().map_or(&[][..], |v| v);
+            let names = enum_names(schema);
+            if let Some(names) = names {
                 if names.len() != values.len() {
                     panic!(
                         "enumNames(length {}) and enum(length {}) have different length",
@@ -651,134 +4119,288 @@ impl<'r> Expander<'r> {
                         values.len()
                     )
                 }
-                names
-                    .iter()
-                    .enumerate()
-                    .map(|(idx, name)| (&values[idx], name))
-                    .flat_map(|(value, name)| {
-                        let pascal_case_variant = name.to_pascal_case();
-                        let variant_name =
-                            rename_keyword("", &pascal_case_variant).unwrap_or_else(|| {
-                                let v = syn::Ident::new(&pascal_case_variant, Span::call_site());
-                                quote!(#v)
-                            });
-                        match value {
-                            Value::String(ref s) => Some(quote! {
-                                #[serde(rename = #s)]
-                                #variant_name
-                            }),
-                            Value::Number(ref n) => {
-                                repr_i64 = true;
-                                let num = syn::LitInt::new(&n.to_string(), Span::call_site());
-                                Some(quote! {
-                                    #variant_name = #num
-                                })
-                            }
-                            Value::Null => {
-                                optional = true;
-                                None
-                            }
-                            _ => panic!("Expected string,bool or number for enum got `{}`", value),
-                        }
-                    })
-                    .collect::<Vec<_>>()
+            }
+            let reserved = if self.open_enums {
+                &["Unknown"][..]
             } else {
-                schema
-                    .enum_
-                    .as_ref()
-                    .map_or(&[][..], |v| v)
-                    .iter()
-                    .flat_map(|v| match *v {
-                        Value::String(ref v) => {
-                            let pascal_case_variant = v.to_pascal_case();
-                            let variant_name = rename_keyword("", &pascal_case_variant)
-                                .unwrap_or_else(|| {
-                                    let v =
-                                        syn::Ident::new(&pascal_case_variant, Span::call_site());
-                                    quote!(#v)
-                                });
-                            Some(if pascal_case_variant == *v {
-                                variant_name
-                            } else {
-                                quote! {
-                                    #[serde(rename = #v)]
-                                    #variant_name
-                                }
-                            })
-                        }
-                        Value::Null => {
-                            optional = true;
+                &[]
+            };
+            let variant_names = enum_variant_names(self.naming, values, names, reserved);
+            let mut variants = Vec::new();
+            for (value, variant_name) in values.iter().zip(&variant_names) {
+                let doc = enum_description(schema, value);
+                let ident = str_to_ident(variant_name);
+                match value {
+                    Value::String(ref s) => {
+                        variant_idents.push(ident.clone());
+                        string_variants.push((ident.clone(), s));
+                        // Open enums are serialized with `as_str`
+                        let serde_rename = if ident == s || self.open_enums {
                             None
-                        }
-                        _ => panic!("Expected string for enum got `{}`", v),
-                    })
-                    .collect::<Vec<_>>()
+                        } else {
+                            Some(quote!(#[serde(rename = #s)]))
+                        };
+                        variants.push(quote! {
+                            #doc
+                            #serde_rename
+                            #ident
+                        });
+                    }
+                    Value::Number(ref n) if n.is_i64() => {
+                        repr_i64 = true;
+                        variant_idents.push(ident.clone());
+                        let num = syn::LitInt::new(&n.to_string(), Span::call_site());
+                        variants.push(quote! {
+                            #doc
+                            #ident = #num
+                        });
+                    }
+                    Value::Null => optional = true,
+                    _ => panic!("Expected string or integer for enum got `{}`", value),
+                }
+            }
+            // Deserialize is implemented by hand to compare the values leniently
+            // or to keep unknown values
+            let open = self.open_enums && !repr_i64;
+            let lenient = !repr_i64 && (self.case_insensitive_enums || self.trim_enums || open);
+            if open {
+                variants.push(quote!(Unknown(String)));
+            }
+            let (serde_derives, repr): (&[&str], _) = if repr_i64 {
+                self.uses_serde_repr = true;
+                (
+                    &["Serialize_repr", "Deserialize_repr"],
+                    Some(quote!(#[repr(i64)])),
+                )
+            } else if open {
+                (&[], None)
+            } else if lenient {
+                (&["Serialize"], None)
+            } else {
+                (&["Deserialize", "Serialize"], None)
+            };
+            let enum_name = if optional {
+                syn::Ident::new(&format!("{}_", name), Span::call_site())
+            } else {
+                name.clone()
+            };
+            let copy: &[&str] = if self.copy_enums && !open {
+                &["Copy"]
+            } else {
+                &[]
             };
+            let derive = self.derive_with_members(
+                &enum_name.to_string(),
+                &[&["Clone"], copy, &["PartialEq", "Debug"], serde_derives].concat(),
+            );
+            let unknown_type = if open { vec!["String".into()] } else { vec![] };
+            self.member_types
+                .insert(enum_name.to_string(), unknown_type);
+            self.enum_variants.insert(
+                enum_name.to_string(),
+                values
+                    .iter()
+                    .zip(&variant_names)
+                    .filter(|(value, _)| !value.is_null())
+                    .map(|(value, name)| (value.clone(), str_to_ident(name)))
+                    .collect(),
+            );
             if optional {
-                let enum_name = syn::Ident::new(&format!("{}_", name), Span::call_site());
-                if repr_i64 {
-                    quote! {
-                        pub type #name = Option<#enum_name>;
-                        #[derive(Clone, PartialEq, Debug, Serialize_repr, Deserialize_repr)]
-                        #serde_rename
-                        #[repr(i64)]
-                        pub enum #enum_name {
-                            #(#variants),*
-                        }
-                    }
+                self.member_types
+                    .insert(type_name.clone(), vec![format!("Option<{}>", enum_name)]);
+            }
+            let non_exhaustive = if self.non_exhaustive {
+                Some(quote!(#[non_exhaustive]))
+            } else {
+                None
+            };
+            let serde_rename = serde_rename.filter(|_| !open);
+            let enum_decl = quote! {
+                #derive
+                #non_exhaustive
+                #deprecated
+                #attrs
+                #serde_rename
+                #repr
+                #vis enum #enum_name {
+                    #(#variants),*
+                }
+                impl #enum_name {
+                    /// Every value allowed by the schema.
+                    pub const VARIANTS: &'static [Self] = &[#(#enum_name::#variant_idents),*];
+                }
+            };
+            let enum_decl = if repr_i64 {
+                enum_decl
+            } else {
+                let string_impls = self.expand_string_enum_impls(&enum_name, &string_variants);
+                let deserialize = if lenient {
+                    Some(self.expand_lenient_enum_deserialize(&enum_name, &string_variants))
                 } else {
-                    quote! {
-                        pub type #name = Option<#enum_name>;
-                        #[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
-                        #serde_rename
-                        pub enum #enum_name {
-                            #(#variants),*
-                        }
-                    }
+                    None
+                };
+                quote! {
+                    #enum_decl
+                    #string_impls
+                    #deserialize
                 }
-            } else if repr_i64 {
+            };
+            if optional {
                 quote! {
-                    #[derive(Clone, PartialEq, Debug, Serialize_repr, Deserialize_repr)]
-                    #serde_rename
-                    #[repr(i64)]
-                    pub enum #name {
-                        #(#variants),*
-                    }
+                    #vis type #name = Option<#enum_name>;
+                    #enum_decl
                 }
             } else {
+                let validate_json = match &pointer {
+                    Some(pointer) => self.expand_validate_json(&name, pointer),
+                    None => TokenStream::new(),
+                };
+                let try_from = if self.try_from_value {
+                    let convert = format!("{}convert", self.schemafy_path)
+                        .parse::<TokenStream>()
+                        .unwrap();
+                    self.expand_try_from_value(
+                        &name,
+                        quote! { #convert::check::<Self>(value, path, errors) },
+                    )
+                } else {
+                    TokenStream::new()
+                };
                 quote! {
-                    #[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
-                    #serde_rename
-                    pub enum #name {
-                        #(#variants),*
-                    }
+                    #enum_decl
+                    #validate_json
+                    #try_from
                 }
             }
+        } else if schema.type_ == [SimpleTypes::String]
+            && self.is_string_newtype(schema)
+            && !schema
+                .format
+                .as_ref()
+                .is_some_and(|f| self.formats.contains_key(f))
+        {
+            self.member_types.insert(type_name.clone(), Vec::new());
+            let newtype = self.expand_string_newtype(&type_name, schema);
+            quote! {
+                #attrs
+                #newtype
+            }
+        } else if schema.type_ == [SimpleTypes::Number]
+            && self.is_bounded(schema)
+            && !self.all_numbers_decimal
+            && schema.format.as_deref() != Some("decimal")
+            && !schema
+                .format
+                .as_ref()
+                .is_some_and(|f| self.formats.contains_key(f))
+        {
+            self.member_types
+                .insert(type_name.clone(), vec!["f64".to_string()]);
+            let newtype = self.expand_bounded_newtype(&type_name, schema);
+            quote! {
+                #attrs
+                #newtype
+            }
         } else {
-            let typ = self
-                .expand_type("", true, schema)
-                .typ
-                .parse::<TokenStream>()
-                .unwrap();
+            let typ = self.expand_type("", true, schema).typ;
+            self.member_types
+                .insert(type_name.clone(), vec![typ.clone()]);
+            let typ = self.with_lifetimes(&typ).parse::<TokenStream>().unwrap();
             // Skip self-referential types, e.g. `struct Schema = Schema`
             if name == typ.to_string() {
                 return TokenStream::new();
             }
+            let lifetime = self.lifetime(&type_name);
             return quote! {
-                pub type #name = #typ;
+                #attrs
+                #vis type #name #lifetime = #typ;
             };
         };
         type_decl
     }
 
-    pub fn expand(&mut self, schema: &Schema) -> TokenStream {
+    fn expand_types(&mut self, schema: &Schema) {
         match self.root_name {
             Some(name) => {
+                self.schema_pointer = Some("#".into());
                 let schema = self.expand_schema(name, schema);
                 self.types.push((name.to_string(), schema));
             }
             None => self.expand_definitions(schema),
         }
+    }
+
+    pub fn expand(&mut self, schema: &Schema) -> TokenStream {
+        self.rename_colliding_definitions(schema);
+        // Which traits a type can derive depends on types which may only
+        // be generated after it, so they are found by expanding the schema
+        // once beforehand
+        let mut first = self.clone();
+        first.expand_types(schema);
+        // `OrderedFloat` is `Eq` and `Hash` despite holding an `f64`
+        if self.ordered_float {
+            for typ in first.member_types.values_mut().flatten() {
+                *typ = typ.replace(ORDERED_FLOAT, "ordered_float::OrderedFloat");
+            }
+        }
+        // Floats and `serde_json::Value` are neither `Eq` nor `Hash`, and the
+        // latter is not even `PartialOrd`, unlike `RawJson`, which is compared by its text
+        let raw_json = format!("{}raw::RawJson", self.schemafy_path);
+        // The types of `schemafy_core` deriving them when their parameters do
+        let core_types = ["Nullable", "IntOrString", "OneOrMany", "Either", "Union3"]
+            .iter()
+            .map(|name| format!("{}{}", self.schemafy_path, name))
+            .collect::<Vec<_>>();
+        let core_type = |path: &str| core_types.iter().any(|typ| typ == path);
+        self.eq_types = first.derivable_types(|path| {
+            EQ_HASH_TYPES.contains(&path)
+                || (self.raw_value && path == raw_json)
+                || core_type(path)
+                || path == "ordered_float::OrderedFloat"
+        });
+        if self.derive_ord {
+            self.partial_ord_types = first.derivable_types(|path| {
+                EQ_HASH_TYPES.contains(&path)
+                    || path == "f64"
+                    || core_type(path)
+                    || path == "ordered_float::OrderedFloat"
+            });
+        }
+        if self.arbitrary {
+            let borrowing_types = first.borrowing_types();
+            self.arbitrary_types = first
+                .derivable_types(|path| EQ_HASH_TYPES.contains(&path) || path == "f64")
+                .difference(&borrowing_types)
+                .cloned()
+                .collect();
+        }
+        if self.borrowed_strings {
+            if self.try_from_value || self.embedded_schema.is_some() {
+                panic!(
+                    "`borrowed_strings` can not be combined with `try_from_value` or \
+                     `embed_schema`"
+                );
+            }
+            self.borrowing_types = first.borrowing_types();
+        }
+        // The `default` of a field may be a variant of an enum generated
+        // after its struct
+        self.enum_variants = first.enum_variants;
+        // The key of a map may be generated before the map
+        self.map_key_types = first.map_key_types;
+        // The items of a list map may be generated after it
+        self.struct_fields = first.struct_fields;
+        self.expand_types(schema);
+        if self.uses_deprecated {
+            for (_, tokens) in &mut self.types {
+                *tokens = allow_deprecated(std::mem::take(tokens));
+            }
+        }
+        if self.no_std {
+            for (_, tokens) in &mut self.types {
+                *tokens = no_std_paths(std::mem::take(tokens));
+            }
+        }
 
         let types = self.types.iter().map(|t| &t.1);
 
@@ -787,6 +4409,45 @@ impl<'r> Expander<'r> {
         }
     }
 
+    /// Expands `schema` into a module per top-level definition, which
+    /// also holds the definitions and inline types nested in it, returning
+    /// the name and items of each. The other types of the root schema are
+    /// put in a module named after the root.
+    pub fn expand_modules(&mut self, schema: &Schema) -> Vec<(String, TokenStream)> {
+        self.expand(schema);
+
+        let mut used = BTreeSet::new();
+        let mut module_name = |name: &str| {
+            let name = str_to_ident(&self.naming.type_name(name).to_snake_case()).to_string();
+            let mut unique = name.clone();
+            let mut suffix = 2;
+            while !used.insert(unique.clone()) {
+                unique = format!("{}_{}", name, suffix);
+                suffix += 1;
+            }
+            unique
+        };
+        let mut in_module = vec![false; self.types.len()];
+        let mut modules = Vec::new();
+        for (name, range) in &self.definition_types {
+            in_module[range.clone()].iter_mut().for_each(|b| *b = true);
+            let types = self.types[range.clone()].iter().map(|t| &t.1);
+            modules.push((module_name(name), quote! { #( #types )* }));
+        }
+        let rest = self
+            .types
+            .iter()
+            .zip(&in_module)
+            .filter(|(_, in_module)| !**in_module)
+            .map(|(t, _)| &t.1)
+            .collect::<Vec<_>>();
+        if !rest.is_empty() {
+            let name = module_name(self.root_name.unwrap_or("root"));
+            modules.push((name, quote! { #( #rest )* }));
+        }
+        modules
+    }
+
     pub fn expand_root(&mut self) -> TokenStream {
         self.expand(self.root)
     }
@@ -855,4 +4516,525 @@ mod tests {
         assert!(types.contains("RootKM"));
         assert!(types.contains("RootTV"));
     }
+
+    #[test]
+    fn enum_variant_docs() {
+        let schema = serde_json::from_value(serde_json::json!({
+            "definitions": {
+                "listed": {
+                    "type": "string",
+                    "enum": ["a", "b"],
+                    "x-enumDescriptions": ["The first", "The second"]
+                },
+                "keyed": {
+                    "type": "string",
+                    "enum": ["a", "b"],
+                    "x-enum-varnames": ["First", "Second"],
+                    "meta:enum": { "b": "The second" }
+                }
+            }
+        }))
+        .unwrap();
+        let mut expander = Expander::new(None, "UNUSED", &schema);
+        let tokens = expander.expand(&schema).to_string();
+        assert!(tokens.contains(r#"# [doc = " The first"] # [serde (rename = "a")] A"#));
+        assert!(tokens.contains(r#"# [doc = " The second"] # [serde (rename = "b")] B"#));
+        assert!(tokens.contains(r#"# [doc = " The second"] # [serde (rename = "b")] Second"#));
+    }
+
+    #[test]
+    fn generate_errors() {
+        let generate = |schema: &str| {
+            crate::Generator::builder()
+                .with_input_schema(schema)
+                .build()
+                .try_generate()
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(
+            generate(r##"{"properties": {"a": {"$ref": "#/definitions/b"}}}"##),
+            "`$ref` `#/definitions/b` does not point to a definition of the schema"
+        );
+        assert_eq!(
+            generate(r##"{"definitions": {"a": {"items": {"$ref": "#"}}}}"##),
+            "`$ref` `#` points to the root of the schema, which needs a `root` name"
+        );
+        assert_eq!(
+            generate("{\n  \"type\": }"),
+            "Cannot parse the schema as JSON: expected value at line 2 column 11"
+        );
+        let err = crate::Generator::builder()
+            .with_input_file("/nonexistent/schema.json")
+            .build()
+            .try_generate()
+            .unwrap_err();
+        assert!(matches!(err, crate::Error::Read(..)));
+    }
+
+    #[test]
+    fn generate_source() {
+        let source = crate::generate(
+            r#"{
+                "definitions": {
+                    "point": {
+                        "type": "object",
+                        "properties": { "x": { "type": "integer" } },
+                        "required": ["x"]
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+        assert!(source.starts_with("#![allow(non_snake_case)]"));
+        assert!(source.contains("pub struct Point"));
+        assert!(crate::generate("[").is_err());
+    }
+
+    #[test]
+    fn preprocessor() {
+        let source = crate::Generator::builder()
+            .with_input_schema(
+                r#"{
+                    "definitions": {
+                        "kept": { "type": "object" },
+                        "removed": { "type": "object" }
+                    }
+                }"#,
+            )
+            .with_preprocessor(|schema| {
+                schema.definitions.remove("removed");
+            })
+            .with_preprocessor(|schema| {
+                let kept = schema.definitions.get_mut("kept").unwrap();
+                kept.type_ = vec![crate::SimpleTypes::Integer];
+            })
+            .build()
+            .generate()
+            .to_string();
+        assert_eq!(source, "pub type Kept = i64 ;");
+    }
+
+    #[test]
+    fn naming_strategy() {
+        use inflector::Inflector;
+
+        #[derive(Debug)]
+        struct Snake;
+
+        impl crate::NamingStrategy for Snake {
+            fn type_name(&self, name: &str) -> String {
+                format!("Api{}", name.to_pascal_case())
+            }
+            fn field_name(&self, name: &str) -> String {
+                name.to_snake_case()
+            }
+            fn variant_name(&self, name: &str) -> String {
+                name.to_screaming_snake_case()
+            }
+        }
+
+        let source = crate::Generator::builder()
+            .with_input_schema(
+                r##"{
+                    "definitions": {
+                        "point": {
+                            "type": "object",
+                            "properties": {
+                                "xValue": { "type": "integer" },
+                                "next": { "$ref": "#/definitions/point" },
+                                "kind": { "$ref": "#/definitions/kind" }
+                            }
+                        },
+                        "kind": { "type": "string", "enum": ["inner-point", "outer"] }
+                    }
+                }"##,
+            )
+            .with_naming_strategy(&Snake)
+            .build()
+            .generate()
+            .to_string();
+        assert!(
+            source.contains("pub enum ApiKind { # [serde (rename = \"inner-point\")] INNER_POINT")
+        );
+        assert!(source.contains("pub struct ApiPoint"));
+        assert!(source.contains("pub kind : Option < ApiKind >"));
+        assert!(source.contains("pub next : Option < Box < ApiPoint >>"));
+        assert!(source.contains("# [serde (rename = \"xValue\")] pub x_value"));
+    }
+
+    #[test]
+    fn acronyms() {
+        use crate::naming::{AcronymNaming, NamingStrategy};
+
+        let naming = AcronymNaming::new(&["http", "HTTPS", "ID", "URL"]);
+        assert_eq!(naming.type_name("HTTPServerURL"), "HttpServerUrl");
+        assert_eq!(naming.type_name("HTTPSProxy"), "HttpsProxy");
+        assert_eq!(naming.type_name("HTTPServer_ids"), "HttpServerIds");
+        assert_eq!(naming.type_name("IDENTITY"), "Identity");
+        assert_eq!(naming.type_name("XURL"), "Xurl");
+        assert_eq!(naming.field_name("IDsList"), "idsList");
+        assert_eq!(naming.field_name("userID"), "userId");
+        assert_eq!(naming.field_name("URLPath"), "urlPath");
+        assert_eq!(naming.field_name("user_id"), "user_id");
+        assert_eq!(naming.variant_name("HTTP"), "Http");
+    }
+
+    #[test]
+    fn type_mapper() {
+        let mapper = |schema: &Schema, raw: Option<&Value>| {
+            if schema.format.as_deref() == Some("uuid") {
+                return Some("uuid::Uuid".to_string());
+            }
+            let rust_type = raw?.get("x-rust-type")?.as_str()?;
+            Some(rust_type.to_string())
+        };
+        let source = crate::Generator::builder()
+            .with_input_schema(
+                r#"{
+                    "definitions": {
+                        "order": {
+                            "type": "object",
+                            "properties": {
+                                "ids": { "type": "array", "items": { "type": "string", "format": "uuid" } },
+                                "total": { "type": "string", "x-rust-type": "rust_decimal::Decimal" },
+                                "note": { "type": "string" }
+                            },
+                            "required": ["ids", "total", "note"]
+                        }
+                    }
+                }"#,
+            )
+            .with_type_mapper(&mapper)
+            .build()
+            .generate()
+            .to_string();
+        assert!(source.contains("pub ids : Vec < uuid :: Uuid >"));
+        assert!(source.contains("pub total : rust_decimal :: Decimal"));
+        assert!(source.contains("pub note : String"));
+    }
+
+    #[test]
+    fn eq_hash() {
+        let source = crate::Generator::builder()
+            .with_input_schema(
+                r##"{
+                    "definitions": {
+                        "a": { "type": "object", "properties": { "b": { "$ref": "#/definitions/b" } } },
+                        "b": { "type": "object", "properties": { "c": { "type": "number" } } },
+                        "d": { "type": "object", "properties": { "d": { "$ref": "#/definitions/d" } } }
+                    }
+                }"##,
+            )
+            .build()
+            .generate()
+            .to_string();
+        let derives = |name: &str| {
+            let end = source.find(&format!("pub struct {} ", name)).unwrap();
+            let start = source[..end].rfind("# [derive").unwrap();
+            source[start..end].contains("Eq , Hash")
+        };
+        assert!(!derives("A"));
+        assert!(!derives("B"));
+        assert!(derives("D"));
+    }
+
+    #[test]
+    fn enum_derives() {
+        let generate = |derive_ord| {
+            crate::Generator::builder()
+                .with_input_schema(
+                    r#"{
+                        "definitions": {
+                            "color": { "type": "string", "enum": ["red", "green"] },
+                            "limits": {
+                                "type": "object",
+                                "propertyNames": { "enum": ["cpu", "memory"] },
+                                "additionalProperties": { "type": "integer" }
+                            }
+                        }
+                    }"#,
+                )
+                .with_derive_ord(derive_ord)
+                .build()
+                .generate()
+                .to_string()
+        };
+        let derives = |source: &str, name: &str| {
+            let end = source.find(&format!("pub enum {} ", name)).unwrap();
+            let start = source[..end].rfind("# [derive").unwrap();
+            source[start..end].to_string()
+        };
+        let source = generate(false);
+        assert!(derives(&source, "Color").contains("PartialEq , Eq , Hash , Debug"));
+        assert!(derives(&source, "LimitsKey").contains("Eq , PartialOrd , Ord , Hash"));
+        let source = generate(true);
+        assert!(derives(&source, "Color").contains("Eq , PartialOrd , Ord , Hash"));
+    }
+
+    #[test]
+    fn single_value_structs() {
+        let generate = |single_value_structs, open_enums| {
+            crate::Generator::builder()
+                .with_root_name_str("Version")
+                .with_input_schema(r#"{ "type": "string", "enum": ["2.0"] }"#)
+                .with_single_value_structs(single_value_structs)
+                .with_open_enums(open_enums)
+                .build()
+                .generate()
+                .to_string()
+        };
+        assert!(generate(true, false).contains("pub struct Version ;"));
+        assert!(generate(false, false).contains("pub enum Version"));
+        assert!(generate(true, true).contains("pub enum Version"));
+    }
+
+    #[test]
+    fn copy_enums() {
+        let generate = |copy_enums| {
+            crate::Generator::builder()
+                .with_root_name_str("Color")
+                .with_input_schema(r#"{ "type": "string", "enum": ["red", "green"] }"#)
+                .with_copy_enums(copy_enums)
+                .build()
+                .generate()
+                .to_string()
+        };
+        assert!(generate(true).contains("# [derive (Clone , Copy , PartialEq"));
+        assert!(generate(false).contains("# [derive (Clone , PartialEq"));
+    }
+
+    #[test]
+    fn no_std() {
+        let tokens = crate::Generator::builder()
+            .with_root_name_str("Color")
+            .with_input_schema(
+                r##"{
+                    "type": "object",
+                    "properties": {
+                        "name": { "$ref": "#/definitions/name" },
+                        "rgb": { "type": "object", "additionalProperties": { "type": "integer" } }
+                    },
+                    "definitions": {
+                        "name": { "type": "string", "enum": ["red", "green"] }
+                    }
+                }"##,
+            )
+            .with_no_std(true)
+            .build()
+            .generate()
+            .to_string();
+        assert!(tokens.contains(":: alloc :: collections :: BTreeMap < String , i64 >"));
+        assert!(tokens.contains("impl :: core :: fmt :: Display for"));
+        assert!(!tokens.contains("std"));
+    }
+
+    #[test]
+    fn json_schema() {
+        let tokens = crate::Generator::builder()
+            .with_root_name_str("Event")
+            .with_input_schema(
+                r#"{
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "string" },
+                        "payload": {
+                            "type": "string",
+                            "contentMediaType": "application/json"
+                        }
+                    },
+                    "required": ["id"]
+                }"#,
+            )
+            .with_json_schema(true)
+            .with_decode_json_strings(true)
+            .build()
+            .generate()
+            .to_string();
+        assert!(tokens.contains("Deserialize , Serialize , schemars :: JsonSchema)]"));
+        assert!(tokens.contains("# [schemars (with = \"Option<String>\")] pub payload"));
+    }
+
+    #[test]
+    fn arbitrary() {
+        let tokens = crate::Generator::builder()
+            .with_root_name_str("Shape")
+            .with_input_schema(
+                r##"{
+                    "type": "object",
+                    "properties": {
+                        "kind": { "$ref": "#/definitions/kind" },
+                        "size": { "type": "number" },
+                        "extra": { "$ref": "#/definitions/extra" }
+                    },
+                    "definitions": {
+                        "kind": { "type": "string", "enum": ["circle", "square"] },
+                        "extra": {
+                            "type": "object",
+                            "properties": { "data": {} }
+                        }
+                    }
+                }"##,
+            )
+            .with_arbitrary(true)
+            .build()
+            .generate()
+            .to_string();
+        let arbitrary = "# [cfg_attr (feature = \"arbitrary\" , derive (arbitrary :: Arbitrary))]";
+        assert!(tokens.contains(&format!(
+            "{} # [serde (rename = \"kind\")] pub enum Kind",
+            arbitrary
+        )));
+        // `serde_json::Value` does not implement `Arbitrary`, so neither
+        // `Extra` nor `Shape` can derive it
+        assert_eq!(tokens.matches(arbitrary).count(), 1);
+    }
+
+    #[test]
+    fn ordered_float() {
+        let tokens = crate::Generator::builder()
+            .with_root_name_str("Point")
+            .with_input_schema(
+                r#"{
+                    "type": "object",
+                    "properties": {
+                        "x": { "type": "number", "default": 1.5 },
+                        "y": { "type": "number", "maximum": 10 }
+                    }
+                }"#,
+            )
+            .with_ordered_float(true)
+            .with_validate(true)
+            .build()
+            .generate()
+            .to_string();
+        assert!(tokens.contains("# [derive (Clone , PartialEq , Eq , Hash , Debug"));
+        assert!(tokens.contains("pub y : Option < ordered_float :: OrderedFloat < f64 >"));
+        assert!(tokens.contains("ordered_float :: OrderedFloat (1.5f64)"));
+        assert!(tokens.contains("if value . 0 > 10"));
+    }
+
+    #[test]
+    fn strict() {
+        let generate = |schema: &str| {
+            crate::Generator::builder()
+                .with_input_schema(schema)
+                .with_strict(true)
+                .build()
+                .try_generate()
+                .map_err(|err| err.to_string())
+        };
+        assert_eq!(
+            generate(
+                r#"{
+                    "definitions": {
+                        "a": {
+                            "type": "object",
+                            "properties": {
+                                "b": { "anyOf": [{ "type": "string" }, { "type": "integer" }] },
+                                "c": { "type": "array", "items": { "type": ["string", "integer"] } },
+                                "if": { "type": "string" }
+                            },
+                            "if": { "required": ["b"] }
+                        }
+                    }
+                }"#
+            )
+            .unwrap_err(),
+            "The schema can not be represented exactly:\n\
+             #/definitions/a/if: `if` is ignored\n\
+             #/definitions/a/properties/b: `anyOf` is generated as `serde_json::Value`\n\
+             #/definitions/a/properties/c/items: a `type` with several types is generated as \
+             `serde_json::Value`"
+        );
+        assert!(generate(r#"{"definitions": {"a": {"type": "string"}}}"#).is_ok());
+        assert_eq!(
+            generate(
+                r#"{"definitions": {"FooBar": {"type": "string"}, "fooBar": {"type": "integer"}}}"#
+            )
+            .unwrap_err(),
+            "The schema can not be represented exactly:\n\
+             #/definitions/fooBar: `fooBar` is generated as `FooBar2` as `FooBar` is already taken"
+        );
+        assert_eq!(
+            generate(r#"{"definitions": {"a": {"properties": {"foo-bar": {}, "foo_bar": {}}}}}"#)
+                .unwrap_err(),
+            "The schema can not be represented exactly:\n\
+             #/definitions/a/properties/foo_bar: `foo_bar` is generated as `foo_bar_2` as \
+             `foo_bar` is already taken"
+        );
+        assert_eq!(
+            generate(
+                r#"{"definitions": {"a": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "x-kubernetes-list-type": "map",
+                    "x-kubernetes-list-map-keys": ["name"]
+                }}}"#
+            )
+            .unwrap_err(),
+            "The schema can not be represented exactly:\n\
+             #/definitions/a: `\"x-kubernetes-list-type\": \"map\"` is ignored on a list which \
+             does not hold structs with ordered fields of `x-kubernetes-list-map-keys`"
+        );
+        assert_eq!(
+            generate(
+                r#"{"definitions": {"a": {
+                    "type": "array",
+                    "items": { "type": "object", "properties": { "b": { "type": "number" } } },
+                    "x-kubernetes-list-type": "map",
+                    "x-kubernetes-list-map-keys": ["b"]
+                }}}"#
+            )
+            .unwrap_err(),
+            "The schema can not be represented exactly:\n\
+             #/definitions/a: `\"x-kubernetes-list-type\": \"map\"` is ignored on a list which \
+             does not hold structs with ordered fields of `x-kubernetes-list-map-keys`"
+        );
+    }
+
+    #[test]
+    fn rename_rules() {
+        let rule = |names: &[&str]| {
+            let names = names
+                .iter()
+                .map(|name| name.to_string())
+                .collect::<Vec<_>>();
+            rename_rule(names.iter())
+        };
+        assert_eq!(rule(&["fooBar", "id", "type"]), Some("camelCase"));
+        assert_eq!(rule(&["FooBar", "Id"]), Some("PascalCase"));
+        assert_eq!(rule(&["foo-bar"]), Some("kebab-case"));
+        assert_eq!(rule(&["FOO_BAR", "ID"]), Some("SCREAMING_SNAKE_CASE"));
+        assert_eq!(rule(&["foo_bar", "id"]), None);
+        assert_eq!(rule(&["fooBar", "foo-baz"]), None);
+        assert_eq!(rule(&["HTTPServer"]), None);
+    }
+
+    #[test]
+    fn constraint_docs() {
+        let schema = serde_json::from_value(serde_json::json!({
+            "type": "object",
+            "properties": {
+                "code": {
+                    "description": "The code",
+                    "type": "string",
+                    "pattern": "^[A-Z]+$",
+                    "maxLength": 3
+                },
+                "step": { "type": "number", "minimum": 0, "exclusiveMinimum": true, "multipleOf": 0.5 },
+                "tags": { "type": "array", "items": { "type": "string" }, "uniqueItems": true }
+            }
+        }))
+        .unwrap();
+        let mut expander = Expander::new(Some("Root"), "UNUSED", &schema);
+        let tokens = expander.expand(&schema).to_string();
+        assert!(tokens.contains(
+            r#"# [doc = " The code"] # [doc = " "] # [doc = " Constraints:"] # [doc = " - `pattern`: `^[A-Z]+$`"] # [doc = " - `maxLength`: 3"]"#
+        ));
+        assert!(tokens.contains(
+            r#"# [doc = " Constraints:"] # [doc = " - `minimum`: 0 (exclusive)"] # [doc = " - `multipleOf`: 0.5"]"#
+        ));
+        assert!(tokens.contains(r#"# [doc = " - `uniqueItems`: true"]"#));
+    }
 }
@@ -0,0 +1,60 @@
+//! An extension point choosing the Rust types of schemas before the
+//! built-in rules do.
+
+use std::fmt;
+
+use serde_json::Value;
+
+use crate::Schema;
+
+/// Chooses the Rust type of particular schemas, such as those with a
+/// custom `format`, a `$ref` to a well known definition or a vendor
+/// extension, instead of the type `schemafy` would generate.
+///
+/// Closures taking the same arguments as `map_type` implement it:
+///
+/// ```rust
+/// let mapper = |schema: &schemafy_lib::Schema, raw: Option<&serde_json::Value>| {
+///     match raw.and_then(|raw| raw.get("x-rust-type")) {
+///         Some(rust_type) => rust_type.as_str().map(String::from),
+///         None if schema.format.as_deref() == Some("uuid") => Some("uuid::Uuid".into()),
+///         None => None,
+///     }
+/// };
+/// let generator = schemafy_lib::Generator::builder()
+///     .with_input_schema(r#"{ "type": "string", "x-rust-type": "my::Id" }"#)
+///     .with_root_name_str("Id")
+///     .with_type_mapper(&mapper)
+///     .build();
+/// assert_eq!(generator.generate().to_string(), "pub type Id = my :: Id ;");
+/// ```
+pub trait TypeMapper {
+    /// The Rust type of `schema`, or `None` to generate it as usual.
+    ///
+    /// `raw` is the JSON of the schema, including the keywords `Schema`
+    /// does not keep (such as vendor extensions starting with `x-`), if it
+    /// could be located in the document.
+    fn map_type(&self, schema: &Schema, raw: Option<&Value>) -> Option<String>;
+}
+
+impl<F> TypeMapper for F
+where
+    F: Fn(&Schema, Option<&Value>) -> Option<String>,
+{
+    fn map_type(&self, schema: &Schema, raw: Option<&Value>) -> Option<String> {
+        self(schema, raw)
+    }
+}
+
+impl fmt::Debug for dyn TypeMapper + '_ {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TypeMapper(..)")
+    }
+}
+
+/// Mappers are compared by identity, so that `Generator` can be.
+impl PartialEq for dyn TypeMapper + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::addr_eq(self, other)
+    }
+}
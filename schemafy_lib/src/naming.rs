@@ -0,0 +1,231 @@
+//! How the names in a schema become the names of the generated Rust
+//! types, fields and enum variants.
+
+use std::fmt;
+
+use inflector::Inflector;
+
+use crate::{replace_invalid_identifier_chars, replace_numeric_start, str_to_ident, transliterate};
+
+/// Derives the Rust names of the items generated from a schema.
+///
+/// Each method returns the name for a name of the schema, which may not be
+/// a valid Rust identifier. Fields and variants whose name differs from the
+/// schema's are renamed with `#[serde(rename)]`, and keywords or invalid
+/// characters which remain in a returned name are still replaced.
+///
+/// ```rust
+/// use schemafy_lib::naming::{DefaultNaming, NamingStrategy};
+///
+/// /// Prefixes the fields of the schema with `f_`.
+/// struct Prefixed;
+///
+/// impl NamingStrategy for Prefixed {
+///     fn field_name(&self, name: &str) -> String {
+///         format!("f_{}", DefaultNaming.field_name(name))
+///     }
+/// }
+///
+/// assert_eq!(Prefixed.field_name("type"), "f_type_");
+/// assert_eq!(Prefixed.type_name("my-type"), "MyType");
+/// ```
+pub trait NamingStrategy {
+    /// The name of the type generated from the definition (or other
+    /// schema) `name`.
+    fn type_name(&self, name: &str) -> String {
+        replace_numeric_start(&replace_invalid_identifier_chars(&pascal_case(name)))
+    }
+
+    /// The name of the field generated from the property `name`.
+    fn field_name(&self, name: &str) -> String {
+        str_to_ident(name).to_string()
+    }
+
+    /// The name of the enum variant generated from the string value (or
+    /// `x-enum-varnames` entry) `name`.
+    fn variant_name(&self, name: &str) -> String {
+        pascal_case(name)
+    }
+}
+
+/// `name` in `PascalCase`, with its non-ASCII letters transliterated. The
+/// digits it starts with are kept with the letters following them, so that
+/// `3dModel` becomes `3dModel` rather than `3Dmodel`.
+fn pascal_case(name: &str) -> String {
+    let name = transliterate(name);
+    let rest = name.trim_start_matches(|c: char| c.is_ascii_digit());
+    if rest.len() == name.len() {
+        return name.to_pascal_case();
+    }
+    let rest = rest.trim_start_matches(|c: char| c.is_lowercase());
+    let start = &name[..name.len() - rest.len()];
+    format!("{}{}", start, rest.to_pascal_case())
+}
+
+/// The names used unless another `NamingStrategy` is given: `PascalCase`
+/// types and variants, and fields named as in the schema.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DefaultNaming;
+
+impl NamingStrategy for DefaultNaming {}
+
+impl<N: NamingStrategy + ?Sized> NamingStrategy for &N {
+    fn type_name(&self, name: &str) -> String {
+        (**self).type_name(name)
+    }
+
+    fn field_name(&self, name: &str) -> String {
+        (**self).field_name(name)
+    }
+
+    fn variant_name(&self, name: &str) -> String {
+        (**self).variant_name(name)
+    }
+}
+
+impl fmt::Debug for dyn NamingStrategy + '_ {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("NamingStrategy(..)")
+    }
+}
+
+/// Strategies are compared by identity, so that `Generator` can be.
+impl PartialEq for dyn NamingStrategy + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::addr_eq(self, other)
+    }
+}
+
+/// Acronyms commonly found in schemas, for `AcronymNaming::common`.
+pub const COMMON_ACRONYMS: &[&str] = &[
+    "API", "CPU", "CSS", "DNS", "HTML", "HTTP", "HTTPS", "ID", "IP", "JSON", "JWT", "OS", "SQL",
+    "SSH", "TCP", "TLS", "TTL", "UDP", "UI", "URI", "URL", "UTF8", "UUID", "XML",
+];
+
+/// Names acronyms written in capitals like words, so that `HTTPServerURL`
+/// becomes the type `HttpServerUrl` rather than `HttpserverUrl`, as the
+/// Rust API guidelines recommend.
+///
+/// The acronyms are recased before the names are given to another strategy,
+/// `DefaultNaming` unless `with_inner` is used. Field names starting with
+/// an acronym start in lower case, so `userID` and `URLPath` become
+/// `userId` and `urlPath`.
+///
+/// ```rust
+/// use schemafy_lib::naming::{AcronymNaming, NamingStrategy};
+///
+/// let naming = AcronymNaming::new(&["HTTP", "URL", "ID"]);
+/// assert_eq!(naming.type_name("HTTPServerURL"), "HttpServerUrl");
+/// assert_eq!(naming.field_name("userIDs"), "userIds");
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct AcronymNaming<N = DefaultNaming> {
+    acronyms: Vec<String>,
+    inner: N,
+}
+
+impl AcronymNaming {
+    pub fn new<S: AsRef<str>>(acronyms: &[S]) -> Self {
+        let mut acronyms: Vec<String> = acronyms
+            .iter()
+            .map(|acronym| acronym.as_ref().to_uppercase())
+            .filter(|acronym| !acronym.is_empty())
+            .collect();
+        // The longest acronym is matched first, `HTTPS` before `HTTP`
+        acronyms.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        acronyms.dedup();
+        AcronymNaming {
+            acronyms,
+            inner: DefaultNaming,
+        }
+    }
+
+    /// The `COMMON_ACRONYMS`.
+    pub fn common() -> Self {
+        AcronymNaming::new(COMMON_ACRONYMS)
+    }
+
+    /// Uses `inner` for the names once their acronyms are recased.
+    pub fn with_inner<N: NamingStrategy>(self, inner: N) -> AcronymNaming<N> {
+        AcronymNaming {
+            acronyms: self.acronyms,
+            inner,
+        }
+    }
+}
+
+impl<N> AcronymNaming<N> {
+    /// `name` with each acronym written like a word. An acronym is only
+    /// matched where it is not part of a longer run of capitals (other than
+    /// the first letter of a word or another acronym), and may be followed
+    /// by a plural `s`.
+    pub fn recase(&self, name: &str, lower_first: bool) -> String {
+        let chars: Vec<char> = name.chars().collect();
+        let mut recased = String::with_capacity(name.len());
+        let mut i = 0;
+        while i < chars.len() {
+            let starts_word = i == 0 || !chars[i - 1].is_uppercase();
+            match self.acronym_at(&chars, i).filter(|_| starts_word) {
+                Some(acronym) => {
+                    // The acronyms following this one are matched too
+                    let mut first = !(lower_first && i == 0);
+                    let mut acronym = Some(acronym);
+                    while let Some(matched) = acronym {
+                        for c in matched.chars() {
+                            if first {
+                                recased.push(c);
+                                first = false;
+                            } else {
+                                recased.extend(c.to_lowercase());
+                            }
+                        }
+                        i += matched.chars().count();
+                        first = true;
+                        acronym = self.acronym_at(&chars, i);
+                    }
+                }
+                None => {
+                    recased.push(chars[i]);
+                    i += 1;
+                }
+            }
+        }
+        recased
+    }
+
+    /// The acronym written at `start` of `chars`, if it ends a word.
+    fn acronym_at(&self, chars: &[char], start: usize) -> Option<&str> {
+        self.acronyms.iter().map(String::as_str).find(|acronym| {
+            let end = start + acronym.chars().count();
+            end <= chars.len()
+                && chars[start..end].iter().copied().eq(acronym.chars())
+                && self.ends_word(chars, end)
+        })
+    }
+
+    fn ends_word(&self, chars: &[char], end: usize) -> bool {
+        match chars.get(end) {
+            None => true,
+            Some(&'s') => !matches!(chars.get(end + 1), Some(c) if c.is_lowercase()),
+            Some(c) if c.is_uppercase() => {
+                matches!(chars.get(end + 1), Some(c) if c.is_lowercase())
+                    || self.acronym_at(chars, end).is_some()
+            }
+            Some(c) => !c.is_alphabetic(),
+        }
+    }
+}
+
+impl<N: NamingStrategy> NamingStrategy for AcronymNaming<N> {
+    fn type_name(&self, name: &str) -> String {
+        self.inner.type_name(&self.recase(name, false))
+    }
+
+    fn field_name(&self, name: &str) -> String {
+        self.inner.field_name(&self.recase(name, true))
+    }
+
+    fn variant_name(&self, name: &str) -> String {
+        self.inner.variant_name(&self.recase(name, false))
+    }
+}
@@ -0,0 +1,294 @@
+//! OpenAPI (and Swagger 2.0) documents, whose schemas are turned into the
+//! definitions of a JSON schema so that types can be generated from them
+//! like from any other schema.
+//!
+//! The `discriminator` of a `oneOf` is kept, and the generated enum picks
+//! its variant from the value of the discriminator property rather than by
+//! trying each variant in turn.
+//!
+//! The `openAPIV3Schema`s of Kubernetes `CustomResourceDefinition`s are
+//! read the same way, along with the `x-kubernetes-` extensions they use.
+
+use serde_json::{Map, Value};
+
+const COMPONENTS_POINTER: &str = "#/components/schemas/";
+
+/// The keywords describing a schema without constraining it.
+const ANNOTATIONS: &[&str] = &[
+    "title",
+    "description",
+    "default",
+    "deprecated",
+    "readOnly",
+    "writeOnly",
+];
+
+/// Whether `document` is an OpenAPI 3 or Swagger 2.0 document rather than
+/// a JSON schema.
+pub fn is_openapi(document: &Value) -> bool {
+    let version = |key: &str, major: char| {
+        document
+            .get(key)
+            .and_then(Value::as_str)
+            .is_some_and(|version| version.starts_with(major))
+    };
+    version("openapi", '3') || version("swagger", '2')
+}
+
+/// Whether `document` is a Kubernetes `CustomResourceDefinition`, or an
+/// object holding the `openAPIV3Schema` of one, rather than a JSON schema.
+pub fn is_crd(document: &Value) -> bool {
+    document.get("kind").and_then(Value::as_str) == Some("CustomResourceDefinition")
+        || document.get("openAPIV3Schema").is_some()
+}
+
+/// The JSON schema holding the `openAPIV3Schema` of each version of the
+/// `CustomResourceDefinition` `document` as its `definitions`, named after
+/// the `kind` of the resource, followed by the version if there are
+/// several, such as `CronTabV1beta1`. An object holding just an
+/// `openAPIV3Schema` is turned into that schema.
+///
+/// The schemas are rewritten as described by `to_json_schema`, and their
+/// extensions handled as follows:
+///
+/// * `"x-kubernetes-int-or-string": true` is a `schemafy_core::IntOrString`.
+/// * The members of an object with
+///   `"x-kubernetes-preserve-unknown-fields": true` which are not among its
+///   `properties` are kept in a map of `serde_json::Value`s.
+/// * An array with `"x-kubernetes-list-type": "map"` is a map from the
+///   values of its `x-kubernetes-list-map-keys` to its items, read and
+///   written with `schemafy_core::list_map`.
+///
+/// Only JSON is read, so a CRD written in YAML has to be converted first,
+/// such as with `kubectl get crd <name> -o json`.
+///
+/// ```rust
+/// use serde_json::json;
+///
+/// let document = json!({
+///     "apiVersion": "apiextensions.k8s.io/v1",
+///     "kind": "CustomResourceDefinition",
+///     "spec": {
+///         "names": { "kind": "CronTab" },
+///         "versions": [{
+///             "name": "v1",
+///             "schema": { "openAPIV3Schema": { "type": "object" } }
+///         }]
+///     }
+/// });
+/// assert_eq!(
+///     schemafy_lib::openapi::crd_to_json_schema(&document),
+///     json!({ "definitions": { "CronTab": { "type": "object" } } })
+/// );
+/// ```
+pub fn crd_to_json_schema(document: &Value) -> Value {
+    if let Some(schema) = document.get("openAPIV3Schema") {
+        let mut schema = schema.clone();
+        rewrite_schema(&mut schema);
+        return schema;
+    }
+    let kind = document
+        .pointer("/spec/names/kind")
+        .and_then(Value::as_str)
+        .unwrap_or("Resource");
+    let versions = document
+        .pointer("/spec/versions")
+        .and_then(Value::as_array)
+        .map_or(&[][..], Vec::as_slice);
+    let schemas = versions
+        .iter()
+        .filter_map(|version| {
+            let schema = version.pointer("/schema/openAPIV3Schema")?;
+            let name = version.get("name").and_then(Value::as_str).unwrap_or("");
+            Some((name, schema))
+        })
+        // The schema shared by every version in `apiextensions.k8s.io/v1beta1`
+        .chain(
+            document
+                .pointer("/spec/validation/openAPIV3Schema")
+                .map(|schema| ("", schema)),
+        )
+        .collect::<Vec<_>>();
+    let mut definitions = Map::new();
+    for (version, schema) in &schemas {
+        let mut name = kind.to_string();
+        if schemas.len() > 1 {
+            let mut chars = version.chars();
+            name.extend(chars.next().map(|c| c.to_ascii_uppercase()));
+            name.push_str(chars.as_str());
+        }
+        let mut schema = (*schema).clone();
+        rewrite_schema(&mut schema);
+        definitions.insert(name, schema);
+    }
+    let mut schema = Map::new();
+    schema.insert("definitions".to_string(), Value::Object(definitions));
+    Value::Object(schema)
+}
+
+/// The JSON schema holding the `components.schemas` of the OpenAPI 3.0 or
+/// 3.1 `document`, or the `definitions` of the Swagger 2.0 `document`, as
+/// its `definitions`.
+///
+/// The keywords of OpenAPI which JSON schema lacks, or spells differently
+/// than the draft 4 schemas read by this crate, are rewritten:
+///
+/// * `"nullable": true` (or the `"x-nullable": true` of Swagger 2.0) adds
+///   `null` to the `type` of the schema, or makes
+///   it an `anyOf` with `{ "type": "null" }` if it has no `type`, such as
+///   `{ "allOf": [{ "$ref": .. }], "nullable": true }`.
+/// * The numeric `exclusiveMinimum` and `exclusiveMaximum` of OpenAPI 3.1
+///   become a `minimum` or `maximum` with `"exclusiveMinimum": true` or
+///   `"exclusiveMaximum": true`.
+/// * The `$ref`s to `#/components/schemas/`, including those of the
+///   `mapping` of a `discriminator`, point to `#/definitions/`.
+/// * The `discriminator` of Swagger 2.0, the name of the property, becomes
+///   `{ "propertyName": .. }`.
+///
+/// The `collectionFormat` of Swagger 2.0 arrays is kept, and arrays of
+/// scalars with a `csv`, `ssv`, `tsv` or `pipes` format are read from a
+/// single string of delimited items.
+///
+/// ```rust
+/// use serde_json::json;
+///
+/// let document = json!({
+///     "openapi": "3.0.3",
+///     "components": {
+///         "schemas": {
+///             "Pet": {
+///                 "type": "object",
+///                 "properties": {
+///                     "owner": { "$ref": "#/components/schemas/Owner" },
+///                     "name": { "type": "string", "nullable": true }
+///                 }
+///             },
+///             "Owner": { "type": "string" }
+///         }
+///     }
+/// });
+/// assert_eq!(
+///     schemafy_lib::openapi::to_json_schema(&document),
+///     json!({
+///         "definitions": {
+///             "Pet": {
+///                 "type": "object",
+///                 "properties": {
+///                     "owner": { "$ref": "#/definitions/Owner" },
+///                     "name": { "type": ["string", "null"] }
+///                 }
+///             },
+///             "Owner": { "type": "string" }
+///         }
+///     })
+/// );
+/// ```
+pub fn to_json_schema(document: &Value) -> Value {
+    let mut definitions = document
+        .pointer("/components/schemas")
+        .or_else(|| document.get("definitions"))
+        .and_then(Value::as_object)
+        .cloned()
+        .unwrap_or_default();
+    for schema in definitions.values_mut() {
+        rewrite_schema(schema);
+    }
+    let mut schema = Map::new();
+    schema.insert("definitions".to_string(), Value::Object(definitions));
+    Value::Object(schema)
+}
+
+/// Points `ref_` to the `definitions` rather than to the
+/// `components.schemas` of a document.
+fn rewrite_ref(ref_: &mut String) {
+    if let Some(i) = ref_.find(COMPONENTS_POINTER) {
+        let name = &ref_[i + COMPONENTS_POINTER.len()..];
+        *ref_ = format!("{}#/definitions/{}", &ref_[..i], name);
+    }
+}
+
+/// Rewrites `schema` and its subschemas as described by `to_json_schema`.
+fn rewrite_schema(schema: &mut Value) {
+    let object = match schema {
+        Value::Object(object) => object,
+        Value::Array(schemas) => {
+            schemas.iter_mut().for_each(rewrite_schema);
+            return;
+        }
+        _ => return,
+    };
+    for (keyword, value) in object.iter_mut() {
+        match keyword.as_str() {
+            // Values rather than schemas
+            "enum" | "const" | "default" | "example" | "examples" => (),
+            "$ref" => {
+                if let Value::String(ref_) = value {
+                    rewrite_ref(ref_);
+                }
+            }
+            "discriminator" => {
+                if let Value::String(property_name) = value {
+                    *value = serde_json::json!({ "propertyName": property_name });
+                }
+                let mapping = value.get_mut("mapping").and_then(Value::as_object_mut);
+                for ref_ in mapping.into_iter().flat_map(|mapping| mapping.values_mut()) {
+                    if let Value::String(ref_) = ref_ {
+                        rewrite_ref(ref_);
+                    }
+                }
+            }
+            // Maps of schemas, whose keys may be anything
+            "properties" | "patternProperties" | "definitions" | "$defs" => {
+                if let Value::Object(schemas) = value {
+                    schemas.values_mut().for_each(rewrite_schema);
+                }
+            }
+            _ => rewrite_schema(value),
+        }
+    }
+    for (exclusive, bound) in [
+        ("exclusiveMinimum", "minimum"),
+        ("exclusiveMaximum", "maximum"),
+    ] {
+        if let Some(limit) = object.get(exclusive).filter(|limit| limit.is_number()) {
+            let limit = limit.clone();
+            object.insert(bound.to_string(), limit);
+            object.insert(exclusive.to_string(), Value::Bool(true));
+        }
+    }
+    let nullable = object.remove("nullable") == Some(Value::Bool(true));
+    let x_nullable = object.remove("x-nullable") == Some(Value::Bool(true));
+    if nullable || x_nullable {
+        match object.get_mut("type") {
+            Some(Value::String(type_)) => {
+                let type_ = std::mem::take(type_);
+                object.insert("type".to_string(), serde_json::json!([type_, "null"]));
+            }
+            Some(Value::Array(types)) => {
+                if !types.iter().any(|type_| type_ == "null") {
+                    types.push(Value::from("null"));
+                }
+            }
+            _ => {
+                let mut non_null = std::mem::take(object);
+                // The annotations describe the field rather than the variant
+                for annotation in ANNOTATIONS {
+                    if let Some(value) = non_null.remove(*annotation) {
+                        object.insert(annotation.to_string(), value);
+                    }
+                }
+                // `allOf` is the way to add `nullable` to a `$ref` in 3.0
+                let non_null = match non_null.get("allOf") {
+                    Some(Value::Array(all_of)) if all_of.len() == 1 && non_null.len() == 1 => {
+                        all_of[0].clone()
+                    }
+                    _ => Value::Object(non_null),
+                };
+                object.insert(
+                    "anyOf".to_string(),
+                    serde_json::json!([non_null, { "type": "null" }]),
+                );
+            }
+        }
+    }
+}
@@ -2,6 +2,13 @@ pub type PositiveInteger = i64;
 pub type PositiveIntegerDefault0 = serde_json::Value;
 pub type SchemaArray = Vec<Schema>;
 #[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct SchemaDiscriminator {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mapping: Option<::std::collections::BTreeMap<String, String>>,
+    #[serde(rename = "propertyName")]
+    pub property_name: String,
+}
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
 #[serde(rename = "simpleTypes")]
 pub enum SimpleTypes {
     #[serde(rename = "array")]
@@ -41,14 +48,32 @@ pub struct Schema {
     #[serde(rename = "anyOf")]
     pub any_of: Option<SchemaArray>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "collectionFormat")]
+    pub collection_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "const")]
+    pub const_: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contains: Option<Box<Schema>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "contentMediaType")]
+    pub content_media_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "contentSchema")]
+    pub content_schema: Option<Box<Schema>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub default: Option<serde_json::Value>,
     #[serde(default)]
     pub definitions: ::std::collections::BTreeMap<String, Schema>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub dependencies: Option<::std::collections::BTreeMap<String, serde_json::Value>>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub discriminator: Option<SchemaDiscriminator>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "enum")]
     pub enum_: Option<Vec<serde_json::Value>>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -79,6 +104,9 @@ pub struct Schema {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub maximum: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "meta:enum")]
+    pub meta_enum: Option<::std::collections::BTreeMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "minItems")]
     pub min_items: Option<PositiveIntegerDefault0>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -95,6 +123,8 @@ pub struct Schema {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub not: Option<Box<Schema>>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub nullable: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "oneOf")]
     pub one_of: Option<SchemaArray>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -105,6 +135,12 @@ pub struct Schema {
     #[serde(default)]
     pub properties: ::std::collections::BTreeMap<String, Schema>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "propertyNames")]
+    pub property_names: Option<Box<Schema>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "readOnly")]
+    pub read_only: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub required: Option<StringArray>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
@@ -115,4 +151,43 @@ pub struct Schema {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "uniqueItems")]
     pub unique_items: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "writeOnly")]
+    pub write_only: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "x-deprecated")]
+    pub x_deprecated: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "x-enum-varnames")]
+    pub x_enum_varnames: Option<StringArray>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "x-enumDescriptions")]
+    pub x_enum_descriptions: Option<StringArray>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "x-flatten")]
+    pub x_flatten: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "x-empty-string-as-none")]
+    pub x_empty_string_as_none: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "x-stringified")]
+    pub x_stringified: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "x-kubernetes-int-or-string")]
+    pub x_kubernetes_int_or_string: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "x-kubernetes-preserve-unknown-fields")]
+    pub x_kubernetes_preserve_unknown_fields: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "x-kubernetes-list-type")]
+    pub x_kubernetes_list_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "x-kubernetes-list-map-keys")]
+    pub x_kubernetes_list_map_keys: Option<StringArray>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "x-map-or-list")]
+    pub x_map_or_list: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "x-lenient")]
+    pub x_lenient: Option<bool>,
 }
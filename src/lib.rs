@@ -6,8 +6,11 @@
 //! This is a Rust crate which can take a [json schema (draft
 //! 4)](http://json-schema.org/) and generate Rust types which are
 //! serializable with [serde](https://serde.rs/). No checking such as
-//! `min_value` are done but instead only the structure of the schema
-//! is followed as closely as possible.
+//! `minimum` is done during deserialization but instead only the
+//! structure of the schema is followed as closely as possible (the
+//! `validate` option generates code to check such constraints). The
+//! constraints a field's type does not enforce are listed in its
+//! documentation.
 //!
 //! As a schema could be arbitrarily complex this crate makes no
 //! guarantee that it can generate good types or even any types at all
@@ -49,10 +52,241 @@
 //! }
 //! ```
 
+use std::path::Component;
+
+use quote::ToTokens;
+
 /// Generate Rust types from a JSON schema.
 ///
 /// If the `root` parameter is supplied, then a type will be
-/// generated from the root of the schema.
+/// generated from the root of the schema. Otherwise the root type is named
+/// after the `title` of the schema, if it has one.
+///
+/// The schema may also be an OpenAPI 3.0 or 3.1 document, whose
+/// `components.schemas` are generated like the `definitions` of a schema,
+/// or a Swagger 2.0 document. A Kubernetes `CustomResourceDefinition`
+/// generates a type named after its `kind` from the `openAPIV3Schema` of
+/// each version.
+///
+/// Relative paths are resolved against the directory of the crate
+/// (`CARGO_MANIFEST_DIR`), so they work the same in every member of a
+/// workspace. Paths starting with `./` or `../` are instead relative to
+/// the file invoking the macro, like those of `include_str!`. Environment
+/// variables, such as the `OUT_DIR` of a build script, can be used with
+/// `"${OUT_DIR}/schema.json"` or `concat!(env!("OUT_DIR"), "/schema.json")`.
+///
+/// Several paths, separated by commas, generate the types of each schema
+/// into a module named after its file (`events` for `events.json`).
+/// `$ref`s to the other files, by their file name or `$id`, refer to the
+/// types in their modules, so the schemas may define types of the same name.
+///
+/// Small schemas can be written inline with `schema:` instead of giving
+/// the path of a file:
+///
+/// ```rust
+/// # use serde::{Serialize, Deserialize};
+/// schemafy::schemafy!(
+///     root: Point
+///     schema: r#"{
+///         "type": "object",
+///         "properties": { "x": { "type": "integer" }, "y": { "type": "integer" } },
+///         "required": ["x", "y"]
+///     }"#
+/// );
+/// # fn main() {
+/// # let _ = Point { x: 1, y: 2 };
+/// # }
+/// ```
+///
+/// Other options may be given before the path, each as `name: value`:
+///
+/// * `mod: my_schema` - wrap the generated items in a `my_schema` module
+///   (with the same visibility as the items), which imports the serde
+///   derives they need.
+/// * `visibility: pub(crate)` - the visibility of the generated types and
+///   their fields (`pub` by default, `private` for none).
+/// * `prefix: "Dap"`, `suffix: "V2"` - prepended or appended to the name of
+///   every generated type, so that types from different schemas or schema
+///   versions do not clash.
+/// * `decimal_type: rust_decimal::Decimal` - the type used for
+///   `number` schemas with `"format": "decimal"` (defaults to
+///   `serde_json::Number`).
+/// * `all_numbers_decimal: true` - use the decimal type for every
+///   `number` schema instead of `f64`.
+/// * `formats { "ipv4" => "std::net::Ipv4Addr" }` - use the given
+///   Rust type for schemas with a matching `format`. A `string` schema
+///   whose format is mapped to a number type, such as
+///   `formats { "int64" => "i64" }`, is (de)serialized as a string through
+///   `schemafy_core::stringified`, like numbers with `"x-stringified": true`.
+/// * `format_modules { "unix-time" => "schemafy_core::timestamp::seconds" }` -
+///   (de)serialize the schemas with a matching `format`, whose type is given
+///   by `formats`, with the given module (`#[serde(with = "...")]`), such as
+///   `schemafy_core::timestamp::seconds` or `milliseconds` for Unix timestamps.
+/// * `derives: [PartialOrd]` - extra traits to derive for every generated
+///   type. A derive named like one of the defaults replaces it, so
+///   `derives: [my_serde::Deserialize]` changes the path of the derive.
+///   `Eq` and `Hash` are derived by default for the types which hold no
+///   floats or `serde_json::Value`, even through other generated types.
+/// * `attrs { "Event": ["#[non_exhaustive]"] }` - extra attributes for
+///   the types generated from the named definitions.
+/// * `replace { "Timestamp": "chrono::DateTime<chrono::Utc>" }` - use an
+///   existing Rust type for the named definitions instead of generating
+///   them.
+/// * `rename { "definitions/foo-bar" => "FooBar", "Event.type" => "kind" }` -
+///   names to use instead of the generated ones, for definitions and for
+///   fields (given by the name of their type and their name in the schema).
+/// * `acronyms: ["HTTP", "URL"]` - acronyms written like words in the
+///   generated names, so that `HTTPServerURL` becomes `HttpServerUrl`
+///   rather than `HttpserverUrl`. `acronyms: true` uses a list of common
+///   ones (`ID`, `URL`, `HTTP`, `API`...).
+/// * `decode_json_strings: true` - strings with `"contentMediaType":
+///   "application/json"` are decoded into the type described by their
+///   `contentSchema`.
+/// * `set_type: std::collections::BTreeSet` - arrays with
+///   `"uniqueItems": true` of strings, integers or booleans use the
+///   given set type instead of `Vec`.
+/// * `map_type: indexmap::IndexMap` - the map type used for objects
+///   with `additionalProperties` (defaults to `BTreeMap`).
+/// * `empty_structs: true` - object schemas without any properties
+///   become empty structs instead of maps.
+/// * `deny_unknown_fields: true` - every struct rejects members it does not
+///   declare, unless its schema allows them with a schema in
+///   `additionalProperties` or `patternProperties` (structs with
+///   `"additionalProperties": false` always do).
+/// * `non_exhaustive: true` - the structs whose schema does not set
+///   `"additionalProperties": false`, and the enums, are `#[non_exhaustive]`,
+///   so that other crates do not break when the schema gains members.
+/// * `read_write_only: true` - fields with `"readOnly": true` are not
+///   serialized and those with `"writeOnly": true` are not deserialized, as
+///   for requests and responses of a client of an API.
+/// * `borrowed_strings: true` - strings are generated as `Cow<'a, str>`
+///   borrowing from the deserialized input (with `#[serde(borrow)]`), and the
+///   types which hold them, directly or not, get a lifetime `'a`. Strings in
+///   arrays or maps, and those with escapes, are still owned. Such types can
+///   not be deserialized from a `serde_json::Value`, so this can not be
+///   combined with `try_from_value` or `embed_schema`.
+/// * `derive_ord: true` - structs and untagged enums derive `PartialOrd`, and
+///   `Ord` when they also derive `Eq`, if the types of their members allow
+///   it, so that generated records can be sorted.
+/// * `builders: true` - every struct `Foo` gets a `FooBuilder`, returned by
+///   `Foo::builder()`, with a setter per field and a `build` method which
+///   fails with `schemafy_core::builder::MissingField` if a required field
+///   (without a `default`) was not set.
+/// * `constructors: true` - every struct gets a `new` function taking its
+///   required fields (those without a `default`), which sets the other
+///   fields to `None` or their `default`.
+/// * `no_std: true` - the generated code only uses `core` and `alloc`, for
+///   crates with `#![no_std]` and `extern crate alloc`. `String`, `Vec`,
+///   `Box` and `ToString` have to be imported from `alloc` unless `mod`
+///   is set. `schemafy_core` is then used without its `std` feature, so
+///   `validate` can not be combined with `pattern`.
+/// * `json_schema: true` - every type derives `schemars::JsonSchema`, which
+///   requires `schemars` as a dependency, so that the schema can be emitted
+///   again from the generated types.
+/// * `arbitrary: true` - types derive `arbitrary::Arbitrary` when the
+///   `arbitrary` feature of the crate is enabled and the types of their
+///   members implement it, so that fuzz targets can build values shaped like
+///   the schema. Types holding `serde_json::Value`, borrowed strings or
+///   constrained numbers and strings do not.
+/// * `raw_value: true` - free-form JSON is generated as
+///   `schemafy_core::raw::RawJson`, a `Box<serde_json::value::RawValue>`
+///   compared by its text, instead of `serde_json::Value`, which keeps the
+///   exact bytes of large opaque payloads without parsing them. Requires the
+///   `raw_value` feature of `schemafy_core`.
+/// * `nullable_fields: true` - fields which are not required and whose
+///   schema allows `null` are generated as `schemafy_core::Nullable<T>`,
+///   which tells an absent field (`Absent`) apart from an explicit `null`
+///   (`Null`), such as for `PATCH` requests.
+/// * `ordered_float: true` - numbers are generated as
+///   `ordered_float::OrderedFloat<f64>` instead of `f64`, so that the types
+///   holding them can still derive `Eq` and `Hash`. Requires the
+///   `ordered-float` crate with its `serde` feature.
+/// * `read_write_variants: true` - every struct `Foo` with `readOnly` or
+///   `writeOnly` fields gets a `FooRead` struct without the `writeOnly` fields
+///   (the shape of responses) and a `FooWrite` struct without the `readOnly`
+///   ones (the shape of requests), both implementing `From<Foo>`.
+/// * `one_or_many_arrays: true` - fields accepting one item or an array of
+///   them (`anyOf` of `T` and an array of `T`) are always serialized as an array
+///   instead of writing a single item without it.
+/// * `one_or_many_type: true` - fields accepting one item or an array of them
+///   are generated as `schemafy_core::OneOrMany<T>` instead of `Vec<T>`, which
+///   remembers the form received and writes it back identically.
+/// * `base64_bytes: true` - `string` schemas with `"format": "byte"` are
+///   generated as `Vec<u8>`, (de)serialized as base64 through
+///   `schemafy_core::base64_bytes`.
+/// * `union_types: true` - `anyOf` with two or three variants and `type`
+///   arrays with two or three types are generated as `schemafy_core::Either<A, B>`
+///   or `schemafy_core::Union3<A, B, C>` instead of `serde_json::Value`.
+/// * `dedup_types: true` - inline objects with identical schemas share a single
+///   struct, the other names being type aliases of it.
+/// * `title_names: true` - inline objects and `oneOf` variants with a `title`
+///   are named after it (PascalCased) instead of after their parent type and field,
+///   unless another type already has this name.
+/// * `pointer_names: true` - anonymous types are named after their path from
+///   the definition holding them, such as `ConfigServersItemTls` for
+///   `#/definitions/config/properties/servers/items/properties/tls`, instead of
+///   after their parent type and field, which may be ambiguous.
+/// * `raw_identifiers: true` - fields named after a keyword, such as `type`,
+///   are raw identifiers (`r#type`) rather than having a trailing underscore
+///   (`type_`), so that they are named exactly like the JSON keys.
+/// * `copy_enums: false` - the enums generated from `enum` derive `Copy`
+///   unless this is set to `false`.
+/// * `validate: true` - implement `schemafy_core::validation::Validate`
+///   for every struct, checking the constraints its fields do not enforce
+///   (`minimum`, `maximum`, `multipleOf`, `minLength`, `maxLength`, `pattern`,
+///   `minItems`, `maxItems`, `uniqueItems`, `contains`, ...) and
+///   reporting the JSON pointer of each failing value. Deserializing a
+///   `schemafy_core::validated::ValidatedJson<T>` runs these checks as
+///   part of deserialization.
+/// * `int_enums: true` - integer schemas with an `enum` become
+///   `#[repr(i64)]` enums using `serde_repr`, named by `x-enum-varnames`
+///   (or `Number1`, `NumberMinus1`, ...), instead of `i64`. The
+///   `Serialize_repr` and `Deserialize_repr` derives must be in scope.
+/// * `open_enums: true` - string enums get a final `Unknown(String)`
+///   variant holding any value missing from the schema, which is
+///   serialized back unchanged.
+/// * `single_value_structs: true` - string schemas with a single value in
+///   their `enum` are generated as a unit struct always serializing as the
+///   value (and only deserializing from it), such as for version fields,
+///   rather than as an enum with one variant. Ignored with `open_enums`.
+/// * `case_insensitive_enums: true` - string enums deserialize from
+///   values which differ from the schema's only in (ASCII) case.
+/// * `trim_enums: true` - string enums ignore surrounding whitespace
+///   when deserializing.
+/// * `pattern_types: true` - string schemas with a `pattern` become
+///   newtypes which only deserialize from (or `TryFrom`) matching strings.
+/// * `bounded_numbers: true` - integers with a `minimum` or `maximum`
+///   use `schemafy_core::bounded::Bounded` and such numbers become
+///   newtypes, both failing to deserialize when out of range.
+/// * `length_types: true` - string schemas with a `minLength` or
+///   `maxLength` become newtypes which only deserialize from (or
+///   `TryFrom`) strings of an allowed length.
+/// * `validator_attributes: true` - derive `validator::Validate` for the
+///   generated structs, with `#[validate(length(..), range(..), regex(..))]`
+///   attributes on the constrained fields and `#[validate(nested)]` on the
+///   fields holding other generated structs. As with the derive itself,
+///   `validator::Validate` must be in scope.
+/// * `try_from_value: true` - implement `TryFrom<serde_json::Value>` for
+///   the generated structs and enums. Unlike `serde_json::from_value`, the
+///   error (`schemafy_core::validation::ValidationErrors`) lists every
+///   missing or mismatched field, descending into nested structs and
+///   arrays, along with its JSON pointer.
+/// * `embed_schema: true` - embed the schema and generate a
+///   `validate_json` function for the root and definition types which
+///   checks a `serde_json::Value` against it (with the `jsonschema`
+///   crate, enabled by the `jsonschema` feature of `schemafy_core`)
+///   before deserializing it.
+/// * `rename_all: true` - use snake case field names with a single
+///   `#[serde(rename_all = "camelCase")]` (or `PascalCase`, `kebab-case`,
+///   ...) on structs whose fields all follow that convention.
+/// * `strict: true` - fail, listing the JSON pointers of the offending
+///   schemas, instead of generating `serde_json::Value` for schemas which
+///   can not be represented exactly (such as an `anyOf` of several types)
+///   or ignoring keywords such as `if` and `not`.
+/// * `report: true` - generate a `GENERATION_REPORT: &[&str]` constant
+///   listing everything the generated code does not represent exactly,
+///   such as unchecked constraints, enums generated as plain strings and
+///   ignored keywords, each with the JSON pointer of its schema.
 ///
 /// ```rust
 /// extern crate serde;
@@ -75,36 +309,940 @@
 #[proc_macro]
 pub fn schemafy(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let def = syn::parse_macro_input!(tokens as Def);
-    let root_name = def.root;
-    let input_file = def.input_file.value();
-    schemafy_lib::Generator::builder()
-        .with_root_name(root_name)
-        .with_input_file(&input_file)
-        .build()
-        .generate()
-        .into()
+    if let Some(err) = reject_output(&def) {
+        return err.into();
+    }
+    generate(def).into()
+}
+
+/// Generate Rust types from a JSON schema like
+/// [`schemafy!`](macro.schemafy.html), and also write their formatted
+/// source to the file given by `output:`, so that the generated code can
+/// be committed and reviewed while the macro stays the source of truth.
+///
+/// Takes the same options as `schemafy!`. The schema may be given with
+/// `input:` or, as with `schemafy!`, after the options. Both paths are
+/// resolved like those of `schemafy!`. The file is only written when its
+/// contents change. The other macros never write files, and reject
+/// `output:`:
+///
+/// ```compile_fail
+/// # use serde::{Serialize, Deserialize};
+/// schemafy::schemafy!(
+///     root: Nested
+///     output: "tests/generated/nested.rs"
+///     "tests/nested.json"
+/// );
+/// # fn main() {}
+/// ```
+///
+/// ```rust
+/// # use serde::{Serialize, Deserialize};
+/// schemafy::generate_file!(
+///     root: Nested
+///     input: "tests/nested.json"
+///     output: "tests/generated/nested.rs"
+/// );
+/// # fn main() {}
+/// ```
+#[proc_macro]
+pub fn generate_file(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let def = syn::parse_macro_input!(tokens as Def);
+    if def.output.is_none() {
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "Expected an `output:` path to write the generated code to",
+        )
+        .to_compile_error()
+        .into();
+    }
+    generate(def).into()
+}
+
+/// Generate Rust types from a JSON schema given as a string.
+///
+/// Takes the same options as [`schemafy!`](macro.schemafy.html), followed
+/// by the schema itself instead of a path. The schema may be a string
+/// literal (possibly passed in by another macro), `include_str!("path")`
+/// with a path relative to the invoking file as for the real macro, or a
+/// `concat!` of those.
+///
+/// ```rust
+/// # use serde::{Serialize, Deserialize};
+/// schemafy::schemafy_str!(
+///     root: Nested
+///     include_str!("../tests/nested.json")
+/// );
+/// # fn main() {}
+/// ```
+#[proc_macro]
+pub fn schemafy_str(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let StrDef(def) = syn::parse_macro_input!(tokens as StrDef);
+    if let Some(err) = reject_output(&def) {
+        return err.into();
+    }
+    generate(def).into()
+}
+
+/// Generate Rust types from a JSON schema into a module.
+///
+/// Takes the same options and path as [`schemafy!`](macro.schemafy.html)
+/// and adds the types to the module it is attached to, which also gets
+/// the serde derives they need. Unlike a macro in item position, the
+/// module works with rustfmt, IDE navigation and `#[cfg]`, and may hold
+/// other items (such as `impl`s of the generated types) as well.
+///
+/// ```rust
+/// #[schemafy::schema(root: Nested "tests/nested.json")]
+/// pub mod nested {
+///     impl Defnested {
+///         pub fn append(&self) -> &str {
+///             self.append.as_deref().unwrap_or_default()
+///         }
+///     }
+/// }
+/// # fn main() {
+/// # let _ = nested::Nested::default();
+/// # }
+/// ```
+#[proc_macro_attribute]
+pub fn schema(
+    attr: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let def = syn::parse_macro_input!(attr as Def);
+    if let Some(err) = reject_output(&def) {
+        return err.into();
+    }
+    let ModDef {
+        attrs,
+        vis,
+        ident,
+        inner_attrs,
+        content,
+    } = syn::parse_macro_input!(item as ModDef);
+    let tokens = generate(def);
+    // Imported with a glob so that imports of the same names in `content`
+    // take precedence instead of conflicting
+    quote::quote!(
+        #(#attrs)*
+        #vis mod #ident {
+            #(#inner_attrs)*
+            #[allow(unused_imports)]
+            use self::__schemafy_derives::*;
+            mod __schemafy_derives {
+                pub use serde::{Deserialize, Serialize};
+            }
+            #content
+            #tokens
+        }
+    )
+    .into()
+}
+
+/// The module `#[schema]` is attached to.
+struct ModDef {
+    attrs: Vec<syn::Attribute>,
+    vis: syn::Visibility,
+    ident: syn::Ident,
+    inner_attrs: Vec<syn::Attribute>,
+    content: proc_macro2::TokenStream,
+}
+
+impl syn::parse::Parse for ModDef {
+    fn parse(input: syn::parse::ParseStream<'_>) -> syn::Result<Self> {
+        let attrs = input.call(syn::Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        input.parse::<syn::Token![mod]>()?;
+        let ident = input.parse()?;
+        if input.peek(syn::Token![;]) {
+            return Err(input.error("Expected a module with a body, such as `mod types {}`"));
+        }
+        let content;
+        syn::braced!(content in input);
+        Ok(ModDef {
+            attrs,
+            vis,
+            ident,
+            inner_attrs: content.call(syn::Attribute::parse_inner)?,
+            content: content.parse()?,
+        })
+    }
+}
+
+/// The error of the macros other than `generate_file!` when they are given
+/// an `output:` path, which only `generate_file!` writes to.
+fn reject_output(def: &Def) -> Option<proc_macro2::TokenStream> {
+    def.output.as_ref()?;
+    Some(
+        syn::Error::new(
+            def.span,
+            "`output:` is only supported by `schemafy::generate_file!`",
+        )
+        .to_compile_error(),
+    )
+}
+
+fn generate(def: Def) -> proc_macro2::TokenStream {
+    let input_files = def
+        .input_files
+        .iter()
+        .map(|path| resolve_input_file(path))
+        .collect::<Vec<_>>();
+    let schema = def.schema.as_deref();
+    let mut builder = schemafy_lib::Generator::builder()
+        .with_root_name(def.root)
+        .with_all_numbers_decimal(def.all_numbers_decimal)
+        .with_decode_json_strings(def.decode_json_strings)
+        .with_empty_structs(def.empty_structs)
+        .with_deny_unknown_fields(def.deny_unknown_fields)
+        .with_non_exhaustive(def.non_exhaustive)
+        .with_read_write_only(def.read_write_only)
+        .with_borrowed_strings(def.borrowed_strings)
+        .with_derive_ord(def.derive_ord)
+        .with_builders(def.builders)
+        .with_constructors(def.constructors)
+        .with_no_std(def.no_std)
+        .with_json_schema(def.json_schema)
+        .with_arbitrary(def.arbitrary)
+        .with_raw_value(def.raw_value)
+        .with_nullable_fields(def.nullable_fields)
+        .with_ordered_float(def.ordered_float)
+        .with_read_write_variants(def.read_write_variants)
+        .with_one_or_many_arrays(def.one_or_many_arrays)
+        .with_one_or_many_type(def.one_or_many_type)
+        .with_base64_bytes(def.base64_bytes)
+        .with_union_types(def.union_types)
+        .with_dedup_types(def.dedup_types)
+        .with_title_names(def.title_names)
+        .with_pointer_names(def.pointer_names)
+        .with_raw_identifiers(def.raw_identifiers)
+        .with_copy_enums(def.copy_enums)
+        .with_validate(def.validate)
+        .with_int_enums(def.int_enums)
+        .with_open_enums(def.open_enums)
+        .with_single_value_structs(def.single_value_structs)
+        .with_case_insensitive_enums(def.case_insensitive_enums)
+        .with_trim_enums(def.trim_enums)
+        .with_pattern_types(def.pattern_types)
+        .with_bounded_numbers(def.bounded_numbers)
+        .with_length_types(def.length_types)
+        .with_validator_attributes(def.validator_attributes)
+        .with_try_from_value(def.try_from_value)
+        .with_embed_schema(def.embed_schema)
+        .with_rename_all(def.rename_all)
+        .with_strict(def.strict)
+        .with_report(def.report);
+    if let [input_file] = &input_files[..] {
+        builder = builder.with_input_file(input_file);
+    } else {
+        builder = builder.with_input_files(&input_files);
+    }
+    if let Some(schema) = schema {
+        builder = builder.with_input_schema(schema);
+    }
+    if let Some(set_type) = &def.set_type {
+        builder = builder.with_set_type(set_type);
+    }
+    if let Some(map_type) = &def.map_type {
+        builder = builder.with_map_type(map_type);
+    }
+    if let Some(module) = &def.module {
+        builder = builder.with_module(module);
+    }
+    if let Some(visibility) = &def.visibility {
+        builder = builder.with_visibility(visibility);
+    }
+    if let Some(prefix) = &def.prefix {
+        builder = builder.with_prefix(prefix);
+    }
+    if let Some(suffix) = &def.suffix {
+        builder = builder.with_suffix(suffix);
+    }
+    if let Some(decimal_type) = &def.decimal_type {
+        builder = builder.with_decimal_type(decimal_type);
+    }
+    for (format, rust_type) in &def.formats {
+        builder = builder.with_format_type(format, rust_type);
+    }
+    for (format, module) in &def.format_modules {
+        builder = builder.with_format_module(format, module);
+    }
+    for derive in &def.derives {
+        builder = builder.with_derive(derive);
+    }
+    for (type_name, rust_type) in &def.replace {
+        builder = builder.with_replacement(type_name, rust_type);
+    }
+    for (from, to) in &def.rename {
+        builder = builder.with_rename(from, to);
+    }
+    if def.common_acronyms {
+        builder = builder.with_common_acronyms();
+    }
+    for acronym in &def.acronyms {
+        builder = builder.with_acronym(acronym);
+    }
+    for (type_name, attributes) in &def.attrs {
+        for attribute in attributes {
+            builder = builder.with_type_attribute(type_name, attribute);
+        }
+    }
+    let generator = builder.build();
+    let tokens = match generator.try_generate() {
+        Ok(tokens) => tokens,
+        Err(err) => return syn::Error::new(def.span, err).to_compile_error(),
+    };
+    if let Some(output) = &def.output {
+        if let Err(err) = write_output(&generator, &input_files, &resolve_input_file(output)) {
+            return syn::Error::new(def.span, err).to_compile_error();
+        }
+    }
+    // Including the schema makes the compiler rebuild the crate when the
+    // schema file changes
+    let tracked = input_files.iter().chain(&def.included_files).map(|file| {
+        let file = file.to_string_lossy();
+        quote::quote! { const _: &str = include_str!(#file); }
+    });
+    quote::quote!(#(#tracked)* #tokens)
+}
+
+/// Writes the formatted source of the generated code to `output`, unless
+/// the file is already up to date.
+fn write_output(
+    generator: &schemafy_lib::Generator<'_, '_>,
+    input_files: &[std::path::PathBuf],
+    output: &std::path::Path,
+) -> Result<(), schemafy_lib::Error> {
+    let source = generator.try_generate_source()?;
+    let inputs = input_files
+        .iter()
+        .map(|file| {
+            format!(
+                "`{}`",
+                file.file_name().unwrap_or_default().to_string_lossy()
+            )
+        })
+        .collect::<Vec<_>>();
+    let source = if inputs.is_empty() {
+        format!(
+            "// Generated by `schemafy::generate_file!`, do not edit.\n{}",
+            source
+        )
+    } else {
+        format!(
+            "// Generated by `schemafy::generate_file!` from {}, do not edit.\n{}",
+            inputs.join(", "),
+            source
+        )
+    };
+    if std::fs::read_to_string(output).ok().as_deref() == Some(&source[..]) {
+        return Ok(());
+    }
+    std::fs::write(output, source)
+        .map_err(|err| schemafy_lib::Error::Write(output.to_path_buf(), err))
+}
+
+/// Makes `path` relative to the directory of the file invoking the macro
+/// if it starts with `./` or `../`, and to the crate root otherwise.
+fn resolve_input_file(path: &str) -> std::path::PathBuf {
+    let path = std::path::Path::new(path);
+    let file_relative = path
+        .components()
+        .next()
+        .is_some_and(|c| matches!(c, Component::CurDir | Component::ParentDir));
+    match invoking_dir() {
+        Some(dir) if file_relative => dir.join(path),
+        _ => crate_relative(path),
+    }
+}
+
+/// Makes `path` relative to the directory of the file invoking the macro,
+/// like `include_str!` does, or to the crate root if that file is unknown.
+fn resolve_included_file(path: &str) -> std::path::PathBuf {
+    let path = std::path::Path::new(path);
+    match invoking_dir() {
+        Some(dir) => dir.join(path),
+        None => crate_relative(path),
+    }
+}
+
+/// The directory of the file invoking the macro, if it is a file on disk.
+fn invoking_dir() -> Option<std::path::PathBuf> {
+    let file = proc_macro::Span::call_site().local_file()?;
+    Some(
+        std::env::current_dir()
+            .ok()?
+            .join(file)
+            .parent()?
+            .to_path_buf(),
+    )
+}
+
+fn crate_relative(path: &std::path::Path) -> std::path::PathBuf {
+    match std::env::var_os("CARGO_MANIFEST_DIR") {
+        Some(crate_root) => std::path::Path::new(&crate_root).join(path),
+        None => path.to_path_buf(),
+    }
 }
 
 struct Def {
     root: Option<String>,
-    input_file: syn::LitStr,
+    module: Option<String>,
+    visibility: Option<String>,
+    prefix: Option<String>,
+    suffix: Option<String>,
+    decimal_type: Option<String>,
+    all_numbers_decimal: bool,
+    formats: Vec<(String, String)>,
+    format_modules: Vec<(String, String)>,
+    derives: Vec<String>,
+    attrs: Vec<(String, Vec<String>)>,
+    replace: Vec<(String, String)>,
+    rename: Vec<(String, String)>,
+    acronyms: Vec<String>,
+    common_acronyms: bool,
+    decode_json_strings: bool,
+    set_type: Option<String>,
+    map_type: Option<String>,
+    empty_structs: bool,
+    deny_unknown_fields: bool,
+    non_exhaustive: bool,
+    read_write_only: bool,
+    borrowed_strings: bool,
+    derive_ord: bool,
+    builders: bool,
+    constructors: bool,
+    no_std: bool,
+    json_schema: bool,
+    arbitrary: bool,
+    raw_value: bool,
+    nullable_fields: bool,
+    ordered_float: bool,
+    read_write_variants: bool,
+    one_or_many_arrays: bool,
+    one_or_many_type: bool,
+    base64_bytes: bool,
+    union_types: bool,
+    dedup_types: bool,
+    title_names: bool,
+    pointer_names: bool,
+    raw_identifiers: bool,
+    copy_enums: bool,
+    validate: bool,
+    int_enums: bool,
+    open_enums: bool,
+    single_value_structs: bool,
+    case_insensitive_enums: bool,
+    trim_enums: bool,
+    pattern_types: bool,
+    bounded_numbers: bool,
+    length_types: bool,
+    validator_attributes: bool,
+    try_from_value: bool,
+    embed_schema: bool,
+    rename_all: bool,
+    strict: bool,
+    report: bool,
+    schema: Option<String>,
+    input_files: Vec<String>,
+    /// The files read through `include_str!`, which the compiler is told
+    /// about so that changing them regenerates the types.
+    included_files: Vec<std::path::PathBuf>,
+    /// The file `generate_file!` writes the generated code to.
+    output: Option<String>,
+    /// The schema or its path, which errors point to.
+    span: proc_macro2::Span,
 }
 
 impl syn::parse::Parse for Def {
     fn parse(input: syn::parse::ParseStream<'_>) -> syn::Result<Self> {
-        let root = if input.peek(syn::Ident) {
-            let root_ident: syn::Ident = input.parse()?;
-            if root_ident != "root" {
-                return Err(syn::Error::new(root_ident.span(), "Expected `root`"));
+        Def::parse_with(input, false)
+    }
+}
+
+/// The input of `schemafy_str!`, which ends with the schema itself.
+struct StrDef(Def);
+
+impl syn::parse::Parse for StrDef {
+    fn parse(input: syn::parse::ParseStream<'_>) -> syn::Result<Self> {
+        Def::parse_with(input, true).map(StrDef)
+    }
+}
+
+impl Def {
+    fn parse_with(input: syn::parse::ParseStream<'_>, schema_text: bool) -> syn::Result<Self> {
+        let mut root = None;
+        let mut module = None;
+        let mut visibility = None;
+        let mut prefix = None;
+        let mut suffix = None;
+        let mut decimal_type = None;
+        let mut all_numbers_decimal = false;
+        let mut formats = Vec::new();
+        let mut format_modules = Vec::new();
+        let mut derives = Vec::new();
+        let mut attrs = Vec::new();
+        let mut replace = Vec::new();
+        let mut rename = Vec::new();
+        let mut acronyms = Vec::new();
+        let mut common_acronyms = false;
+        let mut decode_json_strings = false;
+        let mut set_type = None;
+        let mut map_type = None;
+        let mut empty_structs = false;
+        let mut deny_unknown_fields = false;
+        let mut non_exhaustive = false;
+        let mut read_write_only = false;
+        let mut borrowed_strings = false;
+        let mut derive_ord = false;
+        let mut builders = false;
+        let mut constructors = false;
+        let mut no_std = false;
+        let mut json_schema = false;
+        let mut arbitrary = false;
+        let mut raw_value = false;
+        let mut nullable_fields = false;
+        let mut ordered_float = false;
+        let mut read_write_variants = false;
+        let mut one_or_many_arrays = false;
+        let mut one_or_many_type = false;
+        let mut base64_bytes = false;
+        let mut union_types = false;
+        let mut dedup_types = false;
+        let mut title_names = false;
+        let mut pointer_names = false;
+        let mut raw_identifiers = false;
+        let mut copy_enums = true;
+        let mut validate = false;
+        let mut int_enums = false;
+        let mut open_enums = false;
+        let mut single_value_structs = false;
+        let mut case_insensitive_enums = false;
+        let mut trim_enums = false;
+        let mut pattern_types = false;
+        let mut bounded_numbers = false;
+        let mut length_types = false;
+        let mut validator_attributes = false;
+        let mut try_from_value = false;
+        let mut embed_schema = false;
+        let mut rename_all = false;
+        let mut strict = false;
+        let mut report = false;
+        let mut schema = None;
+        let mut input_file = None;
+        let mut output = None;
+        let mut included_files = Vec::new();
+        let mut schema_span = proc_macro2::Span::call_site();
+        while (input.peek(syn::Ident) && !input.peek2(syn::Token![!]))
+            || input.peek(syn::Token![mod])
+        {
+            let option = if input.peek(syn::Token![mod]) {
+                let token = input.parse::<syn::Token![mod]>()?;
+                syn::Ident::new("mod", token.span)
+            } else {
+                input.parse::<syn::Ident>()?
+            };
+            if input.peek(syn::Token![:]) {
+                input.parse::<syn::Token![:]>()?;
             }
-            input.parse::<syn::Token![:]>()?;
-            Some(input.parse::<syn::Ident>()?.to_string())
+            if option == "mod" {
+                module = Some(input.parse::<syn::Ident>()?.to_string());
+            } else if option == "schema" {
+                let literal = input.parse::<syn::LitStr>()?;
+                schema_span = literal.span();
+                schema = Some(literal.value());
+            } else if option == "input" {
+                let path = input.parse::<PathExpr>()?;
+                included_files.extend(path.1);
+                input_file = Some(path.0);
+            } else if option == "output" {
+                let path = input.parse::<PathExpr>()?;
+                included_files.extend(path.1);
+                output = Some(path.0);
+            } else if option == "root" {
+                root = Some(input.parse::<syn::Ident>()?.to_string());
+            } else if option == "visibility" {
+                visibility = Some(if input.peek(syn::Ident) {
+                    let private = input.parse::<syn::Ident>()?;
+                    if private != "private" {
+                        return Err(syn::Error::new(
+                            private.span(),
+                            "Expected a visibility such as `pub(crate)` or `private`",
+                        ));
+                    }
+                    String::new()
+                } else {
+                    input
+                        .parse::<syn::Visibility>()?
+                        .to_token_stream()
+                        .to_string()
+                });
+            } else if option == "prefix" {
+                prefix = Some(input.parse::<syn::LitStr>()?.value());
+            } else if option == "suffix" {
+                suffix = Some(input.parse::<syn::LitStr>()?.value());
+            } else if option == "decimal_type" {
+                let typ: syn::Type = input.parse()?;
+                decimal_type = Some(typ.to_token_stream().to_string());
+            } else if option == "all_numbers_decimal" {
+                all_numbers_decimal = input.parse::<syn::LitBool>()?.value;
+            } else if option == "formats" {
+                formats.extend(parse_string_map(input)?);
+            } else if option == "format_modules" {
+                format_modules.extend(parse_string_map(input)?);
+            } else if option == "derives" {
+                let content;
+                syn::bracketed!(content in input);
+                let paths = content
+                    .parse_terminated::<syn::Path, syn::Token![,]>(syn::Path::parse_mod_style)?;
+                derives.extend(paths.iter().map(|path| path.to_token_stream().to_string()));
+            } else if option == "attrs" {
+                attrs.extend(parse_attribute_map(input)?);
+            } else if option == "replace" {
+                replace.extend(parse_string_map(input)?);
+            } else if option == "rename" {
+                rename.extend(parse_string_map(input)?);
+            } else if option == "acronyms" {
+                if input.peek(syn::LitBool) {
+                    common_acronyms = input.parse::<syn::LitBool>()?.value;
+                } else {
+                    let content;
+                    syn::bracketed!(content in input);
+                    let names = content.parse_terminated::<syn::LitStr, syn::Token![,]>(
+                        <syn::LitStr as syn::parse::Parse>::parse,
+                    )?;
+                    acronyms.extend(names.iter().map(syn::LitStr::value));
+                }
+            } else if option == "decode_json_strings" {
+                decode_json_strings = input.parse::<syn::LitBool>()?.value;
+            } else if option == "set_type" {
+                let typ: syn::Type = input.parse()?;
+                set_type = Some(typ.to_token_stream().to_string());
+            } else if option == "map_type" {
+                let typ: syn::Type = input.parse()?;
+                map_type = Some(typ.to_token_stream().to_string());
+            } else if option == "empty_structs" {
+                empty_structs = input.parse::<syn::LitBool>()?.value;
+            } else if option == "deny_unknown_fields" {
+                deny_unknown_fields = input.parse::<syn::LitBool>()?.value;
+            } else if option == "non_exhaustive" {
+                non_exhaustive = input.parse::<syn::LitBool>()?.value;
+            } else if option == "read_write_only" {
+                read_write_only = input.parse::<syn::LitBool>()?.value;
+            } else if option == "borrowed_strings" {
+                borrowed_strings = input.parse::<syn::LitBool>()?.value;
+            } else if option == "derive_ord" {
+                derive_ord = input.parse::<syn::LitBool>()?.value;
+            } else if option == "builders" {
+                builders = input.parse::<syn::LitBool>()?.value;
+            } else if option == "constructors" {
+                constructors = input.parse::<syn::LitBool>()?.value;
+            } else if option == "no_std" {
+                no_std = input.parse::<syn::LitBool>()?.value;
+            } else if option == "json_schema" {
+                json_schema = input.parse::<syn::LitBool>()?.value;
+            } else if option == "arbitrary" {
+                arbitrary = input.parse::<syn::LitBool>()?.value;
+            } else if option == "raw_value" {
+                raw_value = input.parse::<syn::LitBool>()?.value;
+            } else if option == "nullable_fields" {
+                nullable_fields = input.parse::<syn::LitBool>()?.value;
+            } else if option == "ordered_float" {
+                ordered_float = input.parse::<syn::LitBool>()?.value;
+            } else if option == "read_write_variants" {
+                read_write_variants = input.parse::<syn::LitBool>()?.value;
+            } else if option == "one_or_many_arrays" {
+                one_or_many_arrays = input.parse::<syn::LitBool>()?.value;
+            } else if option == "one_or_many_type" {
+                one_or_many_type = input.parse::<syn::LitBool>()?.value;
+            } else if option == "base64_bytes" {
+                base64_bytes = input.parse::<syn::LitBool>()?.value;
+            } else if option == "union_types" {
+                union_types = input.parse::<syn::LitBool>()?.value;
+            } else if option == "dedup_types" {
+                dedup_types = input.parse::<syn::LitBool>()?.value;
+            } else if option == "title_names" {
+                title_names = input.parse::<syn::LitBool>()?.value;
+            } else if option == "pointer_names" {
+                pointer_names = input.parse::<syn::LitBool>()?.value;
+            } else if option == "raw_identifiers" {
+                raw_identifiers = input.parse::<syn::LitBool>()?.value;
+            } else if option == "copy_enums" {
+                copy_enums = input.parse::<syn::LitBool>()?.value;
+            } else if option == "validate" {
+                validate = input.parse::<syn::LitBool>()?.value;
+            } else if option == "int_enums" {
+                int_enums = input.parse::<syn::LitBool>()?.value;
+            } else if option == "open_enums" {
+                open_enums = input.parse::<syn::LitBool>()?.value;
+            } else if option == "single_value_structs" {
+                single_value_structs = input.parse::<syn::LitBool>()?.value;
+            } else if option == "case_insensitive_enums" {
+                case_insensitive_enums = input.parse::<syn::LitBool>()?.value;
+            } else if option == "trim_enums" {
+                trim_enums = input.parse::<syn::LitBool>()?.value;
+            } else if option == "pattern_types" {
+                pattern_types = input.parse::<syn::LitBool>()?.value;
+            } else if option == "bounded_numbers" {
+                bounded_numbers = input.parse::<syn::LitBool>()?.value;
+            } else if option == "length_types" {
+                length_types = input.parse::<syn::LitBool>()?.value;
+            } else if option == "validator_attributes" {
+                validator_attributes = input.parse::<syn::LitBool>()?.value;
+            } else if option == "try_from_value" {
+                try_from_value = input.parse::<syn::LitBool>()?.value;
+            } else if option == "embed_schema" {
+                embed_schema = input.parse::<syn::LitBool>()?.value;
+            } else if option == "rename_all" {
+                rename_all = input.parse::<syn::LitBool>()?.value;
+            } else if option == "strict" {
+                strict = input.parse::<syn::LitBool>()?.value;
+            } else if option == "report" {
+                report = input.parse::<syn::LitBool>()?.value;
+            } else {
+                return Err(syn::Error::new(
+                    option.span(),
+                    format!("Unknown option `{}`", option),
+                ));
+            }
+            if input.peek(syn::Token![,]) {
+                input.parse::<syn::Token![,]>()?;
+            }
+        }
+        if schema.is_none() && input_file.is_none() && !schema_text && input.is_empty() {
+            return Err(input.error("Expected the path of a schema"));
+        }
+        let span = if schema.is_some() && !schema_text {
+            schema_span
+        } else {
+            input.span()
+        };
+        let input_files = if let Some(input_file) = input_file {
+            vec![input_file]
+        } else if schema.is_some() || schema_text {
+            Vec::new()
         } else {
-            None
+            input
+                .parse_terminated::<PathExpr, syn::Token![,]>(
+                    <PathExpr as syn::parse::Parse>::parse,
+                )?
+                .into_iter()
+                .map(|path| {
+                    included_files.extend(path.1);
+                    path.0
+                })
+                .collect()
         };
+        if schema_text {
+            let text = input.parse::<StrExpr>()?;
+            included_files.extend(text.1);
+            schema = Some(text.0);
+        }
         Ok(Def {
             root,
-            input_file: input.parse()?,
+            module,
+            visibility,
+            prefix,
+            suffix,
+            decimal_type,
+            all_numbers_decimal,
+            formats,
+            format_modules,
+            derives,
+            attrs,
+            replace,
+            rename,
+            acronyms,
+            common_acronyms,
+            decode_json_strings,
+            set_type,
+            map_type,
+            empty_structs,
+            deny_unknown_fields,
+            non_exhaustive,
+            read_write_only,
+            borrowed_strings,
+            derive_ord,
+            builders,
+            constructors,
+            no_std,
+            json_schema,
+            arbitrary,
+            raw_value,
+            nullable_fields,
+            ordered_float,
+            read_write_variants,
+            one_or_many_arrays,
+            one_or_many_type,
+            base64_bytes,
+            union_types,
+            dedup_types,
+            title_names,
+            pointer_names,
+            raw_identifiers,
+            copy_enums,
+            validate,
+            int_enums,
+            open_enums,
+            single_value_structs,
+            case_insensitive_enums,
+            trim_enums,
+            pattern_types,
+            bounded_numbers,
+            length_types,
+            validator_attributes,
+            try_from_value,
+            embed_schema,
+            rename_all,
+            strict,
+            report,
+            span,
+            output,
+            input_files,
+            included_files,
+            schema,
         })
     }
 }
+
+/// The path of the schema file: a string literal, in which `${VAR}` is
+/// replaced by the environment variable `VAR`, or a `concat!` of string
+/// literals and `env!("VAR")`.
+struct PathExpr(String, Vec<std::path::PathBuf>);
+
+impl syn::parse::Parse for PathExpr {
+    fn parse(input: syn::parse::ParseStream<'_>) -> syn::Result<Self> {
+        if input.peek(syn::LitStr) {
+            let path = input.parse::<syn::LitStr>()?;
+            return expand_env_vars(&path.value())
+                .map(|path| PathExpr(path, Vec::new()))
+                .map_err(|err| syn::Error::new(path.span(), err));
+        }
+        input
+            .parse::<StrExpr>()
+            .map(|path| PathExpr(path.0, path.1))
+    }
+}
+
+/// Replaces every `${VAR}` in `s` by the value of the environment variable
+/// `VAR`.
+fn expand_env_vars(mut s: &str) -> Result<String, String> {
+    let mut result = String::new();
+    while let Some(start) = s.find("${") {
+        result.push_str(&s[..start]);
+        let rest = &s[start + 2..];
+        let end = rest
+            .find('}')
+            .ok_or_else(|| format!("Missing `}}` after `${{{}`", rest))?;
+        let name = &rest[..end];
+        let value = std::env::var(name)
+            .map_err(|_| format!("Environment variable `{}` is not set", name))?;
+        result.push_str(&value);
+        s = &rest[end + 1..];
+    }
+    result.push_str(s);
+    Ok(result)
+}
+
+/// A string literal, `include_str!("path")`, `env!("VAR")` or `concat!(..)`
+/// of those, with the files it includes.
+struct StrExpr(String, Vec<std::path::PathBuf>);
+
+impl syn::parse::Parse for StrExpr {
+    fn parse(input: syn::parse::ParseStream<'_>) -> syn::Result<Self> {
+        if input.peek(syn::LitStr) {
+            return Ok(StrExpr(input.parse::<syn::LitStr>()?.value(), Vec::new()));
+        }
+        let mac: syn::Macro = input.parse()?;
+        if mac.path.is_ident("include_str") {
+            let path: syn::LitStr = mac.parse_body()?;
+            let file = resolve_included_file(&path.value());
+            match std::fs::read_to_string(&file) {
+                Ok(text) => Ok(StrExpr(text, vec![file])),
+                Err(err) => Err(syn::Error::new(
+                    path.span(),
+                    format!("Unable to read `{}`: {}", file.to_string_lossy(), err),
+                )),
+            }
+        } else if mac.path.is_ident("env") {
+            let name: syn::LitStr = mac.parse_body()?;
+            std::env::var(name.value())
+                .map(|value| StrExpr(value, Vec::new()))
+                .map_err(|_| {
+                    syn::Error::new(
+                        name.span(),
+                        format!("Environment variable `{}` is not set", name.value()),
+                    )
+                })
+        } else if mac.path.is_ident("concat") {
+            let parts = mac.parse_body_with(
+                syn::punctuated::Punctuated::<StrExpr, syn::Token![,]>::parse_terminated,
+            )?;
+            let mut text = String::new();
+            let mut included_files = Vec::new();
+            for part in parts {
+                text.push_str(&part.0);
+                included_files.extend(part.1);
+            }
+            Ok(StrExpr(text, included_files))
+        } else {
+            Err(syn::Error::new_spanned(
+                &mac.path,
+                "Expected a string literal, `include_str!`, `env!` or `concat!`",
+            ))
+        }
+    }
+}
+
+/// Parses `{ "Type": ["#[attribute]", ...], ... }`, checking that each
+/// attribute is valid.
+fn parse_attribute_map(
+    input: syn::parse::ParseStream<'_>,
+) -> syn::Result<Vec<(String, Vec<String>)>> {
+    let content;
+    syn::braced!(content in input);
+    let mut entries = Vec::new();
+    while !content.is_empty() {
+        let key: syn::LitStr = content.parse()?;
+        content.parse::<syn::Token![:]>()?;
+        let list;
+        syn::bracketed!(list in content);
+        let attributes = list.parse_terminated::<syn::LitStr, syn::Token![,]>(
+            <syn::LitStr as syn::parse::Parse>::parse,
+        )?;
+        for attribute in &attributes {
+            attribute.parse_with(syn::Attribute::parse_outer)?;
+        }
+        entries.push((
+            key.value(),
+            attributes.iter().map(syn::LitStr::value).collect(),
+        ));
+        if content.peek(syn::Token![,]) {
+            content.parse::<syn::Token![,]>()?;
+        }
+    }
+    Ok(entries)
+}
+
+/// Parses `{ "key" => "value", ... }`, or the same with `:` separating the
+/// keys and values.
+fn parse_string_map(input: syn::parse::ParseStream<'_>) -> syn::Result<Vec<(String, String)>> {
+    let content;
+    syn::braced!(content in input);
+    let mut entries = Vec::new();
+    while !content.is_empty() {
+        let key: syn::LitStr = content.parse()?;
+        if content.peek(syn::Token![=>]) {
+            content.parse::<syn::Token![=>]>()?;
+        } else {
+            content.parse::<syn::Token![:]>()?;
+        }
+        let value: syn::LitStr = content.parse()?;
+        entries.push((key.value(), value.value()));
+        if content.peek(syn::Token![,]) {
+            content.parse::<syn::Token![,]>()?;
+        }
+    }
+    Ok(entries)
+}
@@ -1,73 +1,595 @@
-use std::io::copy;
-use std::path::Path;
-use std::process::{Command, Stdio};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 
-use anyhow::{anyhow, bail, Context, Result};
-use schemafy_lib::Generator;
+use anyhow::{anyhow, Context, Result};
+use schemafy_lib::{Generator, GeneratorBuilder};
 use structopt::StructOpt;
 use tempfile::NamedTempFile;
 
-/// Generate Rust structs from a JSON schema
+/// Generate Rust structs from JSON schemas
 #[derive(Debug, StructOpt)]
-struct Opts {
-    /// Name for the root structure
-    #[structopt(short, long, value_name = "NAME", default_value = "Schema")]
-    root: String,
-    /// Output file [default: stdout]
+#[structopt(name = "schemafy")]
+enum Opts {
+    /// Generate the Rust types of JSON schemas, taking the options of the
+    /// `schemafy!` macro
+    Gen(GenOpts),
+    /// Generate the types of every JSON schema in a directory into a
+    /// module each, and again whenever a schema changes
+    Watch(WatchOpts),
+}
+
+#[derive(Debug, StructOpt)]
+struct GenOpts {
+    /// Output file [default: stdout], or directory with `--split`
     #[structopt(short, long, value_name = "PATH")]
-    output: Option<String>,
-    /// JSON schema file
-    schema_path: String,
+    output: Option<PathBuf>,
+    /// Write a file per top-level definition (or schema) and a `mod.rs`
+    /// declaring them into the `--output` directory
+    #[structopt(long, requires = "output")]
+    split: bool,
+    #[structopt(flatten)]
+    options: Options,
+    /// JSON schema files. The types of several schemas are put in a module
+    /// named after each file
+    #[structopt(value_name = "SCHEMA", required = true)]
+    schema_paths: Vec<PathBuf>,
 }
 
-pub fn main() -> Result<()> {
-    let opts = Opts::from_args();
-
-    // generate the Rust code
-    let mut generated_file = NamedTempFile::new()?;
-    Generator::builder()
-        .with_root_name_str(&opts.root)
-        .with_input_file(&opts.schema_path)
-        .build()
-        .generate_to_file(
-            &generated_file
-                .path()
-                .to_str()
-                .ok_or_else(|| anyhow!("converting output path"))?,
-        )?;
-
-    // run it through rustfmt and write it out
-    let (output_file, output_path) = NamedTempFile::new_in(
-        opts.output
-            .as_ref()
-            .map(|p| Path::new(p).parent())
-            .flatten()
-            .unwrap_or(&std::env::temp_dir()),
-    )
-    .context("creating temporary output file")?
-    .into_parts();
-    let mut formatter = Command::new("rustfmt")
-        .args(&["--edition", "2018"])
-        .stdin(Stdio::piped())
-        .stdout(
-            opts.output
-                .as_ref()
-                .and(Some(output_file.into()))
-                .unwrap_or(Stdio::inherit()),
-        )
-        .spawn()
-        .context("running rustfmt")?;
-    copy(
-        generated_file.as_file_mut(),
-        formatter.stdin.as_mut().expect("stdin"),
-    )?;
-    let result = formatter.wait()?;
-    if !result.success() {
-        bail!("rustfmt failed");
-    }
-    if let Some(path) = &opts.output {
-        output_path.persist(path)?;
+#[derive(Debug, StructOpt)]
+struct WatchOpts {
+    /// Output directory, which gets a `<schema>.rs` file for every schema
+    /// and a `mod.rs` declaring them
+    #[structopt(short, long, value_name = "DIR")]
+    output: PathBuf,
+    /// Milliseconds between checks for changed schemas
+    #[structopt(long, value_name = "MILLISECONDS", default_value = "500")]
+    interval: u64,
+    #[structopt(flatten)]
+    options: Options,
+    /// Directory of the JSON schema files
+    #[structopt(value_name = "DIR")]
+    schema_dir: PathBuf,
+}
+
+/// The options of the `schemafy!` macro.
+#[derive(Debug, StructOpt)]
+struct Options {
+    /// Name for the root structure, the `title` of the schema by default
+    #[structopt(short, long, value_name = "NAME")]
+    root: Option<String>,
+    /// Wrap the generated items in a module
+    #[structopt(long = "mod", value_name = "NAME")]
+    module: Option<String>,
+    /// Visibility of the generated types and their fields, `private` for
+    /// none [default: pub]
+    #[structopt(long, value_name = "VISIBILITY")]
+    visibility: Option<String>,
+    /// Prepended to the name of every generated type
+    #[structopt(long, value_name = "PREFIX")]
+    prefix: Option<String>,
+    /// Appended to the name of every generated type
+    #[structopt(long, value_name = "SUFFIX")]
+    suffix: Option<String>,
+    /// Type of `number` schemas with `"format": "decimal"`
+    /// [default: serde_json::Number]
+    #[structopt(long, value_name = "TYPE")]
+    decimal_type: Option<String>,
+    /// Use the decimal type for every `number` schema
+    #[structopt(long)]
+    all_numbers_decimal: bool,
+    /// Type of schemas with a `format`, such as `ipv4=std::net::Ipv4Addr`
+    #[structopt(
+        long = "format",
+        number_of_values = 1,
+        value_name = "FORMAT=TYPE",
+        parse(try_from_str = parse_key_value),
+    )]
+    formats: Vec<(String, String)>,
+    /// Module to (de)serialize the schemas with a `format` with, such as
+    /// `unix-time=schemafy_core::timestamp::seconds`
+    #[structopt(
+        long = "format-module",
+        number_of_values = 1,
+        value_name = "FORMAT=MODULE",
+        parse(try_from_str = parse_key_value),
+    )]
+    format_modules: Vec<(String, String)>,
+    /// Extra trait to derive for every generated type
+    #[structopt(long = "derive", number_of_values = 1, value_name = "TRAIT")]
+    derives: Vec<String>,
+    /// Extra attribute of the type generated from a definition, such as
+    /// `Event=#[non_exhaustive]`
+    #[structopt(
+        long = "attr",
+        number_of_values = 1,
+        value_name = "DEFINITION=ATTRIBUTE",
+        parse(try_from_str = parse_key_value),
+    )]
+    attrs: Vec<(String, String)>,
+    /// Existing type to use for a definition, such as
+    /// `Timestamp=chrono::DateTime<chrono::Utc>`
+    #[structopt(
+        long,
+        number_of_values = 1,
+        value_name = "DEFINITION=TYPE",
+        parse(try_from_str = parse_key_value),
+    )]
+    replace: Vec<(String, String)>,
+    /// Name to use for a definition or a field, such as
+    /// `definitions/foo-bar=FooBar` or `Event.type=kind`
+    #[structopt(
+        long,
+        number_of_values = 1,
+        value_name = "NAME=NAME",
+        parse(try_from_str = parse_key_value),
+    )]
+    rename: Vec<(String, String)>,
+    /// Acronym, such as `URL`, to write like a word in generated names
+    #[structopt(long, number_of_values = 1, value_name = "ACRONYM")]
+    acronym: Vec<String>,
+    /// Write common acronyms (`ID`, `URL`, `HTTP`, `API`...) like words in
+    /// generated names
+    #[structopt(long)]
+    common_acronyms: bool,
+    /// Decode strings with `"contentMediaType": "application/json"`
+    #[structopt(long)]
+    decode_json_strings: bool,
+    /// Set type of arrays with `"uniqueItems": true`
+    #[structopt(long, value_name = "TYPE")]
+    set_type: Option<String>,
+    /// Map type of objects with `additionalProperties` [default: BTreeMap]
+    #[structopt(long, value_name = "TYPE")]
+    map_type: Option<String>,
+    /// Generate empty structs for objects without properties
+    #[structopt(long)]
+    empty_structs: bool,
+    /// Reject unknown members in every struct
+    #[structopt(long)]
+    deny_unknown_fields: bool,
+    /// Mark the structs of open schemas and the enums `#[non_exhaustive]`
+    #[structopt(long)]
+    non_exhaustive: bool,
+    /// Skip serializing `readOnly` fields and deserializing `writeOnly` ones
+    #[structopt(long)]
+    read_write_only: bool,
+    /// Derive `PartialOrd` and `Ord` where the types of the members allow it
+    #[structopt(long)]
+    derive_ord: bool,
+    /// Generate a builder for every struct
+    #[structopt(long)]
+    builders: bool,
+    /// Generate a `new` function taking the required fields for every struct
+    #[structopt(long)]
+    constructors: bool,
+    /// Generate code for `no_std` crates, using `core` and `alloc`
+    #[structopt(long)]
+    no_std: bool,
+    /// Derive `schemars::JsonSchema` for every type
+    #[structopt(long)]
+    json_schema: bool,
+    /// Derive `arbitrary::Arbitrary` behind the `arbitrary` feature
+    #[structopt(long)]
+    arbitrary: bool,
+    /// Generate `schemafy_core::raw::RawJson` instead of `serde_json::Value`
+    #[structopt(long)]
+    raw_value: bool,
+    /// Generate `schemafy_core::Nullable` for optional fields allowing `null`
+    #[structopt(long)]
+    nullable_fields: bool,
+    /// Generate `ordered_float::OrderedFloat<f64>` for numbers
+    #[structopt(long)]
+    ordered_float: bool,
+    /// Generate `FooRead` and `FooWrite` structs for `readOnly` and `writeOnly` fields
+    #[structopt(long)]
+    read_write_variants: bool,
+    /// Always serialize fields accepting one item or an array as an array
+    #[structopt(long)]
+    one_or_many_arrays: bool,
+    /// Generate `schemafy_core::OneOrMany<T>` for fields accepting one item or an array
+    #[structopt(long)]
+    one_or_many_type: bool,
+    /// Generate `Vec<u8>` for strings with `"format": "byte"`, (de)serialized as base64
+    #[structopt(long)]
+    base64_bytes: bool,
+    /// Generate `schemafy_core::Either` or `Union3` for unions instead of `serde_json::Value`
+    #[structopt(long)]
+    union_types: bool,
+    /// Generate a single struct for identical inline objects
+    #[structopt(long)]
+    dedup_types: bool,
+    /// Name inline objects and `oneOf` variants after their `title`
+    #[structopt(long)]
+    title_names: bool,
+    /// Name anonymous types after their path, such as `ConfigServersItemTls`
+    #[structopt(long)]
+    pointer_names: bool,
+    /// Name keyword fields with raw identifiers, such as `r#type`
+    #[structopt(long)]
+    raw_identifiers: bool,
+    /// Do not derive `Copy` for the enums generated from `enum`
+    #[structopt(long)]
+    no_copy_enums: bool,
+    /// Implement `schemafy_core::validation::Validate` for every struct
+    #[structopt(long)]
+    validate: bool,
+    /// Generate `serde_repr` enums for integer enums
+    #[structopt(long)]
+    int_enums: bool,
+    /// Add an `Unknown` variant to string enums
+    #[structopt(long)]
+    open_enums: bool,
+    /// Generate unit structs for string enums with a single value
+    #[structopt(long)]
+    single_value_structs: bool,
+    /// Deserialize string enums ignoring (ASCII) case
+    #[structopt(long)]
+    case_insensitive_enums: bool,
+    /// Deserialize string enums ignoring surrounding whitespace
+    #[structopt(long)]
+    trim_enums: bool,
+    /// Generate newtypes for strings with a `pattern`
+    #[structopt(long)]
+    pattern_types: bool,
+    /// Generate bounded types for numbers with a `minimum` or `maximum`
+    #[structopt(long)]
+    bounded_numbers: bool,
+    /// Generate newtypes for strings with a `minLength` or `maxLength`
+    #[structopt(long)]
+    length_types: bool,
+    /// Derive `validator::Validate` for every struct
+    #[structopt(long)]
+    validator_attributes: bool,
+    /// Implement `TryFrom<serde_json::Value>` reporting every error
+    #[structopt(long)]
+    try_from_value: bool,
+    /// Embed the schema and generate `validate_json` functions
+    #[structopt(long)]
+    embed_schema: bool,
+    /// Use snake case fields with `#[serde(rename_all)]`
+    #[structopt(long)]
+    rename_all: bool,
+    /// Fail on schemas which can not be represented exactly
+    #[structopt(long)]
+    strict: bool,
+    /// Generate a `GENERATION_REPORT` constant
+    #[structopt(long)]
+    report: bool,
+}
+
+fn parse_key_value(s: &str) -> Result<(String, String)> {
+    let (key, value) = s
+        .split_once('=')
+        .ok_or_else(|| anyhow!("expected `KEY=VALUE`, found `{}`", s))?;
+    Ok((key.to_string(), value.to_string()))
+}
+
+/// The generator of `opts`, without any input.
+fn builder(opts: &Options) -> GeneratorBuilder<'_, '_> {
+    let mut builder = Generator::builder()
+        .with_root_name(opts.root.clone())
+        .with_all_numbers_decimal(opts.all_numbers_decimal)
+        .with_decode_json_strings(opts.decode_json_strings)
+        .with_empty_structs(opts.empty_structs)
+        .with_deny_unknown_fields(opts.deny_unknown_fields)
+        .with_non_exhaustive(opts.non_exhaustive)
+        .with_read_write_only(opts.read_write_only)
+        .with_derive_ord(opts.derive_ord)
+        .with_builders(opts.builders)
+        .with_constructors(opts.constructors)
+        .with_no_std(opts.no_std)
+        .with_json_schema(opts.json_schema)
+        .with_arbitrary(opts.arbitrary)
+        .with_raw_value(opts.raw_value)
+        .with_nullable_fields(opts.nullable_fields)
+        .with_ordered_float(opts.ordered_float)
+        .with_read_write_variants(opts.read_write_variants)
+        .with_one_or_many_arrays(opts.one_or_many_arrays)
+        .with_one_or_many_type(opts.one_or_many_type)
+        .with_base64_bytes(opts.base64_bytes)
+        .with_union_types(opts.union_types)
+        .with_dedup_types(opts.dedup_types)
+        .with_title_names(opts.title_names)
+        .with_pointer_names(opts.pointer_names)
+        .with_raw_identifiers(opts.raw_identifiers)
+        .with_copy_enums(!opts.no_copy_enums)
+        .with_validate(opts.validate)
+        .with_int_enums(opts.int_enums)
+        .with_open_enums(opts.open_enums)
+        .with_single_value_structs(opts.single_value_structs)
+        .with_case_insensitive_enums(opts.case_insensitive_enums)
+        .with_trim_enums(opts.trim_enums)
+        .with_pattern_types(opts.pattern_types)
+        .with_bounded_numbers(opts.bounded_numbers)
+        .with_length_types(opts.length_types)
+        .with_validator_attributes(opts.validator_attributes)
+        .with_try_from_value(opts.try_from_value)
+        .with_embed_schema(opts.embed_schema)
+        .with_rename_all(opts.rename_all)
+        .with_strict(opts.strict)
+        .with_report(opts.report);
+    if let Some(set_type) = &opts.set_type {
+        builder = builder.with_set_type(set_type);
+    }
+    if let Some(map_type) = &opts.map_type {
+        builder = builder.with_map_type(map_type);
+    }
+    if let Some(module) = &opts.module {
+        builder = builder.with_module(module);
+    }
+    if let Some(visibility) = &opts.visibility {
+        let visibility = if visibility == "private" {
+            ""
+        } else {
+            visibility
+        };
+        builder = builder.with_visibility(visibility);
+    }
+    if let Some(prefix) = &opts.prefix {
+        builder = builder.with_prefix(prefix);
+    }
+    if let Some(suffix) = &opts.suffix {
+        builder = builder.with_suffix(suffix);
+    }
+    if let Some(decimal_type) = &opts.decimal_type {
+        builder = builder.with_decimal_type(decimal_type);
     }
+    for (format, rust_type) in &opts.formats {
+        builder = builder.with_format_type(format, rust_type);
+    }
+    for (format, module) in &opts.format_modules {
+        builder = builder.with_format_module(format, module);
+    }
+    for derive in &opts.derives {
+        builder = builder.with_derive(derive);
+    }
+    for (type_name, rust_type) in &opts.replace {
+        builder = builder.with_replacement(type_name, rust_type);
+    }
+    for (from, to) in &opts.rename {
+        builder = builder.with_rename(from, to);
+    }
+    if opts.common_acronyms {
+        builder = builder.with_common_acronyms();
+    }
+    for acronym in &opts.acronym {
+        builder = builder.with_acronym(acronym);
+    }
+    for (type_name, attribute) in &opts.attrs {
+        builder = builder.with_type_attribute(type_name, attribute);
+    }
+    builder
+}
 
+fn generator<'a>(opts: &'a Options, schema_paths: &'a [PathBuf]) -> Generator<'a, 'a> {
+    match schema_paths {
+        [schema_path] => builder(opts).with_input_file(schema_path),
+        _ => builder(opts).with_input_files(schema_paths),
+    }
+    .build()
+}
+
+/// Generates the source of the types of `schema_paths`, formatted with
+/// rustfmt if it is installed.
+fn generate(opts: &Options, schema_paths: &[PathBuf]) -> Result<String> {
+    Ok(generator(opts, schema_paths).try_generate_source()?)
+}
+
+/// Writes `source` to `path` through a temporary file, so that a failure
+/// does not leave a truncated output behind.
+fn write_output(path: &Path, source: &str) -> Result<()> {
+    let mut output_file = NamedTempFile::new_in(
+        path.parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new(".")),
+    )
+    .context("creating temporary output file")?;
+    output_file.write_all(source.as_bytes())?;
+    output_file
+        .persist(path)
+        .with_context(|| format!("writing `{}`", path.display()))?;
     Ok(())
 }
+
+fn gen(opts: &GenOpts) -> Result<()> {
+    if let (true, Some(dir)) = (opts.split, &opts.output) {
+        return Ok(generator(&opts.options, &opts.schema_paths).generate_to_dir(dir)?);
+    }
+    let source = generate(&opts.options, &opts.schema_paths)?;
+    match &opts.output {
+        Some(path) => write_output(path, &source),
+        None => Ok(std::io::stdout().write_all(source.as_bytes())?),
+    }
+}
+
+/// The `.json` files of `dir` with their modification times.
+fn schema_files(dir: &Path) -> Result<BTreeMap<PathBuf, SystemTime>> {
+    let mut files = BTreeMap::new();
+    for entry in std::fs::read_dir(dir).with_context(|| format!("reading `{}`", dir.display()))? {
+        let path = entry?.path();
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            let modified = std::fs::metadata(&path)?.modified()?;
+            files.insert(path, modified);
+        }
+    }
+    Ok(files)
+}
+
+/// What `watch` last generated.
+#[derive(Default)]
+struct Watched {
+    /// The schema files with their modification times.
+    schemas: BTreeMap<PathBuf, SystemTime>,
+    /// The modules written to the output directory.
+    modules: BTreeSet<String>,
+}
+
+/// Generates the modules of the schemas of `opts.schema_dir` into
+/// `opts.output` if they changed since `watched`.
+///
+/// Errors are reported without stopping, as the schemas are likely still
+/// being edited. Failing to read or write files is retried on the next call,
+/// while invalid schemas wait for the next change.
+fn watch_once(opts: &WatchOpts, watched: &mut Watched) {
+    let schemas = match schema_files(&opts.schema_dir) {
+        Ok(schemas) => schemas,
+        Err(err) => return eprintln!("Error: {:#}", err),
+    };
+    if schemas == watched.schemas {
+        return;
+    }
+    let schema_paths = schemas.keys().collect::<Vec<_>>();
+    let generated = if schema_paths.is_empty() {
+        Ok(Vec::new())
+    } else {
+        // generated together, so that the schemas can refer to each other
+        builder(&opts.options)
+            .with_input_files(schema_paths)
+            .build()
+            .try_generate_modules()
+    };
+    let generated = match generated {
+        Ok(generated) => generated,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            watched.schemas = schemas;
+            return;
+        }
+    };
+    let mut declarations = String::new();
+    let mut modules = BTreeSet::new();
+    for (module, source) in &generated {
+        let output = opts.output.join(module).with_extension("rs");
+        if let Err(err) = write_output(&output, source) {
+            return eprintln!("Error: {:#}", err);
+        }
+        eprintln!("Generated `{}`", output.display());
+        declarations.push_str(&format!("pub mod {};\n", module));
+        modules.insert(module.clone());
+    }
+    if let Err(err) = write_output(&opts.output.join("mod.rs"), &declarations) {
+        return eprintln!("Error: {:#}", err);
+    }
+    for module in watched.modules.difference(&modules) {
+        let output = opts.output.join(module).with_extension("rs");
+        match std::fs::remove_file(&output) {
+            Ok(()) => eprintln!("Removed `{}`", output.display()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => eprintln!("Error: removing `{}`: {}", output.display(), err),
+        }
+    }
+    *watched = Watched { schemas, modules };
+}
+
+fn watch(opts: &WatchOpts) -> Result<()> {
+    std::fs::create_dir_all(&opts.output)
+        .with_context(|| format!("creating `{}`", opts.output.display()))?;
+    let mut watched = Watched::default();
+    loop {
+        watch_once(opts, &mut watched);
+        std::thread::sleep(Duration::from_millis(opts.interval));
+    }
+}
+
+pub fn main() -> Result<()> {
+    match Opts::from_args() {
+        Opts::Gen(opts) => gen(&opts),
+        Opts::Watch(opts) => watch(&opts),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::ffi::OsStr;
+
+    fn parse(args: &[&OsStr]) -> Opts {
+        Opts::from_iter(std::iter::once(OsStr::new("schemafy")).chain(args.iter().copied()))
+    }
+
+    fn watch_opts(schema_dir: &Path, output: &Path) -> WatchOpts {
+        let args = [
+            "watch".as_ref(),
+            "--output".as_ref(),
+            output.as_ref(),
+            schema_dir.as_ref(),
+        ];
+        match parse(&args) {
+            Opts::Watch(opts) => opts,
+            opts => panic!("{:?}", opts),
+        }
+    }
+
+    #[test]
+    fn gen_split() {
+        let output = tempfile::tempdir().unwrap();
+        let args = [
+            "gen".as_ref(),
+            "--split".as_ref(),
+            "--output".as_ref(),
+            output.path().as_os_str(),
+            "tests/modules/common.json".as_ref(),
+            "tests/modules/events.json".as_ref(),
+        ];
+        match parse(&args) {
+            Opts::Gen(opts) => gen(&opts).unwrap(),
+            opts => panic!("{:?}", opts),
+        }
+        let read = |file: &str| std::fs::read_to_string(output.path().join(file)).unwrap();
+        assert_eq!(read("mod.rs"), "pub mod common;\npub mod events;\n");
+        assert!(read("common.rs").contains("pub struct Error"));
+        let events = read("events.rs");
+        assert!(events.contains("pub struct Event"));
+        assert!(events.contains("super::common::Error"));
+    }
+
+    #[test]
+    fn watch_once_generates_modules() {
+        let schema_dir = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        for file in ["common.json", "events.json"] {
+            let from = Path::new("tests/modules").join(file);
+            std::fs::copy(from, schema_dir.path().join(file)).unwrap();
+        }
+        let type_schema = schema_dir.path().join("type.json");
+        std::fs::write(&type_schema, r#"{ "title": "Kind", "type": "string" }"#).unwrap();
+        let opts = watch_opts(schema_dir.path(), output.path());
+
+        let mut watched = Watched::default();
+        watch_once(&opts, &mut watched);
+        let read = |file: &str| std::fs::read_to_string(output.path().join(file)).unwrap();
+        assert_eq!(
+            read("mod.rs"),
+            "pub mod common;\npub mod events;\npub mod type_;\n"
+        );
+        assert!(read("events.rs").contains("super::common::Error"));
+        assert!(read("type_.rs").contains("pub type Kind = String;"));
+
+        std::fs::remove_file(&type_schema).unwrap();
+        watch_once(&opts, &mut watched);
+        assert_eq!(read("mod.rs"), "pub mod common;\npub mod events;\n");
+        assert!(!output.path().join("type_.rs").exists());
+    }
+
+    #[test]
+    fn watch_once_retries_errors() {
+        let schema_dir = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let missing = schema_dir.path().join("missing");
+        let opts = watch_opts(&missing, output.path());
+
+        let mut watched = Watched::default();
+        watch_once(&opts, &mut watched);
+        assert!(!output.path().join("mod.rs").exists());
+
+        std::fs::create_dir(&missing).unwrap();
+        std::fs::copy("tests/modules/common.json", missing.join("common.json")).unwrap();
+        watch_once(&opts, &mut watched);
+        assert!(output.path().join("common.rs").exists());
+    }
+}
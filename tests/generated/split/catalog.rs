@@ -0,0 +1,17 @@
+#![allow(non_snake_case)]
+#[allow(unused_imports)]
+use super::*;
+#[allow(unused_imports)]
+use serde::{Deserialize, Serialize};
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default, Deserialize, Serialize)]
+pub struct CatalogOwner {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default, Deserialize, Serialize)]
+pub struct Catalog {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub orders: Option<Vec<Order>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<CatalogOwner>,
+}
@@ -0,0 +1,6 @@
+mod order;
+pub use self::order::*;
+mod status;
+pub use self::status::*;
+mod catalog;
+pub use self::catalog::*;
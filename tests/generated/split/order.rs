@@ -0,0 +1,16 @@
+#![allow(non_snake_case)]
+#[allow(unused_imports)]
+use super::*;
+#[allow(unused_imports)]
+use serde::{Deserialize, Serialize};
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Deserialize, Serialize)]
+pub struct OrderItemLines {
+    pub quantity: i64,
+}
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Deserialize, Serialize)]
+#[serde(rename = "order")]
+pub struct Order {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lines: Option<Vec<OrderItemLines>>,
+    pub status: Status,
+}
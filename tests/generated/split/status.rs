@@ -0,0 +1,43 @@
+#![allow(non_snake_case)]
+#[allow(unused_imports)]
+use super::*;
+#[allow(unused_imports)]
+use serde::{Deserialize, Serialize};
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Deserialize, Serialize)]
+#[serde(rename = "status")]
+pub enum Status {
+    #[serde(rename = "open")]
+    Open,
+    #[serde(rename = "closed")]
+    Closed,
+}
+impl Status {
+    #[doc = r" Every value allowed by the schema."]
+    pub const VARIANTS: &'static [Self] = &[Status::Open, Status::Closed];
+}
+impl Status {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Status::Open => "open",
+            Status::Closed => "closed",
+        }
+    }
+}
+impl ::std::fmt::Display for Status {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+impl ::std::str::FromStr for Status {
+    type Err = ::schemafy_core::enums::ParseEnumError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open" => Ok(Status::Open),
+            "closed" => Ok(Status::Closed),
+            _ => Err(::schemafy_core::enums::ParseEnumError {
+                value: s.to_string(),
+                expected: &["open", "closed"],
+            }),
+        }
+    }
+}
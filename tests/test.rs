@@ -1,3 +1,6 @@
+extern crate alloc;
+
+use schemafy_core::validation::Validate;
 use serde_derive::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 
@@ -119,6 +122,7 @@ schemafy::schemafy!(
 );
 
 #[test]
+#[allow(clippy::get_first)]
 fn root_array() {
     let a = RootArray::default();
     let _: Option<&RootArrayItem> = a.get(0);
@@ -135,6 +139,16 @@ fn enum_names_int() {
     assert_eq!(serde_json::to_string(&EnumNamesInt::B).unwrap(), "2");
 }
 
+#[test]
+fn enum_variants() {
+    assert_eq!(EnumNamesInt::VARIANTS, [EnumNamesInt::A, EnumNamesInt::B]);
+    assert_eq!(Status::VARIANTS, [Status::Active, Status::Disabled]);
+    assert_eq!(
+        IntEnumsOffset::VARIANTS,
+        [IntEnumsOffset::NumberMinus1, IntEnumsOffset::Number1]
+    );
+}
+
 schemafy::schemafy!(
     root: EnumNamesStr
     "tests/enum-names-str.json"
@@ -146,6 +160,22 @@ fn enum_names_str() {
     assert_eq!(serde_json::to_string(&EnumNamesStr::B).unwrap(), "\"2\"");
 }
 
+#[test]
+fn enum_strings() {
+    assert_eq!(EnumNamesStr::A.as_str(), "1");
+    assert_eq!(EnumNamesStr::B.to_string(), "2");
+    assert_eq!("2".parse::<EnumNamesStr>(), Ok(EnumNamesStr::B));
+    assert_eq!(
+        "b".parse::<EnumNamesStr>().unwrap_err().to_string(),
+        "unknown variant `b`, expected one of `1`, `2`"
+    );
+    assert_eq!(
+        "archived".parse::<Status>(),
+        Ok(Status::Unknown("archived".into()))
+    );
+    assert_eq!(Status::Unknown("archived".into()).to_string(), "archived");
+}
+
 schemafy::schemafy!(
     root: RecursiveTypes
     "tests/recursive_types.json"
@@ -154,6 +184,13 @@ schemafy::schemafy!(
 #[allow(dead_code)]
 fn recursive_types_exist(_: RecursiveTypes) {}
 
+#[test]
+fn box_accessors() {
+    let test: TestA = serde_json::from_str(r#"{"ref":{"ref":{}}}"#).unwrap();
+    let inner: Option<&TestA> = test.ref_();
+    assert!(inner.unwrap().ref_().unwrap().ref_().is_none());
+}
+
 schemafy::schemafy!(
     root: OneOfSchema
     "tests/one-of-types.json"
@@ -192,9 +229,2636 @@ fn unknown_fields() {
     serde_json::from_str::<RootArrayItem>(r#"{"zzz": 5}"#).unwrap_err();
     // empty struct with additionalProperties: true
     serde_json::from_str::<AnyProperties>(r#"{"zzz": 5}"#).unwrap();
-    // empty struct with additionalProperties: false and patternProperties
-    // non-empty
-    serde_json::from_str::<PatternProperties>(r#"{"zzz": 5}"#).unwrap();
+    // map of patternProperties with additionalProperties: false
+    serde_json::from_str::<PatternProperties>(r#"{"zzz": {"a": 5}}"#).unwrap();
     // non-empty struct with additionalProperties unspecified
     serde_json::from_str::<ArrayType>(r#"{"required": [], "zzz": 5}"#).unwrap();
 }
+
+schemafy::schemafy!(
+    root: Decimal
+    "tests/decimal.json"
+);
+
+schemafy::schemafy!(
+    root: AllDecimal
+    all_numbers_decimal: true
+    "tests/decimal.json"
+);
+
+#[test]
+fn decimal() {
+    let d: Decimal = serde_json::from_str(r#"{"price": 12.5, "ratio": 0.5}"#).unwrap();
+    let _: serde_json::Number = d.price;
+    let _: f64 = d.ratio;
+
+    let d: AllDecimal = serde_json::from_str(r#"{"price": 12.5, "ratio": 0.5}"#).unwrap();
+    let _: serde_json::Number = d.price;
+    let _: serde_json::Number = d.ratio;
+}
+
+schemafy::schemafy!(
+    root: Formats
+    formats {
+        "ipv4" => "std::net::Ipv4Addr",
+        "path" => "std::path::PathBuf",
+    }
+    "tests/formats.json"
+);
+
+#[test]
+fn formats() {
+    let f: Formats = serde_json::from_str(
+        r#"{"address": "127.0.0.1", "config": "/etc/config.toml", "name": "localhost"}"#,
+    )
+    .unwrap();
+    assert_eq!(f.address, std::net::Ipv4Addr::LOCALHOST);
+    assert_eq!(f.config, std::path::PathBuf::from("/etc/config.toml"));
+    let _: String = f.name;
+    serde_json::from_str::<Formats>(r#"{"address": "x", "config": "", "name": ""}"#).unwrap_err();
+}
+
+schemafy::schemafy!(
+    root: ContentMediaType
+    decode_json_strings: true
+    "tests/content-media-type.json"
+);
+
+#[test]
+fn content_media_type() {
+    let c: ContentMediaType = serde_json::from_str(r#"{"payload": "{\"id\": 3}"}"#).unwrap();
+    assert_eq!(c.payload, ContentMediaTypePayload { id: 3 });
+    assert_eq!(c.extra, None);
+
+    let c: ContentMediaType =
+        serde_json::from_str(r#"{"payload": "{\"id\": 3}", "extra": "[1, 2]"}"#).unwrap();
+    assert_eq!(c.extra, Some(serde_json::json!([1, 2])));
+    assert_eq!(
+        serde_json::to_string(&c).unwrap(),
+        r#"{"extra":"[1,2]","payload":"{\"id\":3}"}"#
+    );
+
+    serde_json::from_str::<ContentMediaType>(r#"{"payload": {"id": 3}}"#).unwrap_err();
+}
+
+schemafy::schemafy!(
+    root: Tuple
+    "tests/tuple.json"
+);
+
+#[test]
+fn tuple() {
+    let t: Tuple = serde_json::from_str(
+        r#"{"position": [1.5, 2.0, "north"], "range": [1, {"inclusive": true}]}"#,
+    )
+    .unwrap();
+    assert_eq!(t.position, (1.5, 2.0, "north".to_string()));
+    assert_eq!(
+        t.range,
+        Some((
+            1,
+            TupleItem1Range {
+                inclusive: Some(true)
+            }
+        ))
+    );
+    let _: Option<Vec<i64>> = t.open;
+    serde_json::from_str::<Tuple>(r#"{"position": [1.5, 2.0]}"#).unwrap_err();
+}
+
+schemafy::schemafy!(
+    root: UniqueItems
+    set_type: std::collections::BTreeSet
+    "tests/unique-items.json"
+);
+
+#[test]
+fn unique_items() {
+    let u: UniqueItems =
+        serde_json::from_str(r#"{"tags": ["b", "a", "b"], "weights": [1.0], "ids": [2, 2]}"#)
+            .unwrap();
+    let expected: std::collections::BTreeSet<String> =
+        vec!["a".to_string(), "b".to_string()].into_iter().collect();
+    assert_eq!(u.tags, expected);
+    let _: Vec<f64> = u.weights;
+    assert_eq!(u.ids, vec![2, 2]);
+}
+
+schemafy::schemafy!(
+    root: HashMapProperties
+    map_type: std::collections::HashMap
+    "tests/any-properties.json"
+);
+
+#[test]
+fn map_type() {
+    let m: HashMapProperties = serde_json::from_str(r#"{"a": 1}"#).unwrap();
+    let _: &std::collections::HashMap<String, serde_json::Value> = &m;
+    assert_eq!(m["a"], serde_json::json!(1));
+}
+
+schemafy::schemafy!(
+    root: PatternPropertiesFields
+    "tests/pattern-properties-fields.json"
+);
+
+#[test]
+fn pattern_properties() {
+    let p: PatternProperties = serde_json::from_str(r#"{"foo": {"a": 5}}"#).unwrap();
+    assert_eq!(p["foo"]["a"], serde_json::json!(5));
+
+    let p: PatternPropertiesFields = serde_json::from_str(r#"{"name": "a", "x-b": "c"}"#).unwrap();
+    assert_eq!(p.name, "a");
+    assert_eq!(p.pattern_properties["x-b"], "c");
+    assert_eq!(
+        serde_json::to_string(&p).unwrap(),
+        r#"{"name":"a","x-b":"c"}"#
+    );
+}
+
+schemafy::schemafy!(
+    root: PropertyNames
+    "tests/property-names.json"
+);
+
+#[test]
+fn property_names() {
+    use std::convert::TryFrom;
+
+    let p: PropertyNames =
+        serde_json::from_str(r#"{"limits": {"cpu": 2}, "labels": {"app": "web"}}"#).unwrap();
+    assert_eq!(p.limits[&PropertyNamesLimitsKey::Cpu], 2);
+    let app = PropertyNamesLabelsKey::try_from("app".to_string()).unwrap();
+    assert_eq!(p.labels[&app], "web");
+    assert_eq!(&*app, "app");
+    assert_eq!(
+        serde_json::to_string(&p).unwrap(),
+        r#"{"labels":{"app":"web"},"limits":{"cpu":2}}"#
+    );
+
+    PropertyNamesLabelsKey::try_from("App".to_string()).unwrap_err();
+    serde_json::from_str::<PropertyNames>(r#"{"limits": {"disk": 2}, "labels": {}}"#).unwrap_err();
+    serde_json::from_str::<PropertyNames>(r#"{"limits": {}, "labels": {"App": "web"}}"#)
+        .unwrap_err();
+}
+
+schemafy::schemafy!(
+    root: AdditionalProperties
+    "tests/additional-properties.json"
+);
+
+#[test]
+fn additional_properties() {
+    let json = r#"{"count":3,"metadata":{"extra":[1],"version":1},"name":"a"}"#;
+    let a: AdditionalProperties = serde_json::from_str(json).unwrap();
+    assert_eq!(a.name, "a");
+    assert_eq!(a.additional_properties["count"], 3);
+    let metadata = a.metadata.as_ref().unwrap();
+    assert_eq!(metadata.version, Some(1));
+    assert_eq!(
+        metadata.additional_properties["extra"],
+        serde_json::json!([1])
+    );
+    assert_eq!(
+        serde_json::to_value(&a).unwrap(),
+        serde_json::from_str::<serde_json::Value>(json).unwrap()
+    );
+
+    serde_json::from_str::<AdditionalProperties>(r#"{"name": "a", "count": "3"}"#).unwrap_err();
+}
+
+schemafy::schemafy!(
+    root: EmptyObject
+    empty_structs: true
+    "tests/empty-object.json"
+);
+
+#[test]
+fn empty_object() {
+    let e: EmptyObject = serde_json::from_str(r#"{"marker": {}, "inline": {"a": 1}}"#).unwrap();
+    assert_eq!(e.marker, Some(Marker {}));
+    assert_eq!(e.inline, Some(EmptyObjectInline {}));
+    assert_eq!(
+        serde_json::to_string(&EmptyObject::default()).unwrap(),
+        "{}"
+    );
+    serde_json::from_str::<Marker>("1").unwrap_err();
+}
+
+schemafy::schemafy!(
+    root: NullableValues
+    "tests/nullable-values.json"
+);
+
+#[test]
+fn nullable_values() {
+    let n: NullableValues = serde_json::from_str(
+        r#"{
+            "types": {"a": "b", "c": null},
+            "any_of": {"a": 1, "c": null},
+            "one_of": {"a": {"x": "y"}, "c": null}
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(n.types["a"], Some("b".to_string()));
+    assert_eq!(n.types["c"], None);
+    assert_eq!(n.any_of["a"], Some(1));
+    assert_eq!(n.any_of["c"], None);
+    assert_eq!(
+        n.one_of["a"],
+        Some(NullableValuesOneOf {
+            x: Some("y".into())
+        })
+    );
+    assert_eq!(n.one_of["c"], None);
+    assert_eq!(n.count, None);
+
+    let n: NullableValues =
+        serde_json::from_str(r#"{"types": {}, "any_of": {}, "one_of": {}, "count": "5"}"#).unwrap();
+    assert_eq!(n.count, Some(5));
+    assert_eq!(
+        serde_json::to_string(&n).unwrap(),
+        r#"{"any_of":{},"count":"5","one_of":{},"types":{}}"#
+    );
+}
+
+schemafy::schemafy!(
+    root: Contains
+    validate: true
+    "tests/contains.json"
+);
+
+#[test]
+fn contains() {
+    let c: Contains = serde_json::from_str(r#"{"roles": ["user", "admin"]}"#).unwrap();
+    assert_eq!(c.validate(), Ok(()));
+    let c: Contains = serde_json::from_str(r#"{"roles": ["user"], "scores": [1, 2]}"#).unwrap();
+    let errors = c.validate().unwrap_err();
+    assert_eq!(
+        errors.0.iter().map(|e| &e.path[..]).collect::<Vec<_>>(),
+        ["/roles", "/scores"]
+    );
+}
+
+schemafy::schemafy!(
+    root: IntEnums
+    int_enums: true
+    "tests/int-enums.json"
+);
+
+#[test]
+fn int_enums() {
+    let e: IntEnums = serde_json::from_str(r#"{"level": 2, "offset": -1}"#).unwrap();
+    assert_eq!(e.level, IntEnumsLevel::Warn);
+    assert_eq!(e.offset, Some(IntEnumsOffset::NumberMinus1));
+    assert_eq!(
+        serde_json::to_string(&e).unwrap(),
+        r#"{"level":2,"offset":-1}"#
+    );
+    assert!(serde_json::from_str::<IntEnums>(r#"{"level": 3}"#).is_err());
+}
+
+schemafy::schemafy!(
+    root: OpenEnums
+    open_enums: true
+    "tests/open-enums.json"
+);
+
+#[test]
+fn open_enums() {
+    let e: OpenEnums = serde_json::from_str(r#"{"status": "disabled"}"#).unwrap();
+    assert_eq!(e.status, Some(Status::Disabled));
+    let e: OpenEnums = serde_json::from_str(r#"{"status":"archived"}"#).unwrap();
+    assert_eq!(e.status, Some(Status::Unknown("archived".into())));
+    // Unknown values are written back as they were received
+    assert_eq!(
+        serde_json::to_string(&e).unwrap(),
+        r#"{"status":"archived"}"#
+    );
+}
+
+schemafy::schemafy!(
+    root: LenientEnums
+    case_insensitive_enums: true
+    trim_enums: true
+    "tests/lenient-enums.json"
+);
+
+#[test]
+fn lenient_enums() {
+    let e: LenientEnums = serde_json::from_str(r#"{"color": " Red "}"#).unwrap();
+    assert_eq!(e.color, Some(Color::Red));
+    let e: LenientEnums = serde_json::from_str(r#"{"color": "green"}"#).unwrap();
+    assert_eq!(e.color, Some(Color::Green));
+    assert_eq!(serde_json::to_string(&e).unwrap(), r#"{"color":"GREEN"}"#);
+    assert!(serde_json::from_str::<LenientEnums>(r#"{"color": "blue"}"#).is_err());
+}
+
+schemafy::schemafy!(
+    root: EnumCollisions
+    "tests/enum-collisions.json"
+);
+
+#[test]
+fn enum_collisions() {
+    assert_eq!(
+        EnumCollisions::VARIANTS,
+        [
+            EnumCollisions::FooBar,
+            EnumCollisions::FooBar2,
+            EnumCollisions::FooBar22,
+            EnumCollisions::Unknown
+        ]
+    );
+    assert_eq!(EnumCollisions::FooBar2.as_str(), "foo_bar");
+    assert_eq!(
+        serde_json::from_str::<EnumCollisions>(r#""FooBar2""#).unwrap(),
+        EnumCollisions::FooBar22
+    );
+}
+
+schemafy::schemafy!(
+    root: SingleValueEnum
+    single_value_structs: true
+    "tests/single-value-enum.json"
+);
+
+#[test]
+fn single_value_enum() {
+    let s: SingleValueEnum = serde_json::from_str(r#"{"version": "2.0"}"#).unwrap();
+    assert_eq!(s.version, Version);
+    assert_eq!(
+        serde_json::to_string(&SingleValueEnum { version: Version }).unwrap(),
+        r#"{"version":"2.0"}"#
+    );
+    assert!(serde_json::from_str::<SingleValueEnum>(r#"{"version": "1.0"}"#).is_err());
+}
+
+schemafy::schemafy!(
+    root: Validated
+    validate: true
+    "tests/validate.json"
+);
+
+#[test]
+fn validate() {
+    let v: Validated = serde_json::from_str(
+        r#"{"name": "abc", "age": 20, "ratio": 0.5, "tags": ["a"], "children": [{"age": 3}]}"#,
+    )
+    .unwrap();
+    assert_eq!(v.validate(), Ok(()));
+
+    let v: Validated = serde_json::from_str(
+        r#"{
+            "name": "ABCDEF",
+            "age": -1,
+            "ratio": 0,
+            "tags": ["a", "abcd", "a"],
+            "children": [{"age": 3}, {"age": 18}]
+        }"#,
+    )
+    .unwrap();
+    let errors = v.validate().unwrap_err();
+    assert_eq!(
+        errors.to_string(),
+        "/age: must be at least 0\n\
+         /children/1/age: must be at most 17\n\
+         /name: must be at most 5 characters long\n\
+         /name: must match the pattern `^[a-z]+$`\n\
+         /ratio: must be greater than 0\n\
+         /tags: must not contain duplicate items\n\
+         /tags/1: must be at most 3 characters long"
+    );
+}
+
+schemafy::schemafy!(
+    root: MultipleOf
+    validate: true
+    "tests/multiple-of.json"
+);
+
+#[test]
+fn multiple_of() {
+    let m: MultipleOf =
+        serde_json::from_str(r#"{"cents": 25, "hours": 1.75, "ratio": 0.3}"#).unwrap();
+    assert_eq!(m.validate(), Ok(()));
+
+    let m: MultipleOf =
+        serde_json::from_str(r#"{"cents": 12, "hours": 0.1, "ratio": 0.35}"#).unwrap();
+    assert_eq!(
+        m.validate().unwrap_err().to_string(),
+        "/cents: must be a multiple of 5\n\
+         /hours: must be a multiple of 0.25\n\
+         /ratio: must be a multiple of 0.1"
+    );
+}
+
+schemafy::schemafy!(
+    root: PatternTypes
+    pattern_types: true
+    "tests/pattern-types.json"
+);
+
+#[test]
+fn pattern_types() {
+    use std::convert::TryFrom;
+
+    let p: PatternTypes = serde_json::from_str(r#"{"code": "EUR", "zip": "12345"}"#).unwrap();
+    assert_eq!(&*p.code, "EUR");
+    assert_eq!(p.zip, Some(Zip::try_from("12345".to_string()).unwrap()));
+    assert!(serde_json::from_str::<PatternTypes>(r#"{"code": "eur"}"#).is_err());
+    assert!(Zip::try_from("1234".to_string()).is_err());
+    assert_eq!(
+        serde_json::to_string(&p).unwrap(),
+        r#"{"code":"EUR","zip":"12345"}"#
+    );
+}
+
+schemafy::schemafy!(
+    root: BoundedNumbers
+    bounded_numbers: true
+    "tests/bounded-numbers.json"
+);
+
+#[test]
+fn bounded_numbers() {
+    let b: BoundedNumbers =
+        serde_json::from_str(r#"{"percent": 100, "offset": -9, "ratio": 0.5}"#).unwrap();
+    assert_eq!(b.percent.get(), 100);
+    assert_eq!(b.offset.map(|o| *o), Some(-9));
+    assert_eq!(b.ratio.map(f64::from), Some(0.5));
+    assert!(serde_json::from_str::<BoundedNumbers>(r#"{"percent": 101}"#).is_err());
+    assert!(serde_json::from_str::<BoundedNumbers>(r#"{"percent": 1, "offset": -10}"#).is_err());
+    assert!(serde_json::from_str::<BoundedNumbers>(r#"{"percent": 1, "ratio": 1}"#).is_err());
+}
+
+schemafy::schemafy!(
+    root: LengthTypes
+    length_types: true
+    "tests/length-types.json"
+);
+
+#[test]
+fn length_types() {
+    use std::convert::TryFrom;
+
+    let l: LengthTypes = serde_json::from_str(r#"{"name": "abc", "code": "xyz"}"#).unwrap();
+    assert_eq!(&*l.name, "abc");
+    assert!(serde_json::from_str::<LengthTypes>(r#"{"name": ""}"#).is_err());
+    assert!(serde_json::from_str::<LengthTypes>(r#"{"name": "a", "code": "wxyz"}"#).is_err());
+    assert!(serde_json::from_str::<LengthTypes>(r#"{"name": "a", "code": "XY"}"#).is_err());
+    assert_eq!(
+        LengthTypesName::try_from("123456789".to_string())
+            .unwrap_err()
+            .to_string(),
+        "`123456789` is not between 1 and 8 characters long"
+    );
+}
+
+schemafy::schemafy!(
+    root: Embedded
+    embed_schema: true
+    "tests/embed-schema.json"
+);
+
+#[test]
+fn embed_schema() {
+    let e = Embedded::validate_json(&serde_json::json!({"name": "ab", "pet": {"age": 2}})).unwrap();
+    assert_eq!(e.pet.map(|pet| pet.age), Some(2));
+
+    let errors =
+        Embedded::validate_json(&serde_json::json!({"name": "a", "pet": {"age": -1}})).unwrap_err();
+    let mut paths = errors.0.iter().map(|e| &e.path[..]).collect::<Vec<_>>();
+    paths.sort();
+    assert_eq!(paths, ["/name", "/pet/age"]);
+
+    let errors = Pet::validate_json(&serde_json::json!({})).unwrap_err();
+    assert_eq!(errors.0.len(), 1);
+}
+
+mod validator_attributes {
+    use serde_derive::{Deserialize, Serialize};
+    use validator::Validate;
+
+    schemafy::schemafy!(
+        root: ValidatorAttributes
+        validator_attributes: true
+        "tests/validator-attributes.json"
+    );
+
+    #[test]
+    fn validator_attributes() {
+        let v: ValidatorAttributes = serde_json::from_str(
+            r#"{"name": "abc", "age": 3, "ratio": 0.5, "pets": [{"name": "x"}]}"#,
+        )
+        .unwrap();
+        assert!(v.validate().is_ok());
+
+        let v: ValidatorAttributes = serde_json::from_str(
+            r#"{"name": "ABC", "age": 151, "ratio": 0, "tags": ["a", "b", "c"], "pets": [{"name": ""}]}"#,
+        )
+        .unwrap();
+        let errors = v.validate().unwrap_err();
+        let mut fields = errors.errors().keys().map(|k| &k[..]).collect::<Vec<_>>();
+        fields.sort();
+        assert_eq!(fields, ["age", "name", "pets", "ratio", "tags"]);
+    }
+}
+
+#[test]
+fn validated_json() {
+    use schemafy_core::validated::ValidatedJson;
+
+    let v: ValidatedJson<Validated> = serde_json::from_str(r#"{"name": "abc"}"#).unwrap();
+    assert_eq!(v.name, "abc");
+
+    let err = serde_json::from_str::<ValidatedJson<Validated>>(r#"{"name": "A", "age": -1}"#)
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "/age: must be at least 0\n\
+         /name: must be at least 2 characters long\n\
+         /name: must match the pattern `^[a-z]+$`"
+    );
+    let errors = ValidatedJson::<Validated>::from_str(r#"{"name": "A", "age": -1}"#).unwrap_err();
+    assert_eq!(errors.0.len(), 3);
+}
+
+schemafy::schemafy!(
+    root: Order
+    try_from_value: true
+    "tests/try-from-value.json"
+);
+
+#[test]
+fn try_from_value() {
+    use std::convert::TryFrom;
+
+    let order = Order::try_from(serde_json::json!({
+        "id": 1,
+        "status": "open",
+        "lines": [{"sku": "a", "quantity": 2}]
+    }))
+    .unwrap();
+    assert_eq!(order.lines.unwrap()[0].quantity, 2);
+
+    let errors = Order::try_from(serde_json::json!({
+        "id": "1",
+        "note": null,
+        "status": "pending",
+        "lines": [{"sku": "a", "quantity": 2}, {"quantity": 1.5}]
+    }))
+    .unwrap_err();
+    assert_eq!(
+        errors.0.iter().map(|e| &e.path[..]).collect::<Vec<_>>(),
+        ["/id", "/lines/1/quantity", "/lines/1/sku", "/status"]
+    );
+    assert_eq!(errors.0[2].to_string(), "/lines/1/sku: missing field");
+}
+
+schemafy::schemafy!(
+    root: InlineSchema
+    schema: r##"{
+        "type": "object",
+        "properties": {
+            "name": { "type": "string" },
+            "size": { "$ref": "#/definitions/size" }
+        },
+        "required": ["name"],
+        "definitions": {
+            "size": { "type": "string", "enum": ["small", "large"] }
+        }
+    }"##
+);
+
+#[test]
+fn inline_schema() {
+    let s: InlineSchema = serde_json::from_str(r#"{"name": "a", "size": "large"}"#).unwrap();
+    assert_eq!(s.name, "a");
+    assert_eq!(s.size, Some(Size::Large));
+}
+
+macro_rules! schema_literal {
+    ($schema:literal) => {
+        schemafy::schemafy_str!(root: MacroSchema $schema);
+    };
+}
+
+schema_literal!(r#"{"type": "object", "properties": {"id": {"type": "integer"}}}"#);
+
+mod included {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy_str!(
+        root: Nested
+        concat!(include_str!("nested.json"), "\n")
+    );
+}
+
+#[test]
+fn schemafy_str() {
+    let m: MacroSchema = serde_json::from_str(r#"{"id": 1}"#).unwrap();
+    assert_eq!(m.id, Some(1));
+    let n: included::Defnested = serde_json::from_str(r#"{"append": "abc"}"#).unwrap();
+    assert_eq!(n.append.as_deref(), Some("abc"));
+}
+
+mod derives {
+    use serde_derive::Serialize;
+
+    schemafy::schemafy!(
+        root: Tagged
+        derives: [Eq, Hash, serde_derive::Deserialize]
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "name": { "type": "string" },
+                "tags": { "type": "array", "items": { "type": "string" } }
+            },
+            "required": ["name"]
+        }"#
+    );
+
+    #[test]
+    fn derives() {
+        use std::collections::HashSet;
+
+        let tagged: Tagged = serde_json::from_str(r#"{"name": "a", "tags": ["b"]}"#).unwrap();
+        let mut set = HashSet::new();
+        set.insert(tagged.clone());
+        assert!(set.contains(&tagged));
+    }
+}
+
+mod type_attributes {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        attrs {
+            "event": ["#[serde(deny_unknown_fields)]"],
+            "Kind": ["#[allow(clippy::enum_variant_names)]"]
+        }
+        schema: r##"{
+            "definitions": {
+                "event": {
+                    "type": "object",
+                    "properties": { "kind": { "$ref": "#/definitions/kind" } }
+                },
+                "kind": { "type": "string", "enum": ["a", "b"] }
+            }
+        }"##
+    );
+
+    #[test]
+    fn type_attributes() {
+        assert!(serde_json::from_str::<Event>(r#"{"kind": "a"}"#).is_ok());
+        assert!(serde_json::from_str::<Event>(r#"{"kind": "a", "other": 1}"#).is_err());
+    }
+}
+
+mod visibility {
+    use serde_derive::{Deserialize, Serialize};
+
+    mod private {
+        use serde_derive::{Deserialize, Serialize};
+
+        schemafy::schemafy!(
+            root: Hidden
+            visibility: pub(super)
+            schema: r#"{
+                "type": "object",
+                "properties": { "value": { "type": "integer" } },
+                "required": ["value"]
+            }"#
+        );
+    }
+
+    schemafy::schemafy!(
+        root: Private
+        visibility: private
+        schema: r#"{ "type": "object", "properties": { "value": { "type": "string" } } }"#
+    );
+
+    #[test]
+    fn visibility() {
+        let hidden: private::Hidden = serde_json::from_str(r#"{"value": 1}"#).unwrap();
+        assert_eq!(hidden.value, 1);
+        let p: Private = serde_json::from_str(r#"{"value": "a"}"#).unwrap();
+        assert_eq!(p.value.as_deref(), Some("a"));
+    }
+}
+
+schemafy::schemafy!(
+    mod: wrapped
+    root: Wrapped
+    int_enums: true
+    schema: r#"{
+        "type": "object",
+        "properties": {
+            "name": { "type": "string" },
+            "level": { "type": "integer", "enum": [1, 2] }
+        },
+        "required": ["name"]
+    }"#
+);
+
+#[test]
+fn module() {
+    let w: wrapped::Wrapped = serde_json::from_str(r#"{"name": "a", "level": 2}"#).unwrap();
+    assert_eq!(w.name, "a");
+    assert_eq!(w.level, Some(wrapped::WrappedLevel::Number2));
+}
+
+mod prefix {
+    use serde_derive::{Deserialize, Serialize};
+
+    macro_rules! versioned {
+        ($($option:ident: $value:literal)*) => {
+            schemafy::schemafy!(
+                $($option: $value)*
+                schema: r##"{
+                    "definitions": {
+                        "request": {
+                            "type": "object",
+                            "properties": {
+                                "command": { "type": "string" },
+                                "parent": { "$ref": "#/definitions/request" },
+                                "source": {
+                                    "type": "object",
+                                    "properties": { "path": { "type": "string" } }
+                                }
+                            },
+                            "required": ["command"]
+                        }
+                    }
+                }"##
+            );
+        };
+    }
+
+    versioned!(prefix: "Dap");
+    versioned!(prefix: "Lsp" suffix: "V2");
+
+    #[test]
+    fn prefix_and_suffix() {
+        let dap: DapRequest = serde_json::from_str(
+            r#"{"command": "b", "parent": {"command": "a"}, "source": {"path": "x"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            dap.parent.map(|parent| parent.command).as_deref(),
+            Some("a")
+        );
+        let source: Option<DapRequestSource> = dap.source;
+        assert_eq!(source.and_then(|s| s.path).as_deref(), Some("x"));
+
+        let lsp: LspRequestV2 = serde_json::from_str(r#"{"command": "c"}"#).unwrap();
+        let _: Option<Box<LspRequestV2>> = lsp.parent;
+        let _: Option<LspRequestSourceV2> = lsp.source;
+    }
+}
+
+mod replace {
+    use serde_derive::{Deserialize, Serialize};
+
+    mod money {
+        #[derive(Clone, PartialEq, Debug, serde_derive::Deserialize, serde_derive::Serialize)]
+        pub struct Money(pub String);
+    }
+
+    schemafy::schemafy!(
+        root: Invoice
+        replace { "Timestamp": "u64", "money": "money::Money" }
+        schema: r##"{
+            "type": "object",
+            "properties": {
+                "issued": { "$ref": "#/definitions/timestamp" },
+                "total": { "$ref": "#/definitions/money" }
+            },
+            "required": ["issued", "total"],
+            "definitions": {
+                "timestamp": { "type": "string", "format": "date-time" },
+                "money": { "type": "object", "properties": { "cents": { "type": "integer" } } }
+            }
+        }"##
+    );
+
+    #[test]
+    fn replace() {
+        let invoice: Invoice =
+            serde_json::from_str(r#"{"issued": 1700000000, "total": "9.99 EUR"}"#).unwrap();
+        assert_eq!(invoice.issued, 1_700_000_000);
+        assert_eq!(invoice.total, money::Money("9.99 EUR".into()));
+    }
+}
+
+mod rename_all {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: Renamed
+        rename_all: true
+        schema: r##"{
+            "type": "object",
+            "properties": {
+                "firstName": { "type": "string" },
+                "type": { "type": "string" },
+                "id": { "type": "integer" },
+                "settings": { "$ref": "#/definitions/settings" }
+            },
+            "required": ["firstName"],
+            "definitions": {
+                "settings": {
+                    "type": "object",
+                    "properties": {
+                        "dark-mode": { "type": "boolean" },
+                        "fontSize": { "type": "integer" }
+                    }
+                }
+            }
+        }"##
+    );
+
+    #[test]
+    fn rename_all() {
+        let json = r#"{"firstName":"a","settings":{"dark-mode":true,"fontSize":2},"type":"b"}"#;
+        let renamed: Renamed = serde_json::from_str(json).unwrap();
+        assert_eq!(renamed.first_name, "a");
+        assert_eq!(renamed.type_.as_deref(), Some("b"));
+        let settings = renamed.settings.as_ref().unwrap();
+        assert_eq!(settings.dark_mode, Some(true));
+        assert_eq!(settings.fontSize, Some(2));
+        assert_eq!(serde_json::to_string(&renamed).unwrap(), json);
+    }
+}
+
+mod file_relative {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!("./nested.json");
+
+    #[test]
+    fn file_relative_path() {
+        let n: Defnested = serde_json::from_str(r#"{"append": "abc"}"#).unwrap();
+        assert_eq!(n.append.as_deref(), Some("abc"));
+    }
+}
+
+mod env_path {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!("${CARGO_MANIFEST_DIR}/tests/nested.json");
+
+    mod concat {
+        use serde_derive::{Deserialize, Serialize};
+
+        schemafy::schemafy!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/nested.json"));
+    }
+
+    #[test]
+    fn env_path() {
+        let n: Defnested = serde_json::from_str(r#"{"append": "abc"}"#).unwrap();
+        assert_eq!(n.append.as_deref(), Some("abc"));
+        let n: concat::Defnested = serde_json::from_str(r#"{"append": "abc"}"#).unwrap();
+        assert_eq!(n.append.as_deref(), Some("abc"));
+    }
+}
+
+#[schemafy::schema(
+    root: Point
+    schema: r#"{
+        "type": "object",
+        "properties": { "x": { "type": "integer" }, "y": { "type": "integer" } },
+        "required": ["x", "y"]
+    }"#
+)]
+pub mod attribute {
+    use serde_derive::Serialize;
+
+    impl Point {
+        pub fn sum(&self) -> i64 {
+            self.x + self.y
+        }
+    }
+}
+
+#[test]
+fn attribute_macro() {
+    let point: attribute::Point = serde_json::from_str(r#"{"x": 1, "y": 2}"#).unwrap();
+    assert_eq!(point.sum(), 3);
+}
+
+mod modules {
+    schemafy::schemafy!("tests/modules/common.json", "tests/modules/events.json");
+
+    #[test]
+    fn modules() {
+        let event: events::Event = serde_json::from_str(
+            r#"{"error": {"code": 1, "cause": {"message": "a"}}, "source": {"message": "b"}}"#,
+        )
+        .unwrap();
+        let error: events::Error = event.error.unwrap();
+        let cause: Option<common::Error> = error.cause;
+        assert_eq!(cause.unwrap().message, "a");
+        assert_eq!(event.source.unwrap().message, "b");
+    }
+}
+
+mod report {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: Reported
+        report: true
+        schema: r##"{
+            "type": "object",
+            "properties": {
+                "name": { "type": "string", "maxLength": 3 },
+                "kind": { "type": "string", "enum": ["a", "b"] },
+                "value": { "anyOf": [{ "type": "string" }, { "type": "integer" }] }
+            },
+            "not": { "required": ["value"] }
+        }"##
+    );
+
+    #[test]
+    fn report() {
+        assert_eq!(
+            GENERATION_REPORT,
+            [
+                "#/not: `not` is ignored",
+                "#/properties/value: `anyOf` is generated as `serde_json::Value`",
+                "#/properties/kind: `enum` is generated as `String`",
+                "#/properties/name: `maxLength`: 3 is not checked",
+            ]
+        );
+        let _ = Reported::default();
+    }
+}
+
+mod deny_unknown_fields {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: Strict
+        deny_unknown_fields: true
+        schema: r##"{
+            "type": "object",
+            "properties": {
+                "name": { "type": "string" },
+                "labels": {
+                    "type": "object",
+                    "properties": { "color": { "type": "string" } },
+                    "additionalProperties": { "type": "string" }
+                }
+            }
+        }"##
+    );
+
+    #[test]
+    fn deny_unknown_fields() {
+        assert!(serde_json::from_str::<Strict>(r#"{"name":"a","other":1}"#).is_err());
+        let strict: Strict =
+            serde_json::from_str(r#"{"name":"a","labels":{"color":"red","size":"xl"}}"#).unwrap();
+        let labels = strict.labels.unwrap();
+        assert_eq!(labels.additional_properties["size"], "xl");
+    }
+}
+
+mod generate_file {
+    use serde_derive::{Deserialize, Serialize};
+
+    // The file is not committed, as its formatting depends on whether
+    // rustfmt is installed
+    schemafy::generate_file!(
+        root: Nested
+        input: "tests/nested.json"
+        output: "tests/generated/nested.rs"
+    );
+
+    #[test]
+    fn generate_file() {
+        let source = std::fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/generated/nested.rs"
+        ))
+        .unwrap();
+        assert!(source.starts_with("// Generated by `schemafy::generate_file!` from `nested.json`"));
+        assert!(source.contains("pub struct Defnested {"));
+        let nested: Defnested = serde_json::from_str(r#"{ "append": "abc" }"#).unwrap();
+        assert_eq!(nested.append.as_deref(), Some("abc"));
+    }
+}
+
+/// Generated with `schemafy gen --split --root Catalog tests/split.json -o tests/generated/split`
+#[path = "generated/split/mod.rs"]
+#[allow(dead_code)]
+mod split;
+
+#[test]
+fn split_modules() {
+    let catalog: split::Catalog =
+        serde_json::from_str(r#"{"orders":[{"status":"open","lines":[{"quantity":2}]}]}"#).unwrap();
+    let order = &catalog.orders.unwrap()[0];
+    assert_eq!(order.status, split::Status::Open);
+    assert_eq!(order.lines.as_ref().unwrap()[0].quantity, 2);
+}
+
+mod eq_hash {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: Catalog
+        schema: r##"{
+            "type": "object",
+            "properties": {
+                "entries": {
+                    "type": "array",
+                    "items": { "$ref": "#/definitions/entry" }
+                },
+                "price": { "type": "number" }
+            },
+            "definitions": {
+                "entry": {
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "integer" },
+                        "tags": { "type": "array", "items": { "type": "string" } },
+                        "children": {
+                            "type": "array",
+                            "items": { "$ref": "#/definitions/entry" }
+                        },
+                        "kind": { "type": "string", "enum": ["file", "dir"] }
+                    },
+                    "required": ["id"]
+                },
+                "measure": {
+                    "type": "object",
+                    "properties": { "value": { "type": "number" } }
+                }
+            }
+        }"##
+    );
+
+    fn is_eq_hash<T: Eq + std::hash::Hash>() {}
+
+    #[test]
+    fn eq_hash() {
+        is_eq_hash::<Entry>();
+        let entry: Entry = serde_json::from_str(r#"{"id":1,"kind":"dir"}"#).unwrap();
+        let set = std::collections::HashSet::from([entry.clone()]);
+        assert!(set.contains(&entry));
+    }
+}
+
+mod derive_ord {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: Records
+        derive_ord: true
+        schema: r##"{
+            "type": "array",
+            "items": { "$ref": "#/definitions/record" },
+            "definitions": {
+                "record": {
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string" },
+                        "score": { "type": "number" }
+                    },
+                    "required": ["name", "score"]
+                },
+                "key": {
+                    "type": "object",
+                    "properties": { "id": { "type": "integer" } },
+                    "required": ["id"]
+                }
+            }
+        }"##
+    );
+
+    fn is_ord<T: Ord>() {}
+
+    #[test]
+    fn derive_ord() {
+        is_ord::<Key>();
+        let mut records: Records =
+            serde_json::from_str(r#"[{"name":"b","score":1.5},{"name":"a","score":2}]"#).unwrap();
+        records.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(records[0].name, "a");
+    }
+}
+
+mod schema_defaults {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: Settings
+        schema: r##"{
+            "type": "object",
+            "properties": {
+                "name": { "type": "string", "default": "main" },
+                "retries": { "type": "integer", "default": 3 },
+                "ratio": { "type": "number", "default": 0.5 },
+                "verbose": { "type": "boolean", "default": true },
+                "level": { "$ref": "#/definitions/level", "default": "warn" },
+                "tags": { "type": "array", "items": { "type": "string" } }
+            },
+            "required": ["name"],
+            "definitions": {
+                "level": { "type": "string", "enum": ["info", "warn"] }
+            }
+        }"##
+    );
+
+    #[test]
+    fn schema_defaults() {
+        let settings = Settings::default();
+        assert_eq!(settings.name, "main");
+        assert_eq!(settings.retries, Some(3));
+        assert_eq!(settings.ratio, Some(0.5));
+        assert_eq!(settings.verbose, Some(true));
+        assert_eq!(settings.level, Some(Level::Warn));
+        assert_eq!(settings.tags, None);
+    }
+}
+
+mod builders {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: Request
+        builders: true
+        schema: r##"{
+            "type": "object",
+            "properties": {
+                "id": { "type": "integer" },
+                "name": { "type": "string" },
+                "limit": { "type": "integer", "default": 10 },
+                "tags": { "type": "array", "items": { "type": "string" } },
+                "note": { "type": "string" }
+            },
+            "required": ["id", "name", "limit"],
+            "additionalProperties": { "type": "string" }
+        }"##
+    );
+
+    #[test]
+    fn builders() {
+        let request = Request::builder()
+            .id(1)
+            .name("a")
+            .tags(vec!["x".to_string()])
+            .build()
+            .unwrap();
+        assert_eq!(request.id, 1);
+        assert_eq!(request.name, "a");
+        assert_eq!(request.limit, 10);
+        assert_eq!(request.tags, Some(vec!["x".to_string()]));
+        assert_eq!(request.note, None);
+
+        let err = Request::builder().id(1).build().unwrap_err();
+        assert_eq!(err.to_string(), "missing field `name`");
+    }
+}
+
+mod constructors {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: Point
+        constructors: true
+        schema: r##"{
+            "type": "object",
+            "properties": {
+                "x": { "type": "integer" },
+                "y": { "type": "integer" },
+                "label": { "type": "string" },
+                "scale": { "type": "number", "default": 1.0 }
+            },
+            "required": ["x", "y"]
+        }"##
+    );
+
+    #[test]
+    fn constructors() {
+        let point = Point::new(1, 2);
+        assert_eq!((point.x, point.y), (1, 2));
+        assert_eq!(point.label, None);
+        assert_eq!(point.scale, Some(1.0));
+    }
+}
+
+mod non_exhaustive {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: Open
+        non_exhaustive: true
+        schema: r##"{
+            "type": "object",
+            "properties": {
+                "closed": { "$ref": "#/definitions/closed" },
+                "kind": { "$ref": "#/definitions/kind" }
+            },
+            "definitions": {
+                "kind": { "type": "string", "enum": ["a", "b"] },
+                "closed": {
+                    "type": "object",
+                    "properties": { "x": { "type": "integer" } },
+                    "additionalProperties": false
+                }
+            }
+        }"##
+    );
+
+    #[test]
+    fn non_exhaustive() {
+        let open: Open = serde_json::from_str(r#"{"closed":{"x":1},"kind":"a"}"#).unwrap();
+        assert_eq!(open.closed.unwrap().x, Some(1));
+        assert_eq!(open.kind, Some(Kind::A));
+    }
+}
+
+mod deprecated {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: Api
+        builders: true
+        constructors: true
+        validate: true
+        schema: r##"{
+            "type": "object",
+            "properties": {
+                "old": {
+                    "type": "string",
+                    "description": "Use `new` instead.",
+                    "deprecated": true
+                },
+                "new": { "type": "string" },
+                "legacy": { "$ref": "#/definitions/legacy" },
+                "level": { "$ref": "#/definitions/level" }
+            },
+            "definitions": {
+                "legacy": {
+                    "type": "object",
+                    "properties": { "x": { "type": "integer" } },
+                    "x-deprecated": "Removed in v2."
+                },
+                "level": {
+                    "type": "string",
+                    "enum": ["low", "high"],
+                    "x-deprecated": true
+                }
+            }
+        }"##
+    );
+
+    #[test]
+    #[allow(deprecated)]
+    fn deprecated() {
+        let api: Api =
+            serde_json::from_str(r#"{"old":"a","legacy":{"x":1},"level":"low"}"#).unwrap();
+        assert_eq!(api.old.as_deref(), Some("a"));
+        assert_eq!(api.legacy.unwrap().x, Some(1));
+        assert_eq!(api.level, Some(Level::Low));
+    }
+}
+
+mod read_write_only {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: User
+        read_write_only: true
+        schema: r##"{
+            "type": "object",
+            "properties": {
+                "id": { "type": "integer", "readOnly": true },
+                "name": { "type": "string" },
+                "password": { "type": "string", "writeOnly": true }
+            },
+            "required": ["name"]
+        }"##
+    );
+
+    #[test]
+    fn read_write_only() {
+        let user: User =
+            serde_json::from_str(r#"{"id":1,"name":"a","password":"secret"}"#).unwrap();
+        assert_eq!(user.id, Some(1));
+        assert_eq!(user.password, None);
+        let user = User {
+            id: Some(1),
+            name: "a".into(),
+            password: Some("secret".into()),
+        };
+        assert_eq!(
+            serde_json::to_string(&user).unwrap(),
+            r#"{"name":"a","password":"secret"}"#
+        );
+    }
+}
+
+mod borrowed_strings {
+    use serde_derive::{Deserialize, Serialize};
+    use std::borrow::Cow;
+
+    schemafy::schemafy!(
+        root: Document
+        borrowed_strings: true
+        builders: true
+        validate: true
+        schema: r##"{
+            "type": "object",
+            "properties": {
+                "title": { "type": "string", "default": "untitled" },
+                "sections": {
+                    "type": "array",
+                    "items": { "$ref": "#/definitions/section" }
+                },
+                "count": { "type": "integer" }
+            },
+            "definitions": {
+                "section": {
+                    "type": "object",
+                    "properties": {
+                        "heading": { "type": "string" },
+                        "parent": { "$ref": "#/definitions/section" }
+                    },
+                    "required": ["heading"]
+                }
+            }
+        }"##
+    );
+
+    #[test]
+    fn borrowed_strings() {
+        let json = r#"{"title":"a","sections":[{"heading":"b"},{"heading":"c\n"}]}"#;
+        let document: Document<'_> = serde_json::from_str(json).unwrap();
+        assert!(matches!(document.title, Some(Cow::Borrowed("a"))));
+        let sections = document.sections.unwrap();
+        assert!(matches!(sections[0].heading, Cow::Borrowed("b")));
+        // Escaped strings can not be borrowed
+        assert!(matches!(sections[1].heading, Cow::Owned(_)));
+
+        let section = Section::builder().heading("d").build().unwrap();
+        assert_eq!(section.heading, "d");
+        assert_eq!(Document::default().title.as_deref(), Some("untitled"));
+    }
+}
+
+schemafy::schemafy!(
+    root: Settings
+    mod: no_std_types
+    no_std: true
+    schema: r##"{
+        "type": "object",
+        "properties": {
+            "name": { "type": "string", "default": "default" },
+            "mode": { "$ref": "#/definitions/mode" },
+            "labels": { "type": "object", "additionalProperties": { "type": "string" } },
+            "next": { "$ref": "#/definitions/step" }
+        },
+        "definitions": {
+            "mode": { "type": "string", "enum": ["fast", "slow"] },
+            "step": {
+                "type": "object",
+                "properties": { "next": { "$ref": "#/definitions/step" } }
+            }
+        }
+    }"##
+);
+
+#[test]
+fn no_std() {
+    use no_std_types::{Mode, Settings};
+
+    let settings: Settings =
+        serde_json::from_str(r#"{"mode": "slow", "labels": {"a": "b"}, "next": {}}"#).unwrap();
+    assert_eq!(settings.mode, Some(Mode::Slow));
+    assert_eq!(settings.labels.unwrap()["a"], "b");
+    assert!(settings.next.is_some());
+    assert_eq!("fast".parse::<Mode>().unwrap().to_string(), "fast");
+    assert_eq!(Settings::default().name.as_deref(), Some("default"));
+}
+
+mod raw_value {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: Envelope
+        raw_value: true
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "kind": { "type": "string" },
+                "payload": {},
+                "extra": { "type": "object", "additionalProperties": true }
+            },
+            "required": ["kind", "payload"]
+        }"#
+    );
+
+    #[test]
+    fn raw_value() {
+        let json = r#"{"extra":{"c":null},"kind":"a","payload":{ "b": [1,  2.50] }}"#;
+        let envelope: Envelope = serde_json::from_str(json).unwrap();
+        assert_eq!(envelope.payload.get(), r#"{ "b": [1,  2.50] }"#);
+        assert_eq!(envelope.extra.as_ref().unwrap()["c"].get(), "null");
+        assert_eq!(serde_json::to_string(&envelope).unwrap(), json);
+        assert_eq!(envelope.clone(), envelope);
+        // Compared by their text, so they can still be hashed
+        let set = std::collections::HashSet::from([envelope]);
+        assert_eq!(set.len(), 1);
+    }
+}
+
+mod nullable_fields {
+    use schemafy_core::{validation::Validate, Nullable};
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: UserPatch
+        nullable_fields: true
+        validate: true
+        builders: true
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "id": { "type": "integer" },
+                "nickname": { "type": ["string", "null"], "maxLength": 3 },
+                "age": { "type": ["integer", "null"], "default": 18 },
+                "email": { "type": "string" }
+            },
+            "required": ["id"]
+        }"#
+    );
+
+    #[test]
+    fn nullable_fields() {
+        let patch: UserPatch = serde_json::from_str(r#"{"id": 1, "nickname": null}"#).unwrap();
+        assert_eq!(patch.nickname, Nullable::Null);
+        assert_eq!(patch.age, Nullable::Absent);
+        assert_eq!(patch.email, None);
+        let patch: UserPatch = serde_json::from_str(r#"{"id": 1}"#).unwrap();
+        assert_eq!(patch.nickname, Nullable::Absent);
+        assert_eq!(serde_json::to_string(&patch).unwrap(), r#"{"id":1}"#);
+
+        let patch = UserPatch::builder().id(1).nickname("abcd").build().unwrap();
+        assert_eq!(patch.nickname, Nullable::Value("abcd".to_string()));
+        // The `default` of the schema
+        assert_eq!(patch.age, Nullable::Value(18));
+        assert!(patch.validate().is_err());
+    }
+}
+
+mod x_flatten {
+    use serde_derive::{Deserialize, Serialize};
+    use std::convert::TryFrom;
+
+    schemafy::schemafy!(
+        root: Page
+        try_from_value: true
+        schema: r##"{
+            "type": "object",
+            "properties": {
+                "title": { "type": "string" },
+                "meta": { "$ref": "#/definitions/meta", "x-flatten": true }
+            },
+            "required": ["title", "meta"],
+            "definitions": {
+                "meta": {
+                    "type": "object",
+                    "properties": {
+                        "author": { "type": "string" },
+                        "year": { "type": "integer" }
+                    },
+                    "required": ["author"]
+                }
+            }
+        }"##
+    );
+
+    #[test]
+    fn x_flatten() {
+        let json = r#"{"author":"a","title":"t","year":2000}"#;
+        let page: Page = serde_json::from_str(json).unwrap();
+        assert_eq!(page.meta.author, "a");
+        assert_eq!(page.meta.year, Some(2000));
+        assert_eq!(
+            serde_json::to_string(&page).unwrap(),
+            r#"{"author":"a","year":2000,"title":"t"}"#
+        );
+        assert!(Page::try_from(serde_json::json!({ "title": "t", "author": "a" })).is_ok());
+        assert!(Page::try_from(serde_json::json!({ "title": "t" })).is_err());
+    }
+}
+
+mod read_write_variants {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: Account
+        read_write_variants: true
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "id": { "type": "integer", "readOnly": true },
+                "name": { "type": "string" },
+                "password": { "type": "string", "writeOnly": true }
+            },
+            "required": ["id", "name"]
+        }"#
+    );
+
+    #[test]
+    fn read_write_variants() {
+        let account = Account {
+            id: 1,
+            name: "a".into(),
+            password: Some("secret".into()),
+        };
+        let read = AccountRead::from(account.clone());
+        assert_eq!(
+            serde_json::to_string(&read).unwrap(),
+            r#"{"id":1,"name":"a"}"#
+        );
+        let write = AccountWrite::from(account);
+        assert_eq!(
+            serde_json::to_string(&write).unwrap(),
+            r#"{"name":"a","password":"secret"}"#
+        );
+        let write: AccountWrite = serde_json::from_str(r#"{"name":"b"}"#).unwrap();
+        assert_eq!(write.password, None);
+    }
+}
+
+mod one_or_many_arrays {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: Tags
+        one_or_many_arrays: true
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "tags": {
+                    "anyOf": [
+                        { "type": "string" },
+                        { "type": "array", "items": { "type": "string" } }
+                    ]
+                }
+            }
+        }"#
+    );
+
+    #[test]
+    fn one_or_many_arrays() {
+        let tags: Tags = serde_json::from_str(r#"{"tags":"a"}"#).unwrap();
+        assert_eq!(tags.tags, vec!["a".to_string()]);
+        assert_eq!(serde_json::to_string(&tags).unwrap(), r#"{"tags":["a"]}"#);
+        let tags: Tags = serde_json::from_str(r#"{"tags":null}"#).unwrap();
+        assert!(tags.tags.is_empty());
+    }
+}
+
+mod x_empty_string_as_none {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: Address
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "city": { "type": "string", "x-empty-string-as-none": true },
+                "street": { "type": "string", "x-empty-string-as-none": true }
+            },
+            "required": ["street"]
+        }"#
+    );
+
+    #[test]
+    fn x_empty_string_as_none() {
+        let address: Address = serde_json::from_str(r#"{"city":"","street":""}"#).unwrap();
+        assert_eq!(address.city, None);
+        assert_eq!(address.street, None);
+        assert_eq!(serde_json::to_string(&address).unwrap(), r#"{"street":""}"#);
+        let address: Address = serde_json::from_str(r#"{"street":"Main"}"#).unwrap();
+        assert_eq!(address.street.as_deref(), Some("Main"));
+    }
+}
+
+mod x_stringified {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: Order
+        formats { "int64" => "i64" }
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "id": { "type": "string", "format": "int64" },
+                "price": { "type": "number", "x-stringified": true },
+                "quantity": { "type": "integer", "x-stringified": true },
+                "discount": { "type": ["number", "null"], "x-stringified": true }
+            },
+            "required": ["id", "price"]
+        }"#
+    );
+
+    #[test]
+    fn x_stringified() {
+        let order: Order = serde_json::from_str(r#"{"id":"7","price":"1.5"}"#).unwrap();
+        assert_eq!(
+            order,
+            Order {
+                discount: None,
+                id: 7,
+                price: 1.5,
+                quantity: None,
+            }
+        );
+        assert_eq!(
+            serde_json::to_string(&order).unwrap(),
+            r#"{"id":"7","price":"1.5"}"#
+        );
+        let order: Order = serde_json::from_str(r#"{"id":7,"price":2,"quantity":"3"}"#).unwrap();
+        assert_eq!(order.quantity, Some(3));
+        let order: Order = serde_json::from_str(r#"{"id":7,"price":2,"discount":"0.5"}"#).unwrap();
+        assert_eq!(order.discount, Some(0.5));
+        assert!(serde_json::from_str::<Order>(r#"{"id":"a","price":"1"}"#).is_err());
+    }
+}
+
+mod format_modules {
+    use serde_derive::{Deserialize, Serialize};
+    use std::time::{Duration, SystemTime};
+
+    schemafy::schemafy!(
+        root: Event
+        formats { "unix-time" => "std::time::SystemTime", "unix-time-ms" => "std::time::SystemTime" }
+        format_modules {
+            "unix-time" => "schemafy_core::timestamp::seconds",
+            "unix-time-ms" => "schemafy_core::timestamp::milliseconds",
+        }
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "created": { "type": "integer", "format": "unix-time" },
+                "updated": { "type": "integer", "format": "unix-time-ms" }
+            },
+            "required": ["created"]
+        }"#
+    );
+
+    #[test]
+    fn format_modules() {
+        let json = r#"{"created":1700000000,"updated":1700000000500}"#;
+        let event: Event = serde_json::from_str(json).unwrap();
+        let created = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        assert_eq!(event.created, created);
+        assert_eq!(event.updated, Some(created + Duration::from_millis(500)));
+        assert_eq!(serde_json::to_string(&event).unwrap(), json);
+        let event: Event = serde_json::from_str(r#"{"created":0}"#).unwrap();
+        assert_eq!(event.updated, None);
+    }
+}
+
+mod base64_bytes {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: Attachment
+        base64_bytes: true
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "content": { "type": "string", "format": "byte" },
+                "thumbnail": { "type": "string", "format": "byte" },
+                "preview": { "type": ["string", "null"], "format": "byte" }
+            },
+            "required": ["content"]
+        }"#
+    );
+
+    #[test]
+    fn base64_bytes() {
+        let attachment: Attachment = serde_json::from_str(r#"{"content":"aGk="}"#).unwrap();
+        assert_eq!(
+            attachment,
+            Attachment {
+                content: b"hi".to_vec(),
+                preview: None,
+                thumbnail: None,
+            }
+        );
+        assert_eq!(
+            serde_json::to_string(&attachment).unwrap(),
+            r#"{"content":"aGk="}"#
+        );
+        let attachment: Attachment =
+            serde_json::from_str(r#"{"content":"","thumbnail":"AAE"}"#).unwrap();
+        assert_eq!(attachment.thumbnail, Some(vec![0, 1]));
+        let attachment: Attachment =
+            serde_json::from_str(r#"{"content":"","preview":"aGk="}"#).unwrap();
+        assert_eq!(attachment.preview, Some(b"hi".to_vec()));
+        assert_eq!(
+            serde_json::to_string(&attachment).unwrap(),
+            r#"{"content":"","preview":"aGk="}"#
+        );
+        assert!(serde_json::from_str::<Attachment>(r#"{"content":"!"}"#).is_err());
+    }
+}
+
+mod x_kubernetes_int_or_string {
+    use schemafy_core::IntOrString;
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: ServicePort
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "port": { "type": "integer" },
+                "targetPort": {
+                    "anyOf": [{ "type": "integer" }, { "type": "string" }],
+                    "x-kubernetes-int-or-string": true
+                }
+            },
+            "required": ["port"]
+        }"#
+    );
+
+    #[test]
+    fn x_kubernetes_int_or_string() {
+        let port: ServicePort = serde_json::from_str(r#"{"port":80,"targetPort":"http"}"#).unwrap();
+        assert_eq!(port.targetPort, Some(IntOrString::from("http")));
+        let port: ServicePort = serde_json::from_str(r#"{"port":80,"targetPort":8080}"#).unwrap();
+        assert_eq!(port.targetPort, Some(IntOrString::Int(8080)));
+        assert_eq!(
+            serde_json::to_string(&port).unwrap(),
+            r#"{"port":80,"targetPort":8080}"#
+        );
+    }
+}
+
+mod x_map_or_list {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: Inventory
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "counts": {
+                    "type": "object",
+                    "additionalProperties": { "type": "integer" },
+                    "x-map-or-list": true
+                },
+                "items": {
+                    "type": "object",
+                    "additionalProperties": { "type": "string" },
+                    "x-map-or-list": true
+                }
+            },
+            "required": ["items"]
+        }"#
+    );
+
+    #[test]
+    fn x_map_or_list() {
+        let map: Inventory =
+            serde_json::from_str(r#"{"counts":{"a":1},"items":{"a":"apple"}}"#).unwrap();
+        assert_eq!(map.items["a"], "apple");
+        assert_eq!(map.counts.unwrap()["a"], 1);
+        let list: Inventory = serde_json::from_str(r#"{"items":["apple","pear"]}"#).unwrap();
+        assert_eq!(list.items["1"], "pear");
+        assert_eq!(list.counts, None);
+        assert_eq!(
+            serde_json::to_string(&list).unwrap(),
+            r#"{"items":{"0":"apple","1":"pear"}}"#
+        );
+    }
+}
+
+mod x_lenient {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: Legacy
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "active": { "type": "boolean", "x-lenient": true },
+                "count": { "type": "integer", "x-lenient": true }
+            },
+            "required": ["active"]
+        }"#
+    );
+
+    #[test]
+    fn x_lenient() {
+        let legacy: Legacy = serde_json::from_str(r#"{"active":"1","count":"7"}"#).unwrap();
+        assert_eq!(
+            legacy,
+            Legacy {
+                active: true,
+                count: Some(7),
+            }
+        );
+        assert_eq!(
+            serde_json::to_string(&legacy).unwrap(),
+            r#"{"active":true,"count":7}"#
+        );
+        let legacy: Legacy = serde_json::from_str(r#"{"active":"false"}"#).unwrap();
+        assert_eq!(legacy.count, None);
+    }
+}
+
+mod one_or_many_type {
+    use schemafy_core::OneOrMany;
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: Recipients
+        one_or_many_type: true
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "to": {
+                    "anyOf": [
+                        { "type": "string" },
+                        { "type": "array", "items": { "type": "string" } }
+                    ]
+                }
+            }
+        }"#
+    );
+
+    #[test]
+    fn one_or_many_type() {
+        for json in [r#"{"to":"a"}"#, r#"{"to":["a"]}"#] {
+            let recipients: Recipients = serde_json::from_str(json).unwrap();
+            assert_eq!(recipients.to.as_slice(), ["a"]);
+            assert_eq!(serde_json::to_string(&recipients).unwrap(), json);
+        }
+        let recipients: Recipients = serde_json::from_str("{}").unwrap();
+        assert_eq!(recipients.to, OneOrMany::default());
+    }
+}
+
+mod union_types {
+    use schemafy_core::{Either, Union3};
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: Setting
+        union_types: true
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "value": {
+                    "anyOf": [
+                        { "type": "integer" },
+                        { "type": "string" },
+                        { "type": "object", "properties": { "name": { "type": "string" } } }
+                    ]
+                },
+                "fallback": { "type": ["boolean", "string", "null"] }
+            },
+            "required": ["value"]
+        }"#
+    );
+
+    #[test]
+    fn union_types() {
+        let setting: Setting = serde_json::from_str(r#"{"value":1,"fallback":"off"}"#).unwrap();
+        assert_eq!(setting.value, Union3::A(1));
+        assert_eq!(setting.fallback, Some(Either::B("off".into())));
+        let setting: Setting = serde_json::from_str(r#"{"value":{"name":"a"}}"#).unwrap();
+        assert_eq!(
+            setting.value,
+            Union3::C(SettingVariant2Value {
+                name: Some("a".into())
+            })
+        );
+        assert_eq!(
+            serde_json::to_string(&setting).unwrap(),
+            r#"{"value":{"name":"a"}}"#
+        );
+        // `Eq` and `Hash` are still derived
+        let _ = std::collections::HashSet::from([setting]);
+    }
+}
+
+mod type_name_collisions {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: Collisions
+        schema: r##"{
+            "type": "object",
+            "properties": {
+                "lower": { "$ref": "#/definitions/fooBar" },
+                "upper": { "$ref": "#/definitions/FooBar" }
+            },
+            "definitions": {
+                "FooBar": { "type": "object", "properties": { "a": { "type": "integer" } } },
+                "fooBar": { "type": "object", "properties": { "b": { "type": "string" } } },
+                "foo_bar": { "type": "string", "enum": ["x", "y"] }
+            }
+        }"##
+    );
+
+    #[test]
+    fn type_name_collisions() {
+        let collisions: Collisions =
+            serde_json::from_str(r#"{"lower":{"b":"x"},"upper":{"a":1}}"#).unwrap();
+        assert_eq!(
+            collisions,
+            Collisions {
+                lower: Some(FooBar2 {
+                    b: Some("x".into())
+                }),
+                upper: Some(FooBar { a: Some(1) }),
+            }
+        );
+        assert_eq!(FooBar3::X, serde_json::from_str(r#""x""#).unwrap());
+    }
+}
+
+mod dedup_types {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: Route
+        dedup_types: true
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "from": {
+                    "type": "object",
+                    "properties": { "lat": { "type": "integer" }, "lon": { "type": "integer" } }
+                },
+                "to": {
+                    "type": "object",
+                    "properties": { "lat": { "type": "integer" }, "lon": { "type": "integer" } }
+                }
+            },
+            "required": ["from", "to"]
+        }"#
+    );
+
+    #[test]
+    fn dedup_types() {
+        let route: Route =
+            serde_json::from_str(r#"{"from":{"lat":1},"to":{"lat":2,"lon":3}}"#).unwrap();
+        let to: RouteFrom = route.to.clone();
+        assert_eq!(to.lon, Some(3));
+        // The other name is an alias
+        let from: RouteTo = route.from;
+        assert_eq!(from.lat, Some(1));
+        assert_eq!(
+            std::any::type_name::<RouteTo>(),
+            std::any::type_name::<RouteFrom>()
+        );
+    }
+}
+
+mod title_names {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: Order
+        title_names: true
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "billing": {
+                    "title": "Postal address",
+                    "type": "object",
+                    "properties": { "city": { "type": "string" } }
+                },
+                "shipping": {
+                    "title": "Postal address",
+                    "type": "object",
+                    "properties": { "street": { "type": "string" } }
+                },
+                "payment": {
+                    "oneOf": [
+                        {
+                            "title": "Card",
+                            "type": "object",
+                            "properties": { "number": { "type": "string" } },
+                            "required": ["number"]
+                        },
+                        {
+                            "type": "object",
+                            "properties": { "iban": { "type": "string" } },
+                            "required": ["iban"]
+                        }
+                    ]
+                }
+            }
+        }"#
+    );
+
+    #[test]
+    fn title_names() {
+        let order: Order = serde_json::from_str(
+            r#"{"billing":{"city":"a"},"shipping":{"street":"b"},"payment":{"number":"1"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            order.billing,
+            Some(PostalAddress {
+                city: Some("a".into())
+            })
+        );
+        // The title is taken, so the usual name is kept
+        assert_eq!(
+            order.shipping,
+            Some(OrderShipping {
+                street: Some("b".into())
+            })
+        );
+        assert_eq!(
+            order.payment,
+            Some(OrderPayment::Variant0(Card { number: "1".into() }))
+        );
+        let _ = OrderPaymentVariant1 { iban: "x".into() };
+    }
+}
+
+mod pointer_names {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: Root
+        pointer_names: true
+        schema: r##"{
+            "type": "object",
+            "properties": {
+                "config": { "$ref": "#/definitions/config" }
+            },
+            "definitions": {
+                "config": {
+                    "type": "object",
+                    "properties": {
+                        "servers": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "tls": {
+                                        "type": "object",
+                                        "properties": { "cert": { "type": "string" } }
+                                    }
+                                }
+                            }
+                        },
+                        "labels": {
+                            "type": "object",
+                            "additionalProperties": {
+                                "type": "object",
+                                "properties": { "value": { "type": "string" } }
+                            }
+                        }
+                    }
+                }
+            }
+        }"##
+    );
+
+    #[test]
+    fn pointer_names() {
+        let root: Root = serde_json::from_str(
+            r#"{"config":{"servers":[{"tls":{"cert":"a"}}],"labels":{"x":{"value":"b"}}}}"#,
+        )
+        .unwrap();
+        let config = root.config.unwrap();
+        let server: &ConfigServersItem = &config.servers.as_ref().unwrap()[0];
+        let tls: &ConfigServersItemTls = server.tls.as_ref().unwrap();
+        assert_eq!(tls.cert.as_deref(), Some("a"));
+        let label: &ConfigLabelsValue = &config.labels.as_ref().unwrap()["x"];
+        assert_eq!(label.value.as_deref(), Some("b"));
+    }
+}
+
+mod rename {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: Event
+        rename {
+            "definitions/foo-bar" => "Payload",
+            "Event.type" => "kind",
+            "Payload.x-id" => "id",
+        }
+        schema: r##"{
+            "type": "object",
+            "properties": {
+                "type": { "type": "string" },
+                "payload": { "$ref": "#/definitions/foo-bar" }
+            },
+            "required": ["type"],
+            "definitions": {
+                "foo-bar": { "type": "object", "properties": { "x-id": { "type": "integer" } } }
+            }
+        }"##
+    );
+
+    #[test]
+    fn rename() {
+        let json = r#"{"payload":{"x-id":1},"type":"a"}"#;
+        let event: Event = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            event,
+            Event {
+                kind: "a".into(),
+                payload: Some(Payload { id: Some(1) }),
+            }
+        );
+        assert_eq!(serde_json::to_string(&event).unwrap(), json);
+    }
+}
+
+mod acronyms {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: Root
+        acronyms: true
+        schema: r##"{
+            "type": "object",
+            "properties": {
+                "server": { "$ref": "#/definitions/HTTPServerURL" }
+            },
+            "definitions": {
+                "HTTPServerURL": {
+                    "type": "object",
+                    "properties": {
+                        "userID": { "type": "string" },
+                        "IPAddresses": { "type": "array", "items": { "type": "string" } }
+                    }
+                }
+            }
+        }"##
+    );
+
+    #[test]
+    fn acronyms() {
+        let json = r#"{"server":{"IPAddresses":["a"],"userID":"b"}}"#;
+        let root: Root = serde_json::from_str(json).unwrap();
+        let server: &HttpServerUrl = root.server.as_ref().unwrap();
+        assert_eq!(server.userId.as_deref(), Some("b"));
+        assert_eq!(server.ipAddresses.as_deref(), Some(&["a".to_string()][..]));
+        assert_eq!(serde_json::to_string(&root).unwrap(), json);
+    }
+}
+
+mod unicode_names {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: Root
+        schema: r##"{
+            "type": "object",
+            "properties": {
+                "3dModel": { "$ref": "#/definitions/3dModel" },
+                "größe": { "type": "integer" },
+                "x²": { "type": "integer" },
+                "€": { "type": "integer" },
+                "名前": { "type": "string" }
+            },
+            "definitions": {
+                "3dModel": {
+                    "type": "object",
+                    "properties": { "unit": { "$ref": "#/definitions/unit" } }
+                },
+                "unit": { "type": "string", "enum": ["3d", "Ångström"] }
+            }
+        }"##
+    );
+
+    #[test]
+    fn unicode_names() {
+        let json = r#"{"3dModel":{"unit":"Ångström"},"größe":1,"x²":2,"€":3,"名前":"a"}"#;
+        let root: Root = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            root,
+            Root {
+                _3dModel: Some(_3dModel {
+                    unit: Some(Unit::Angstroem),
+                }),
+                groesse: Some(1),
+                x_u00b2: Some(2),
+                _u20ac: Some(3),
+                名前: Some("a".into()),
+            }
+        );
+        assert_eq!(serde_json::to_string(&root).unwrap(), json);
+        assert_eq!(Unit::_3d.as_str(), "3d");
+    }
+}
+
+mod raw_identifiers {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: Token
+        raw_identifiers: true
+        builders: true
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "type": { "type": "string" },
+                "ref": { "type": "string" },
+                "self": { "type": "string" }
+            },
+            "required": ["type"]
+        }"#
+    );
+
+    #[test]
+    fn raw_identifiers() {
+        let json = r#"{"ref":"a","self":"b","type":"c"}"#;
+        let token: Token = serde_json::from_str(json).unwrap();
+        assert_eq!(token.r#type, "c");
+        assert_eq!(token.r#ref.as_deref(), Some("a"));
+        assert_eq!(token.self_.as_deref(), Some("b"));
+        assert_eq!(serde_json::to_string(&token).unwrap(), json);
+        let built = Token::builder().r#type("c").build().unwrap();
+        assert_eq!(built.r#type, "c");
+    }
+}
+
+mod field_collisions {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: Root
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "foo-bar": { "type": "string" },
+                "foo_bar": { "type": "integer" }
+            }
+        }"#
+    );
+
+    #[test]
+    fn field_collisions() {
+        let json = r#"{"foo-bar":"a","foo_bar":1}"#;
+        let root: Root = serde_json::from_str(json).unwrap();
+        assert_eq!(root.foo_bar.as_deref(), Some("a"));
+        assert_eq!(root.foo_bar_2, Some(1));
+        assert_eq!(serde_json::to_string(&root).unwrap(), json);
+    }
+}
+
+mod other_members_collision {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: Root
+        builders: true
+        constructors: true
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "additional_properties": { "type": "string" }
+            },
+            "additionalProperties": { "type": "integer" }
+        }"#
+    );
+
+    #[test]
+    fn other_members_collision() {
+        let json = r#"{"additional_properties":"a","b":1}"#;
+        let root: Root = serde_json::from_str(json).unwrap();
+        assert_eq!(root.additional_properties_2.as_deref(), Some("a"));
+        assert_eq!(root.additional_properties["b"], 1);
+        assert_eq!(serde_json::to_string(&root).unwrap(), json);
+    }
+}
+
+mod title_root {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        schema: r#"{
+            "title": "Server config",
+            "type": "object",
+            "properties": { "port": { "type": "integer" } },
+            "required": ["port"]
+        }"#
+    );
+
+    #[test]
+    fn title_root() {
+        let config: ServerConfig = serde_json::from_str(r#"{"port":80}"#).unwrap();
+        assert_eq!(config.port, 80);
+    }
+}
+
+mod openapi {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        schema: r##"{
+            "openapi": "3.0.3",
+            "info": { "title": "Pet store", "version": "1.0.0" },
+            "paths": {},
+            "components": {
+                "schemas": {
+                    "Pet": {
+                        "oneOf": [
+                            { "$ref": "#/components/schemas/Cat" },
+                            { "$ref": "#/components/schemas/Dog" }
+                        ],
+                        "discriminator": {
+                            "propertyName": "petType",
+                            "mapping": { "kitten": "#/components/schemas/Cat" }
+                        }
+                    },
+                    "Cat": {
+                        "type": "object",
+                        "properties": {
+                            "petType": { "type": "string", "enum": ["kitten"] },
+                            "name": { "type": "string", "nullable": true },
+                            "owner": {
+                                "allOf": [{ "$ref": "#/components/schemas/Owner" }],
+                                "nullable": true
+                            }
+                        },
+                        "required": ["petType", "name", "owner"]
+                    },
+                    "Dog": {
+                        "type": "object",
+                        "properties": {
+                            "petType": { "type": "string" },
+                            "barks": { "type": "boolean" }
+                        },
+                        "required": ["petType", "barks"]
+                    },
+                    "Owner": {
+                        "type": "object",
+                        "properties": { "age": { "type": "integer", "exclusiveMinimum": 0 } }
+                    }
+                }
+            }
+        }"##
+    );
+
+    #[test]
+    fn openapi() {
+        let pet: Pet =
+            serde_json::from_str(r#"{"petType":"kitten","name":null,"owner":{"age":3}}"#).unwrap();
+        match pet {
+            Pet::Kitten(cat) => {
+                assert_eq!(cat.name, None);
+                assert_eq!(cat.owner.unwrap().age, Some(3));
+            }
+            Pet::Dog(_) => panic!("a cat is not a dog"),
+        }
+        let json = r#"{"barks":true,"petType":"Dog"}"#;
+        let pet: Pet = serde_json::from_str(json).unwrap();
+        assert!(matches!(pet, Pet::Dog(Dog { barks: true, .. })));
+        assert_eq!(serde_json::to_string(&pet).unwrap(), json);
+        assert!(serde_json::from_str::<Pet>(r#"{"petType":"dog","barks":true}"#).is_err());
+    }
+}
+
+mod swagger {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        schema: r##"{
+            "swagger": "2.0",
+            "info": { "title": "Pet store", "version": "1.0.0" },
+            "paths": {},
+            "definitions": {
+                "Pet": {
+                    "type": "object",
+                    "discriminator": "petType",
+                    "properties": {
+                        "petType": { "type": "string" },
+                        "owner": { "$ref": "#/definitions/Owner", "x-nullable": true },
+                        "tags": {
+                            "type": "array",
+                            "items": { "type": "string" },
+                            "collectionFormat": "csv"
+                        },
+                        "scores": {
+                            "type": "array",
+                            "items": { "type": "integer" },
+                            "collectionFormat": "pipes"
+                        }
+                    },
+                    "required": ["petType", "owner", "scores"]
+                },
+                "Owner": { "type": "object", "properties": { "name": { "type": "string" } } }
+            }
+        }"##
+    );
+
+    #[test]
+    fn swagger() {
+        let json = r#"{"owner":null,"petType":"cat","scores":"1|2","tags":"a,b"}"#;
+        let pet: Pet = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            pet,
+            Pet {
+                owner: None,
+                petType: "cat".into(),
+                scores: vec![1, 2],
+                tags: Some(vec!["a".into(), "b".into()]),
+            }
+        );
+        assert_eq!(serde_json::to_string(&pet).unwrap(), json);
+    }
+}
+
+mod nullable_keyword {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: Pet
+        schema: r##"{
+            "type": "object",
+            "properties": {
+                "name": { "type": "string", "nullable": true },
+                "owner": { "$ref": "#/definitions/Owner", "nullable": true },
+                "age": { "type": ["integer", "null"], "nullable": true }
+            },
+            "required": ["name", "owner"],
+            "definitions": {
+                "Owner": { "type": "object", "properties": { "name": { "type": "string" } } }
+            }
+        }"##
+    );
+
+    #[test]
+    fn nullable_keyword() {
+        let pet: Pet = serde_json::from_str(r#"{"name":null,"owner":{"name":"a"}}"#).unwrap();
+        assert_eq!(
+            pet,
+            Pet {
+                age: None,
+                name: None,
+                owner: Some(Owner {
+                    name: Some("a".into())
+                }),
+            }
+        );
+        let pet: Pet = serde_json::from_str(r#"{"age":3,"owner":null}"#).unwrap();
+        assert_eq!((pet.age, pet.name, pet.owner), (Some(3), None, None));
+    }
+}
+
+mod nullable_helpers {
+    use serde_derive::{Deserialize, Serialize};
+    use std::time::SystemTime;
+
+    schemafy::schemafy!(
+        root: Record
+        base64_bytes: true
+        formats { "unix-time" => "std::time::SystemTime" }
+        format_modules { "unix-time" => "schemafy_core::timestamp::seconds" }
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "active": { "type": "boolean", "nullable": true, "x-lenient": true },
+                "content": { "type": "string", "format": "byte", "nullable": true },
+                "count": { "type": "integer", "nullable": true, "x-stringified": true },
+                "created": { "type": "integer", "format": "unix-time", "nullable": true },
+                "tags": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "collectionFormat": "csv",
+                    "nullable": true
+                }
+            },
+            "required": ["active", "content", "count", "created", "tags"]
+        }"#
+    );
+
+    #[test]
+    fn nullable_helpers() {
+        let json = r#"{"active":"1","content":"aGk=","count":"5","created":0,"tags":"a,b"}"#;
+        let record: Record = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            record,
+            Record {
+                active: Some(true),
+                content: Some(b"hi".to_vec()),
+                count: Some(5),
+                created: Some(SystemTime::UNIX_EPOCH),
+                tags: Some(vec!["a".into(), "b".into()]),
+            }
+        );
+        assert_eq!(
+            serde_json::to_string(&record).unwrap(),
+            r#"{"active":true,"content":"aGk=","count":"5","created":0,"tags":"a,b"}"#
+        );
+        let json = r#"{"active":null,"content":null,"count":null,"created":null,"tags":null}"#;
+        let record: Record = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            record,
+            Record {
+                active: None,
+                content: None,
+                count: None,
+                created: None,
+                tags: None,
+            }
+        );
+        assert_eq!(serde_json::to_string(&record).unwrap(), json);
+    }
+}
+
+mod kubernetes_crd {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!("tests/kubernetes-crd.json");
+
+    #[test]
+    fn kubernetes_crd() {
+        let json = r#"{"apiVersion":"stable.example.com/v1","kind":"CronTab","spec":{"containers":[{"image":"b","name":"b"},{"name":"a"}],"port":"http","ports":[{"containerPort":80},{"containerPort":53,"protocol":"UDP"}],"replicas":2},"status":{"ready":true}}"#;
+        let cron_tab: CronTab = serde_json::from_str(json).unwrap();
+        let spec = cron_tab.spec.as_ref().unwrap();
+        assert_eq!(spec.containers["b"].image.as_deref(), Some("b"));
+        assert!(spec.ports.as_ref().unwrap()[&(53, Some("UDP".into()))]
+            .protocol
+            .is_some());
+        assert_eq!(
+            spec.port,
+            Some(schemafy_core::IntOrString::String("http".into()))
+        );
+        assert_eq!(spec.additional_properties["replicas"], 2);
+        assert_eq!(cron_tab.status.as_ref().unwrap()["ready"], true);
+        // List maps are written in the order of their keys
+        assert_eq!(
+            serde_json::to_string(&cron_tab).unwrap(),
+            json.replace(
+                r#"[{"image":"b","name":"b"},{"name":"a"}]"#,
+                r#"[{"name":"a"},{"image":"b","name":"b"}]"#
+            )
+            .replace(
+                r#"[{"containerPort":80},{"containerPort":53,"protocol":"UDP"}]"#,
+                r#"[{"containerPort":53,"protocol":"UDP"},{"containerPort":80}]"#
+            )
+        );
+        let duplicate = r#"{"spec":{"containers":[{"name":"a"},{"name":"a"}]}}"#;
+        assert!(serde_json::from_str::<CronTab>(duplicate).is_err());
+    }
+}
+
+mod list_map_keys {
+    use serde_derive::{Deserialize, Serialize};
+
+    schemafy::schemafy!(
+        root: Root
+        schema: r##"{
+            "type": "object",
+            "properties": {
+                "ports": {
+                    "type": "array",
+                    "x-kubernetes-list-type": "map",
+                    "x-kubernetes-list-map-keys": ["protocol"],
+                    "items": {
+                        "type": "object",
+                        "properties": { "protocol": { "$ref": "#/definitions/protocol" } },
+                        "required": ["protocol"]
+                    }
+                },
+                "weights": {
+                    "type": "array",
+                    "x-kubernetes-list-type": "map",
+                    "x-kubernetes-list-map-keys": ["weight"],
+                    "items": {
+                        "type": "object",
+                        "properties": { "weight": { "type": "number" } },
+                        "required": ["weight"]
+                    }
+                }
+            },
+            "definitions": {
+                "protocol": { "type": "string", "enum": ["TCP", "UDP"] }
+            }
+        }"##
+    );
+
+    #[test]
+    fn list_map_keys() {
+        let json = r#"{"ports":[{"protocol":"UDP"}],"weights":[{"weight":0.5},{"weight":0.5}]}"#;
+        let root: Root = serde_json::from_str(json).unwrap();
+        assert!(root.ports.unwrap().contains_key(&Protocol::Udp));
+        // Numbers can not be ordered, so they are left in a list
+        assert_eq!(root.weights.unwrap().len(), 2);
+    }
+}